@@ -1,11 +1,12 @@
 use core::fmt;
 use std::fs;
+use std::sync::Arc;
 
 use crate::{
     benchmark::{
         epd_reader::{self, EpdRead},
         scoring,
-    }, entity::engine::component::config::config, ui::notation::{epd, san}
+    }, entity::engine::component::{config::config, evaluation::evaluator}, ui::notation::{epd, san}
 };
 
 use super::{epd_reader::EpdFileReaderError, scoring::EpdScore};
@@ -97,7 +98,10 @@ impl EpdData {
         &self.epds
     }
 }
-pub fn benchmark(epd_folder: &str) -> Result<Vec<EpdData>, EpdFileReaderError> {
+pub fn benchmark(
+    epd_folder: &str,
+    evaluator: Arc<dyn evaluator::Evaluator>,
+) -> Result<Vec<EpdData>, EpdFileReaderError> {
     let data_all_files_or_error = read_epds_from_folder(epd_folder);
     let conf_depth = 3;
     let max_time_sec = 3;
@@ -106,7 +110,7 @@ pub fn benchmark(epd_folder: &str) -> Result<Vec<EpdData>, EpdFileReaderError> {
     let mut results: Vec<EpdResult> = vec![];
     if let Ok(data_all_files) = &data_all_files_or_error {
         for data_per_file in data_all_files {
-            let epd_with_score = scoring::scoring(data_per_file, &engine_conf, &constraint);
+            let epd_with_score = scoring::scoring(data_per_file, &engine_conf, &evaluator, &constraint);
             let epd_result = EpdResult::new(data_per_file.file_path(), epd_with_score);
             results.push(epd_result);
         }
@@ -114,6 +118,7 @@ pub fn benchmark(epd_folder: &str) -> Result<Vec<EpdData>, EpdFileReaderError> {
         println!("Benchmark results");
         println!("----------------");
         println!("am should be 0, bm should be 1");
+        println!("evaluator: {}", evaluator.name());
         for result in results {
             println!("{}", result);
         }
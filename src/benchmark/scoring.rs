@@ -9,6 +9,7 @@ use std::{
 use actix::Actor;
 
 use crate::entity::engine::{actor::engine_dispatcher as dispatcher, component::config::config::{self, IDDFSConfig}};
+use crate::entity::engine::component::evaluation::evaluator;
 use crate::{
     entity::{
         engine::component::engine_iddfs,
@@ -91,16 +92,25 @@ impl std::fmt::Display for EpdScore {
     }
 }
 
-fn init_game_params(conf: &config::IDDFSConfig) -> engine_iddfs::EngineIddfs {
+fn init_game_params(
+    conf: &config::IDDFSConfig,
+    evaluator: &Arc<dyn evaluator::Evaluator>,
+) -> engine_iddfs::EngineIddfs {
     let game_manager = game_manager::GameManager::new(None);
     let mut engine_player =
         engine_iddfs::EngineIddfs::new(None, game_manager.zobrist_table(), conf);
     engine_player.set_id_number("computer");
+    engine_player.set_evaluator(evaluator.clone());
     engine_player
 }
-pub fn scoring<'a>(epd_data: &'a launcher::EpdData, engine_conf: &IDDFSConfig, constraint: &Constraint) -> Vec<(&'a epd::Epd, EpdScore)> {
+pub fn scoring<'a>(
+    epd_data: &'a launcher::EpdData,
+    engine_conf: &IDDFSConfig,
+    evaluator: &Arc<dyn evaluator::Evaluator>,
+    constraint: &Constraint,
+) -> Vec<(&'a epd::Epd, EpdScore)> {
     let zobrist_table = zobrist::Zobrist::new();
-    let engine = init_game_params(engine_conf);
+    let engine = init_game_params(engine_conf, evaluator);
 
     let epd_evals: Vec<EpdEval> = epd_data
         .epds()
@@ -163,7 +173,8 @@ fn epd_eval(
     let mut b_move_opt: Option<bitboard::BitBoardMove> = None;
     let start = std::time::Instant::now();
     while !stop_flag.load(Ordering::SeqCst) {
-        let b_move = engine_iddfs.iddfs_init(&game, self_actor.clone(), None, &stop_flag_clone);
+        let b_move =
+            engine_iddfs.iddfs_init(&game, self_actor.clone(), None, None, &stop_flag_clone);
         b_move_opt = Some(b_move);
         stop_flag_clone.store(true, Ordering::SeqCst); // Set the flag to true
     }
@@ -0,0 +1,312 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use actix::Addr;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::entity::engine::actor::engine_dispatcher as dispatcher;
+use crate::entity::engine::component::engine_iddfs;
+use crate::entity::game::component::bitboard::zobrist;
+use crate::entity::game::component::{game_state, square};
+use crate::ui::notation::fen::{self, EncodeUserInput};
+
+// positions with a score above this value (mate, won endgame) are not recorded
+const MAX_ABS_SCORE_RECORDED: i32 = 20_000;
+const PROGRESS_FILE_SUFFIX: &str = ".progress";
+
+// Self-play training data generation.
+// Output: one line per quiet position: "<fen> | <score> | <result>"
+//   score: search score from white perspective (1000 = 1 pawn)
+//   result: 1.0 white wins, 0.5 draw, 0.0 black wins
+// Game i is played with the seed 'seed + i' so that a game can be replayed.
+// Completed games are listed in '<output>.progress' so that a run can be resumed: each line
+// is '<game> <size of the output>', the output is truncated to the last size when resuming.
+#[derive(Debug, Clone)]
+pub struct DatagenConf {
+    pub n_games: u64,
+    pub depth: u8,
+    pub max_nodes_opt: Option<u64>,
+    pub random_plies: u8,
+    pub max_plies: u16,
+    pub seed: u64,
+    pub n_threads: usize,
+    pub output: String,
+}
+impl DatagenConf {
+    pub fn new(n_games: u64, depth: u8, seed: u64, output: String) -> Self {
+        Self {
+            n_games,
+            depth,
+            max_nodes_opt: None,
+            random_plies: 8,
+            max_plies: 400,
+            seed,
+            n_threads: num_cpus::get(),
+            output,
+        }
+    }
+    pub fn progress_file(&self) -> String {
+        format!("{}{}", self.output, PROGRESS_FILE_SUFFIX)
+    }
+}
+impl fmt::Display for DatagenConf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "n_games: {}", self.n_games)?;
+        writeln!(f, "depth: {}", self.depth)?;
+        writeln!(f, "max_nodes: {:?}", self.max_nodes_opt)?;
+        writeln!(f, "random_plies: {}", self.random_plies)?;
+        writeln!(f, "max_plies: {}", self.max_plies)?;
+        writeln!(f, "seed: {}", self.seed)?;
+        writeln!(f, "n_threads: {}", self.n_threads)?;
+        writeln!(f, "output: {}", self.output)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatagenRecord {
+    fen: String,
+    score: i32,
+}
+impl DatagenRecord {
+    pub fn fen(&self) -> &str {
+        &self.fen
+    }
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+}
+
+// result from white perspective
+fn game_result(end_game: &game_state::EndGame) -> f32 {
+    match end_game {
+        game_state::EndGame::Mat(square::Color::Black)
+        | game_state::EndGame::TimeOutLost(square::Color::Black)
+        | game_state::EndGame::Resign(square::Color::Black) => 1.0,
+        game_state::EndGame::Mat(square::Color::White)
+        | game_state::EndGame::TimeOutLost(square::Color::White)
+        | game_state::EndGame::Resign(square::Color::White) => 0.0,
+        _ => 0.5,
+    }
+}
+
+// completed games and size of the output after the last one
+fn read_progress(progress_file: &str) -> (HashSet<u64>, u64) {
+    let content = fs::read_to_string(progress_file).unwrap_or_default();
+    let mut completed = HashSet::new();
+    let mut output_len = 0;
+    // a line without end of line has been interrupted
+    for line in content.split_inclusive('\n').filter(|line| line.ends_with('\n')) {
+        let mut fields = line.split_whitespace();
+        let game_opt = fields.next().and_then(|field| field.parse::<u64>().ok());
+        let len_opt = fields.next().and_then(|field| field.parse::<u64>().ok());
+        if let (Some(game_idx), Some(len)) = (game_opt, len_opt) {
+            completed.insert(game_idx);
+            output_len = output_len.max(len);
+        }
+    }
+    (completed, output_len)
+}
+
+fn play_random_opening(
+    game: &mut game_state::GameState,
+    rng: &mut ChaCha8Rng,
+    n_plies: u8,
+    zobrist_table: &zobrist::Zobrist,
+) {
+    for _ in 0..n_plies {
+        let moves = game.gen_moves();
+        if moves.is_empty() {
+            break;
+        }
+        let m = moves[rng.gen_range(0..moves.len())];
+        game.make_move(&m, zobrist_table);
+        game.update_endgame_status();
+        if game.end_game() != game_state::EndGame::None {
+            break;
+        }
+    }
+}
+
+// play one self-play game and return the quiet positions with their score and the game result
+pub fn play_game(
+    game_idx: u64,
+    conf: &DatagenConf,
+    engine: &engine_iddfs::EngineIddfs,
+    self_actor: Addr<dispatcher::EngineDispatcher>,
+    zobrist_table: &zobrist::Zobrist,
+) -> (Vec<DatagenRecord>, f32) {
+    let mut rng = ChaCha8Rng::seed_from_u64(conf.seed.wrapping_add(game_idx));
+    let position = fen::Fen::decode(fen::FEN_START_POSITION).expect("Failed to decode FEN");
+    let mut game = game_state::GameState::new(position, zobrist_table);
+    play_random_opening(&mut game, &mut rng, conf.random_plies, zobrist_table);
+    let is_stop = Arc::new(AtomicBool::new(false));
+    let mut records = vec![];
+    let mut n_plies: u16 = 0;
+    while game.end_game() == game_state::EndGame::None && n_plies < conf.max_plies {
+        let b_move_score =
+            match engine.iddfs_search(&game, self_actor.clone(), None, None, &is_stop) {
+                Some(b_move_score) => b_move_score,
+                None => break,
+            };
+        let b_move = *b_move_score.bitboard_move();
+        let value = b_move_score.score().value();
+        let is_quiet = !game.check_status().is_check()
+            && b_move.capture().is_none()
+            && b_move.promotion().is_none();
+        if is_quiet && value.abs() < MAX_ABS_SCORE_RECORDED {
+            let fen = fen::Fen::encode(&game.bit_position().to()).expect("Failed to encode FEN");
+            records.push(DatagenRecord { fen, score: value });
+        }
+        game.make_move(&b_move, zobrist_table);
+        game.update_endgame_status();
+        n_plies += 1;
+    }
+    (records, game_result(&game.end_game()))
+}
+
+fn format_records(records: &[DatagenRecord], result: f32) -> String {
+    records
+        .iter()
+        .map(|record| format!("{} | {} | {:.1}\n", record.fen, record.score, result))
+        .collect()
+}
+
+// generate the games not yet completed. Return the number of positions written.
+pub fn datagen(
+    conf: &DatagenConf,
+    engine: &engine_iddfs::EngineIddfs,
+    self_actor: Addr<dispatcher::EngineDispatcher>,
+) -> Result<u64, String> {
+    let progress_file = conf.progress_file();
+    let (completed, output_len) = read_progress(&progress_file);
+    let todo: Vec<u64> = (0..conf.n_games)
+        .filter(|idx| !completed.contains(idx))
+        .collect();
+    tracing::info!(
+        "datagen: {} games already completed, {} to play",
+        completed.len(),
+        todo.len()
+    );
+    let open = |path: &str| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|err| format!("Cannot open '{}': {}", path, err))
+    };
+    let output = open(&conf.output)?;
+    // drop the positions of a game interrupted before it was added to the progress file
+    output
+        .set_len(output_len)
+        .map_err(|err| format!("Cannot truncate '{}': {}", conf.output, err))?;
+    let writers = Mutex::new((output, open(&progress_file)?));
+    let n_positions = AtomicU64::new(0);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(conf.n_threads.max(1))
+        .build()
+        .map_err(|err| err.to_string())?;
+    let zobrist_table = zobrist::Zobrist::new();
+    pool.install(|| {
+        todo.into_par_iter().try_for_each(|game_idx| {
+            let (records, result) =
+                play_game(game_idx, conf, engine, self_actor.clone(), &zobrist_table);
+            let mut writers = writers.lock().unwrap();
+            let (output, progress) = &mut *writers;
+            // write positions first: a game is completed once it is in the progress file
+            output
+                .write_all(format_records(&records, result).as_bytes())
+                .and_then(|_| output.flush())
+                .and_then(|_| output.metadata())
+                .and_then(|metadata| writeln!(progress, "{} {}", game_idx, metadata.len()))
+                .and_then(|_| progress.flush())
+                .map_err(|err| format!("Write error: {}", err))?;
+            n_positions.fetch_add(records.len() as u64, Ordering::Relaxed);
+            tracing::info!(
+                "datagen: game {} done, {} positions, result {}",
+                game_idx,
+                records.len(),
+                result
+            );
+            Ok::<(), String>(())
+        })
+    })?;
+    Ok(n_positions.load(Ordering::Relaxed))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix::Actor;
+
+    use super::*;
+    use crate::entity::engine::component::config::config;
+
+    #[actix::test]
+    async fn test_datagen_resume() {
+        let output = std::env::temp_dir()
+            .join(format!("chess_rust_datagen_{}.txt", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let mut conf = DatagenConf::new(2, 1, 7, output.clone());
+        conf.max_plies = 6;
+        conf.n_threads = 2;
+        let _ = fs::remove_file(&conf.output);
+        let _ = fs::remove_file(conf.progress_file());
+        let iddfs_conf = config::IDDFSConfig::new(
+            conf.depth,
+            config::IddfsFeatureConf::default(),
+            config::AlphabetaFeatureConf::default(),
+        );
+        let engine = engine_iddfs::EngineIddfs::new(None, zobrist::Zobrist::new(), &iddfs_conf);
+        let self_actor =
+            dispatcher::EngineDispatcher::new(Arc::new(engine.clone()), None, None).start();
+        let result = actix_rt::task::spawn_blocking({
+            let conf = conf.clone();
+            let engine = engine.clone();
+            let self_actor = self_actor.clone();
+            move || datagen(&conf, &engine, self_actor)
+        })
+        .await
+        .unwrap();
+        let n_positions = result.unwrap();
+        let content = fs::read_to_string(&conf.output).unwrap();
+        assert_eq!(content.lines().count() as u64, n_positions);
+        for line in content.lines() {
+            let fields: Vec<&str> = line.split(" | ").collect();
+            assert_eq!(fields.len(), 3);
+            assert!(fen::Fen::decode(fields[0]).is_ok());
+        }
+        let (completed, output_len) = read_progress(&conf.progress_file());
+        assert_eq!(completed.len(), 2);
+        assert_eq!(output_len, content.len() as u64);
+        // positions of a game interrupted before the progress file was updated
+        let mut file = OpenOptions::new().append(true).open(&conf.output).unwrap();
+        writeln!(file, "8/8/8/8/8/8/8/K1k5 w - - 0 1 | 0 | 0.5").unwrap();
+        // nothing left to play
+        let n_positions = actix_rt::task::spawn_blocking({
+            let conf = conf.clone();
+            move || datagen(&conf, &engine, self_actor)
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(n_positions, 0);
+        assert_eq!(fs::read_to_string(&conf.output).unwrap(), content);
+        let _ = fs::remove_file(&conf.output);
+        let _ = fs::remove_file(conf.progress_file());
+    }
+
+    #[test]
+    fn test_game_result() {
+        assert_eq!(game_result(&game_state::EndGame::Mat(square::Color::Black)), 1.0);
+        assert_eq!(game_result(&game_state::EndGame::Mat(square::Color::White)), 0.0);
+        assert_eq!(game_result(&game_state::EndGame::Pat), 0.5);
+    }
+}
@@ -1,8 +1,9 @@
 pub mod handler_clock;
 
 use actix::prelude::*;
-use std::time::Duration;
+use std::time::Instant;
 
+use crate::entity::clock::component::time_control;
 use crate::{entity::game::actor::game_manager, span_debug};
 
 fn span_debug() -> tracing::Span {
@@ -12,48 +13,84 @@ fn span_debug() -> tracing::Span {
 pub type ClockActor = Addr<Clock>;
 pub struct Clock {
     id: String, // useful for debug
-    remaining_time: u64,
-    inc_time: u64,
+    clock_time: time_control::ClockTime,
     game_actor: game_manager::GameManagerActor,
-    ticking_handle: Option<SpawnHandle>, // Handle to the ticking interval
+    flag_handle: Option<SpawnHandle>, // Handle to the flag check while the clock is running
 }
 
 impl Clock {
+    // times in milliseconds
     pub fn new(
         id: &str,
         starting_time: u64,
         inc_time: u64,
         game_actor: game_manager::GameManagerActor,
+    ) -> Self {
+        Self::with_time_control(
+            id,
+            time_control::TimeControl::fischer(starting_time, inc_time),
+            game_actor,
+        )
+    }
+    pub fn with_time_control(
+        id: &str,
+        time_control: time_control::TimeControl,
+        game_actor: game_manager::GameManagerActor,
     ) -> Self {
         Clock {
             id: id.to_string(),
-            remaining_time: starting_time,
-            inc_time,
+            clock_time: time_control::ClockTime::new(time_control),
             game_actor,
-            ticking_handle: None,
+            flag_handle: None,
         }
     }
-    // Start ticking every second, reducing remaining time
+    // Start the clock and wake up when the flag should fall
     fn start_ticking(&mut self, ctx: &mut Context<Self>) {
+        self.clock_time.start(Instant::now());
+        self.schedule_flag_check(ctx);
+    }
+    fn stop_ticking(&mut self, ctx: &mut Context<Self>) {
+        if let Some(handle) = self.flag_handle.take() {
+            ctx.cancel_future(handle);
+        }
+        self.clock_time.stop(Instant::now());
+    }
+    fn schedule_flag_check(&mut self, ctx: &mut Context<Self>) {
         let span = span_debug();
         let _enter = span.enter();
 
-        // Save the handle for the ticking interval so it can be paused later
-        let ticking_handle = ctx.run_interval(Duration::from_secs(1), |clock, ctx| {
-            if clock.remaining_time > 0 {
-                clock.remaining_time -= 1;
-                if clock.remaining_time % 1000 == 0 {
-                    tracing::debug!(remaining_time = clock.remaining_time);
-                }
-            } else {
-                tracing::debug!("No remaining time.");
-                clock
-                    .game_actor
-                    .do_send(game_manager::handler_clock::TimeOut);
-                ctx.stop(); // Stop the actor when time is up
-            }
-        });
-        self.ticking_handle = Some(ticking_handle);
+        if let Some(handle) = self.flag_handle.take() {
+            ctx.cancel_future(handle);
+        }
+        if let Some(time_to_flag) = self.clock_time.time_to_flag(Instant::now()) {
+            let handle = ctx.run_later(time_to_flag, |clock, ctx| {
+                clock.flag_handle = None;
+                clock.check_flag(ctx);
+            });
+            self.flag_handle = Some(handle);
+        }
+    }
+    fn check_flag(&mut self, ctx: &mut Context<Self>) {
+        let span = span_debug();
+        let _enter = span.enter();
+
+        let now = Instant::now();
+        if !self.clock_time.is_running() {
+            return;
+        }
+        if self.clock_time.is_flagged(now) {
+            tracing::debug!("Clock id '{}': no remaining time.", self.id);
+            self.game_actor
+                .do_send(game_manager::handler_clock::TimeOut);
+            ctx.stop(); // Stop the actor when time is up
+        } else {
+            tracing::debug!(
+                "Clock id '{}': remaining time {} ms",
+                self.id,
+                self.clock_time.remaining_ms(now)
+            );
+            self.schedule_flag_check(ctx);
+        }
     }
 }
 
@@ -1,13 +1,17 @@
-use actix::{ActorContext, AsyncContext, Context, Handler, Message};
+use std::time::{Duration, Instant};
+
+use actix::{ActorContext, Context, Handler, Message, MessageResult};
 
 use super::Clock;
+use crate::entity::clock::component::time_control;
+use crate::entity::engine::component::time_allocation;
 use crate::span_debug;
 
 fn span_debug() -> tracing::Span {
     span_debug!("chessclock::handler_clock")
 }
 
-// Define a message to set the remaining time
+// Define a message to set the remaining time (ms)
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct SetRemainingTime {
@@ -21,16 +25,20 @@ impl SetRemainingTime {
 impl Handler<SetRemainingTime> for Clock {
     type Result = ();
 
-    fn handle(&mut self, msg: SetRemainingTime, _ctx: &mut Context<Self>) {
+    fn handle(&mut self, msg: SetRemainingTime, ctx: &mut Context<Self>) {
         let span = span_debug();
         let _enter = span.enter();
 
-        self.remaining_time = msg.new_time;
-        tracing::debug!("Clock time set to: {}", self.remaining_time);
+        self.clock_time
+            .set_remaining(Duration::from_millis(msg.new_time), Instant::now());
+        if self.clock_time.is_running() {
+            self.schedule_flag_check(ctx);
+        }
+        tracing::debug!("Clock time set to: {} ms", msg.new_time);
     }
 }
 
-// Define a message to set the remaining time
+// Number of moves played: apply the increment / delay and the next periods
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct IncRemainingTime(pub u64);
@@ -41,16 +49,39 @@ impl Handler<IncRemainingTime> for Clock {
         let span = span_debug();
         let _enter = span.enter();
 
-        self.remaining_time += msg.0 * self.inc_time;
+        self.clock_time.moves_played(msg.0 as u32);
+        tracing::debug!(
+            "Clock id '{}' time set to: {} ms after increment",
+            self.id,
+            self.clock_time.remaining_ms(Instant::now())
+        );
+    }
+}
+
+// Number of moves taken back: the time before these moves is restored
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct UndoMoves(pub u64);
+impl Handler<UndoMoves> for Clock {
+    type Result = ();
+
+    fn handle(&mut self, msg: UndoMoves, ctx: &mut Context<Self>) {
+        let span = span_debug();
+        let _enter = span.enter();
+
+        self.clock_time.moves_undone(msg.0 as u32, Instant::now());
+        if self.clock_time.is_running() {
+            self.schedule_flag_check(ctx);
+        }
         tracing::debug!(
-            "Clock id '{}' time set to: {} after increment",
+            "Clock id '{}' time set to: {} ms after undo",
             self.id,
-            self.remaining_time
+            self.clock_time.remaining_ms(Instant::now())
         );
     }
 }
 
-// Define a message to set the remaining time
+// Define a message to set the increment (ms)
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct SetIncTime(u64);
@@ -67,8 +98,30 @@ impl Handler<SetIncTime> for Clock {
         let span = span_debug();
         let _enter = span.enter();
 
-        self.inc_time = msg.0;
-        tracing::debug!("Clock inc time set to: {}", self.inc_time);
+        self.clock_time.set_increment(msg.0);
+        tracing::debug!("Clock inc time set to: {} ms", msg.0);
+    }
+}
+
+// Define a message to set the time control (the clock is reset)
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct SetTimeControl(pub time_control::TimeControl);
+
+impl Handler<SetTimeControl> for Clock {
+    type Result = ();
+
+    fn handle(&mut self, msg: SetTimeControl, ctx: &mut Context<Self>) {
+        let span = span_debug();
+        let _enter = span.enter();
+
+        let is_running = self.clock_time.is_running();
+        self.stop_ticking(ctx);
+        tracing::debug!("Clock id '{}' time control set to: {}", self.id, msg.0);
+        self.clock_time = time_control::ClockTime::new(msg.0);
+        if is_running {
+            self.start_ticking(ctx);
+        }
     }
 }
 
@@ -84,8 +137,8 @@ impl Handler<PauseClock> for Clock {
         let span = span_debug();
         let _enter = span.enter();
 
-        if let Some(handle) = self.ticking_handle.take() {
-            ctx.cancel_future(handle); // Cancel the ticking interval to pause the clock
+        if self.clock_time.is_running() {
+            self.stop_ticking(ctx);
             tracing::debug!("Clock paused");
         }
     }
@@ -103,13 +156,14 @@ impl Handler<ResumeClock> for Clock {
         let span = span_debug();
         let _enter = span.enter();
 
-        if self.ticking_handle.is_none() {
+        if !self.clock_time.is_running() {
             self.start_ticking(ctx); // Resume ticking if it was paused
             tracing::debug!("Clock resumed");
         }
     }
 }
 
+// remaining time in ms
 #[derive(Debug, Message)]
 #[rtype(result = "u64")]
 pub struct GetRemainingTime;
@@ -118,7 +172,28 @@ impl Handler<GetRemainingTime> for Clock {
     type Result = u64;
 
     fn handle(&mut self, _msg: GetRemainingTime, _ctx: &mut Context<Self>) -> u64 {
-        self.remaining_time
+        self.clock_time.remaining_ms(Instant::now())
+    }
+}
+
+// remaining time, increment and moves to go for the time manager
+#[derive(Debug, Message)]
+#[rtype(result = "time_allocation::TimeInfo")]
+pub struct GetTimeInfo;
+
+impl Handler<GetTimeInfo> for Clock {
+    type Result = MessageResult<GetTimeInfo>;
+
+    fn handle(&mut self, _msg: GetTimeInfo, _ctx: &mut Context<Self>) -> Self::Result {
+        MessageResult(time_allocation::TimeInfo {
+            remaining_ms: self.clock_time.remaining_ms(Instant::now()),
+            inc_ms: self.clock_time.increment_ms(),
+            moves_to_go: self.clock_time.moves_to_go(),
+            // the latency and the opponent are not known by the clock
+            move_overhead_ms: 0,
+            opponent_remaining_ms_opt: None,
+            opponent_inc_ms: 0,
+        })
     }
 }
 
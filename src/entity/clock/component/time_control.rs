@@ -0,0 +1,500 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// How the time is given back to a player after each move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DelayMode {
+    None,
+    // the increment (ms) is added after each move
+    Fischer(u64),
+    // the time used for the move is given back, up to the delay (ms)
+    Bronstein(u64),
+    // US delay: the clock starts running once the delay (ms) has elapsed
+    SimpleDelay(u64),
+}
+
+// A period of the time control: 'n_moves' to play in 'time_ms'.
+// n_moves = None: rest of the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimePeriod {
+    pub n_moves: Option<u32>,
+    pub time_ms: u64,
+}
+
+// Time control of one player. When the last period has a number of moves, it is repeated.
+// e.g. 40 moves in 90 min then 30 min for the rest of the game:
+//   [TimePeriod { Some(40), 5_400_000 }, TimePeriod { None, 1_800_000 }]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeControl {
+    pub periods: Vec<TimePeriod>,
+    pub delay_mode: DelayMode,
+}
+impl TimeControl {
+    pub fn new(periods: Vec<TimePeriod>, delay_mode: DelayMode) -> Self {
+        assert!(
+            !periods.is_empty(),
+            "a time control needs at least one period"
+        );
+        Self {
+            periods,
+            delay_mode,
+        }
+    }
+    // sudden death with an increment
+    pub fn fischer(time_ms: u64, inc_ms: u64) -> Self {
+        let delay_mode = if inc_ms == 0 {
+            DelayMode::None
+        } else {
+            DelayMode::Fischer(inc_ms)
+        };
+        Self::new(
+            vec![TimePeriod {
+                n_moves: None,
+                time_ms,
+            }],
+            delay_mode,
+        )
+    }
+    // periods separated by ':' ('moves/ms' or 'ms' for the rest of the game)
+    // followed by '+inc' or '+delay=mode:ms' (mode: fischer, bronstein or simple),
+    // e.g. '40/5400000:1800000+delay=bronstein:2000' or '60000+500'
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (periods_spec, delay_spec_opt) = match spec.trim().split_once('+') {
+            Some((periods_spec, delay_spec)) => (periods_spec, Some(delay_spec)),
+            None => (spec.trim(), None),
+        };
+        let parse_ms = |value: &str| {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid time '{}' in time control '{}'", value, spec))
+        };
+        let mut periods = vec![];
+        for period_spec in periods_spec.split(':') {
+            let period = match period_spec.split_once('/') {
+                Some((n_moves, time_ms)) => TimePeriod {
+                    n_moves: Some(n_moves.parse().ok().filter(|n| *n > 0).ok_or(format!(
+                        "Invalid number of moves '{}' in time control '{}'",
+                        n_moves, spec
+                    ))?),
+                    time_ms: parse_ms(time_ms)?,
+                },
+                None => TimePeriod {
+                    n_moves: None,
+                    time_ms: parse_ms(period_spec)?,
+                },
+            };
+            periods.push(period);
+        }
+        // only the last period can last until the end of the game
+        if periods[..periods.len() - 1]
+            .iter()
+            .any(|period| period.n_moves.is_none())
+        {
+            return Err(format!(
+                "Only the last period of time control '{}' can be without a number of moves",
+                spec
+            ));
+        }
+        let delay_mode = match delay_spec_opt {
+            None => DelayMode::None,
+            Some(delay_spec) => match delay_spec.strip_prefix("delay=") {
+                None => DelayMode::Fischer(parse_ms(delay_spec)?),
+                Some(delay) => match delay.split_once(':') {
+                    Some(("fischer", ms)) => DelayMode::Fischer(parse_ms(ms)?),
+                    Some(("bronstein", ms)) => DelayMode::Bronstein(parse_ms(ms)?),
+                    Some(("simple", ms)) => DelayMode::SimpleDelay(parse_ms(ms)?),
+                    _ => {
+                        return Err(format!(
+                            "Invalid delay '{}': expected fischer:ms, bronstein:ms or simple:ms",
+                            delay
+                        ))
+                    }
+                },
+            },
+        };
+        Ok(Self::new(periods, delay_mode))
+    }
+    fn period(&self, idx: usize) -> Option<&TimePeriod> {
+        match self.periods.get(idx) {
+            Some(period) => Some(period),
+            // repeat the last period
+            None => self
+                .periods
+                .last()
+                .filter(|period| period.n_moves.is_some()),
+        }
+    }
+}
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let periods: Vec<String> = self
+            .periods
+            .iter()
+            .map(|period| match period.n_moves {
+                Some(n_moves) => format!("{}/{}ms", n_moves, period.time_ms),
+                None => format!("{}ms", period.time_ms),
+            })
+            .collect();
+        write!(f, "{} {:?}", periods.join(" + "), self.delay_mode)
+    }
+}
+
+// Clock state before a move, restored when the move is taken back
+#[derive(Debug, Clone)]
+struct MoveBackup {
+    remaining: Duration,
+    period_idx: usize,
+    n_moves_in_period: u32,
+}
+
+// Time of one player with millisecond accuracy
+#[derive(Debug, Clone)]
+pub struct ClockTime {
+    time_control: TimeControl,
+    // remaining time when the clock was started (or stopped)
+    remaining: Duration,
+    started_at_opt: Option<Instant>,
+    // time used for the last move, until its delay is given back
+    last_move_used_opt: Option<Duration>,
+    n_moves: u32,
+    period_idx: usize,
+    n_moves_in_period: u32,
+    backup: Vec<MoveBackup>,
+}
+impl ClockTime {
+    pub fn new(time_control: TimeControl) -> Self {
+        let remaining = Duration::from_millis(time_control.periods[0].time_ms);
+        Self {
+            time_control,
+            remaining,
+            started_at_opt: None,
+            last_move_used_opt: None,
+            n_moves: 0,
+            period_idx: 0,
+            n_moves_in_period: 0,
+            backup: vec![],
+        }
+    }
+    pub fn time_control(&self) -> &TimeControl {
+        &self.time_control
+    }
+    pub fn is_running(&self) -> bool {
+        self.started_at_opt.is_some()
+    }
+    pub fn n_moves(&self) -> u32 {
+        self.n_moves
+    }
+    // time counted since the clock has been started
+    fn used(&self, now: Instant) -> Duration {
+        match self.started_at_opt {
+            None => Duration::ZERO,
+            Some(started_at) => {
+                let elapsed = now.saturating_duration_since(started_at);
+                match self.time_control.delay_mode {
+                    DelayMode::SimpleDelay(delay) => {
+                        elapsed.saturating_sub(Duration::from_millis(delay))
+                    }
+                    _ => elapsed,
+                }
+            }
+        }
+    }
+    pub fn remaining(&self, now: Instant) -> Duration {
+        self.remaining.saturating_sub(self.used(now))
+    }
+    pub fn remaining_ms(&self, now: Instant) -> u64 {
+        self.remaining(now).as_millis() as u64
+    }
+    pub fn is_flagged(&self, now: Instant) -> bool {
+        self.remaining(now).is_zero()
+    }
+    // duration before the flag falls if the clock keeps running
+    pub fn time_to_flag(&self, now: Instant) -> Option<Duration> {
+        let started_at = self.started_at_opt?;
+        let delay = match self.time_control.delay_mode {
+            DelayMode::SimpleDelay(delay) => Duration::from_millis(delay),
+            _ => Duration::ZERO,
+        };
+        let flag_at = started_at + delay + self.remaining;
+        Some(flag_at.saturating_duration_since(now))
+    }
+    pub fn set_remaining(&mut self, remaining: Duration, now: Instant) {
+        self.remaining = remaining;
+        if self.started_at_opt.is_some() {
+            self.started_at_opt = Some(now);
+        }
+    }
+    pub fn set_increment(&mut self, inc_ms: u64) {
+        self.time_control.delay_mode = if inc_ms == 0 {
+            DelayMode::None
+        } else {
+            DelayMode::Fischer(inc_ms)
+        };
+    }
+    pub fn start(&mut self, now: Instant) {
+        if self.started_at_opt.is_none() {
+            self.started_at_opt = Some(now);
+        }
+    }
+    pub fn stop(&mut self, now: Instant) {
+        if let Some(started_at) = self.started_at_opt {
+            self.last_move_used_opt = Some(now.saturating_duration_since(started_at));
+            self.remaining = self.remaining(now);
+            self.started_at_opt = None;
+        }
+    }
+    // n moves have been played: increment / delay and time of the next periods
+    pub fn moves_played(&mut self, n_moves: u32) {
+        // the Bronstein delay is given back only for a single move following a stop of the clock
+        let last_move_used_opt = match self.last_move_used_opt.take() {
+            Some(used) if n_moves == 1 && !self.is_running() => Some(used),
+            _ => None,
+        };
+        for _ in 0..n_moves {
+            self.backup.push(MoveBackup {
+                remaining: self.remaining,
+                period_idx: self.period_idx,
+                n_moves_in_period: self.n_moves_in_period,
+            });
+            match self.time_control.delay_mode {
+                DelayMode::Fischer(inc) => self.remaining += Duration::from_millis(inc),
+                DelayMode::Bronstein(delay) => {
+                    if let Some(used) = last_move_used_opt {
+                        self.remaining += used.min(Duration::from_millis(delay))
+                    }
+                }
+                DelayMode::None | DelayMode::SimpleDelay(_) => {}
+            }
+            self.n_moves += 1;
+            self.n_moves_in_period += 1;
+            let is_end_of_period = self
+                .time_control
+                .period(self.period_idx)
+                .and_then(|period| period.n_moves)
+                .is_some_and(|n| self.n_moves_in_period >= n);
+            if is_end_of_period {
+                self.period_idx += 1;
+                self.n_moves_in_period = 0;
+                if let Some(period) = self.time_control.period(self.period_idx) {
+                    self.remaining += Duration::from_millis(period.time_ms);
+                }
+            }
+        }
+    }
+    // time given back after each move (ms)
+    pub fn increment_ms(&self) -> u64 {
+        match self.time_control.delay_mode {
+            DelayMode::None => 0,
+            DelayMode::Fischer(inc)
+            | DelayMode::Bronstein(inc)
+            | DelayMode::SimpleDelay(inc) => inc,
+        }
+    }
+    // n moves are taken back: the time before these moves is restored
+    pub fn moves_undone(&mut self, n_moves: u32, now: Instant) {
+        for _ in 0..n_moves {
+            let Some(backup) = self.backup.pop() else {
+                break;
+            };
+            self.set_remaining(backup.remaining, now);
+            self.period_idx = backup.period_idx;
+            self.n_moves_in_period = backup.n_moves_in_period;
+            self.n_moves -= 1;
+        }
+    }
+    // moves to play before the next time control (None if sudden death)
+    pub fn moves_to_go(&self) -> Option<u32> {
+        self.time_control
+            .period(self.period_idx)
+            .and_then(|period| period.n_moves)
+            .map(|n| n - self.n_moves_in_period)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(value: u64) -> Duration {
+        Duration::from_millis(value)
+    }
+
+    #[test]
+    fn test_running_clock() {
+        let mut clock = ClockTime::new(TimeControl::fischer(1_000, 0));
+        let t0 = Instant::now();
+        clock.start(t0);
+        assert_eq!(clock.remaining_ms(t0 + ms(250)), 750);
+        assert_eq!(clock.time_to_flag(t0 + ms(250)), Some(ms(750)));
+        clock.stop(t0 + ms(400));
+        // stopped clock does not run
+        assert_eq!(clock.remaining_ms(t0 + ms(5_000)), 600);
+        clock.start(t0 + ms(5_000));
+        assert!(clock.is_flagged(t0 + ms(5_600)));
+    }
+
+    #[test]
+    fn test_fischer_and_bronstein() {
+        let t0 = Instant::now();
+        let mut fischer = ClockTime::new(TimeControl::fischer(10_000, 2_000));
+        fischer.start(t0);
+        fischer.stop(t0 + ms(500));
+        fischer.moves_played(1);
+        assert_eq!(fischer.remaining_ms(t0), 11_500);
+        let mut bronstein = ClockTime::new(TimeControl::new(
+            vec![TimePeriod {
+                n_moves: None,
+                time_ms: 10_000,
+            }],
+            DelayMode::Bronstein(2_000),
+        ));
+        bronstein.start(t0);
+        bronstein.stop(t0 + ms(500));
+        bronstein.moves_played(1);
+        assert_eq!(bronstein.remaining_ms(t0), 10_000);
+        bronstein.start(t0);
+        bronstein.stop(t0 + ms(3_000));
+        bronstein.moves_played(1);
+        assert_eq!(bronstein.remaining_ms(t0), 9_000);
+        // several moves at once: no time used is given back
+        bronstein.start(t0);
+        bronstein.stop(t0 + ms(1_000));
+        bronstein.moves_played(3);
+        assert_eq!(bronstein.remaining_ms(t0), 8_000);
+        // a move played while the clock runs does not get the delay of the last stop
+        bronstein.start(t0);
+        bronstein.stop(t0 + ms(1_000));
+        bronstein.start(t0 + ms(1_000));
+        bronstein.moves_played(1);
+        assert_eq!(bronstein.remaining_ms(t0 + ms(1_000)), 7_000);
+        bronstein.stop(t0 + ms(1_500));
+        bronstein.moves_played(1);
+        assert_eq!(bronstein.remaining_ms(t0), 7_000);
+    }
+
+    #[test]
+    fn test_simple_delay() {
+        let t0 = Instant::now();
+        let mut clock = ClockTime::new(TimeControl::new(
+            vec![TimePeriod {
+                n_moves: None,
+                time_ms: 1_000,
+            }],
+            DelayMode::SimpleDelay(300),
+        ));
+        clock.start(t0);
+        assert_eq!(clock.remaining_ms(t0 + ms(200)), 1_000);
+        assert_eq!(clock.remaining_ms(t0 + ms(500)), 800);
+        assert_eq!(clock.time_to_flag(t0), Some(ms(1_300)));
+    }
+
+    #[test]
+    fn test_multi_period() {
+        // 2 moves in 10 s then 5 s
+        let time_control = TimeControl::new(
+            vec![
+                TimePeriod {
+                    n_moves: Some(2),
+                    time_ms: 10_000,
+                },
+                TimePeriod {
+                    n_moves: None,
+                    time_ms: 5_000,
+                },
+            ],
+            DelayMode::None,
+        );
+        let t0 = Instant::now();
+        let mut clock = ClockTime::new(time_control);
+        assert_eq!(clock.moves_to_go(), Some(2));
+        clock.moves_played(1);
+        assert_eq!(clock.moves_to_go(), Some(1));
+        clock.moves_played(1);
+        assert_eq!(clock.remaining_ms(t0), 15_000);
+        assert_eq!(clock.moves_to_go(), None);
+        clock.moves_played(10);
+        assert_eq!(clock.remaining_ms(t0), 15_000);
+    }
+
+    #[test]
+    fn test_moves_undone() {
+        let time_control = TimeControl::new(
+            vec![
+                TimePeriod {
+                    n_moves: Some(1),
+                    time_ms: 10_000,
+                },
+                TimePeriod {
+                    n_moves: None,
+                    time_ms: 5_000,
+                },
+            ],
+            DelayMode::Fischer(1_000),
+        );
+        let t0 = Instant::now();
+        let mut clock = ClockTime::new(time_control);
+        clock.start(t0);
+        clock.stop(t0 + ms(2_000));
+        clock.moves_played(1);
+        assert_eq!(clock.remaining_ms(t0), 14_000);
+        assert_eq!(clock.moves_to_go(), None);
+        clock.moves_undone(1, t0);
+        assert_eq!(clock.remaining_ms(t0), 8_000);
+        assert_eq!(clock.moves_to_go(), Some(1));
+        assert_eq!(clock.n_moves(), 0);
+        // nothing to take back
+        clock.moves_undone(1, t0);
+        assert_eq!(clock.remaining_ms(t0), 8_000);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            TimeControl::parse("60000+500"),
+            Ok(TimeControl::fischer(60_000, 500))
+        );
+        assert_eq!(
+            TimeControl::parse("40/5400000:1800000+delay=bronstein:2000"),
+            Ok(TimeControl::new(
+                vec![
+                    TimePeriod {
+                        n_moves: Some(40),
+                        time_ms: 5_400_000,
+                    },
+                    TimePeriod {
+                        n_moves: None,
+                        time_ms: 1_800_000,
+                    },
+                ],
+                DelayMode::Bronstein(2_000),
+            ))
+        );
+        assert_eq!(
+            TimeControl::parse("1000+delay=simple:300").map(|tc| tc.delay_mode),
+            Ok(DelayMode::SimpleDelay(300))
+        );
+        assert_eq!(
+            TimeControl::parse("40/60000").map(|tc| tc.delay_mode),
+            Ok(DelayMode::None)
+        );
+        assert!(TimeControl::parse("").is_err());
+        assert!(TimeControl::parse("60000:40/60000").is_err());
+        assert!(TimeControl::parse("0/60000").is_err());
+        assert!(TimeControl::parse("60000+delay=hourglass:100").is_err());
+    }
+
+    #[test]
+    fn test_repeated_period() {
+        let time_control = TimeControl::new(
+            vec![TimePeriod {
+                n_moves: Some(40),
+                time_ms: 60_000,
+            }],
+            DelayMode::None,
+        );
+        let mut clock = ClockTime::new(time_control);
+        clock.moves_played(80);
+        assert_eq!(clock.remaining_ms(Instant::now()), 180_000);
+        assert_eq!(clock.moves_to_go(), Some(40));
+    }
+}
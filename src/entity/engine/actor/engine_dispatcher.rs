@@ -2,8 +2,11 @@ pub mod handler_engine;
 
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
-use crate::entity::engine::component::{engine_logic as logic, time_allocation, ts_bitboard_move};
+use crate::entity::engine::component::{
+    adjudication, engine_logic as logic, time_allocation, ts_bitboard_move,
+};
 use actix::prelude::*;
 
 use crate::entity::game::actor::game_manager;
@@ -31,6 +34,7 @@ pub struct EngineDispatcher {
     game_opt: Option<game_state::GameState>, // initial game to be played
     thinking_id: u64,
     stop_flag: Arc<AtomicBool>,
+    adjudicator: adjudication::Adjudicator,
 }
 impl EngineDispatcher {
     pub fn new(
@@ -50,6 +54,7 @@ impl EngineDispatcher {
             game_opt: None,
             thinking_id: 0,
             stop_flag: Arc::new(AtomicBool::new(false)),
+            adjudicator: adjudication::Adjudicator::default(),
         }
     }
     fn get_best_move(&self) -> Option<ts_bitboard_move::TimestampedBitBoardMove> {
@@ -69,20 +74,24 @@ impl EngineDispatcher {
         self.engine_status.is_thinking()
     }
 
-    async fn get_max_time_for_move(
+    async fn get_time_manager(
         game_manager_actor: game_manager::GameManagerActor,
         game: game_state::GameState,
         player_turn: square::Color,
-    ) -> Option<std::time::Duration> {
-        tracing::debug!("start get_max_time_for_move...");
+        start: Instant,
+    ) -> Option<time_allocation::TimeManager> {
+        tracing::debug!("start get_time_manager...");
         let result = game_manager_actor
-            .send(game_manager::handler_clock::GetClockRemainingTime::new(
-                player_turn,
-            ))
+            .send(game_manager::handler_clock::GetClockTimeInfo::new(player_turn))
             .await;
-        tracing::debug!("end get_max_time_for_move: {:?}", result);
+        tracing::debug!("end get_time_manager: {:?}", result);
         match result {
-            Ok(clock_opt) => time_allocation::estimate_time_allocation(clock_opt, &game),
+            Ok(time_info_opt) => time_info_opt.map(|time_info| {
+                let n_plies =
+                    time_allocation::n_plies_played(game.bit_position().bit_position_status());
+                let limits = time_allocation::compute_time_limits(&time_info, n_plies);
+                time_allocation::TimeManager::new(limits, start).with_time_info(time_info)
+            }),
             Err(err) => panic!("{:?}", err),
         }
     }
@@ -107,14 +116,11 @@ impl EngineDispatcher {
         }
         self.engine_status = self.engine_status.clone().set_is_thinking(true);
 
+        // the search starts once the time limits are known
         self.self_actor_opt
             .as_ref()
             .unwrap()
             .do_send(handler_engine::EngineInitTimeLimit::new(game));
-        self.self_actor_opt
-            .as_ref()
-            .unwrap()
-            .do_send(handler_engine::EngineInit);
 
         if let Some(debug_actor) = &self.debug_actor_opt {
             debug_actor.do_send(debug::AddMessage(format!(
@@ -1,15 +1,22 @@
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
-use actix::{Arbiter, AsyncContext, Handler, Message};
+use actix::{Actor, Arbiter, AsyncContext, Handler, Message, ResponseFuture};
 
-use crate::entity::engine::component::{engine_logic as logic, ts_best_move, ts_bitboard_move};
-use crate::entity::game::component::game_state;
+use crate::entity::engine::component::evaluation::score;
+use crate::entity::engine::component::{
+    adjudication, engine_logic as logic, time_allocation, ts_best_move, ts_bitboard_move,
+};
+use crate::entity::game::component::{game_state, square};
 use crate::entity::stat::actor::stat_entity;
 use crate::ui::notation::long_notation;
 use crate::{
     entity::{
-        game::{actor::game_manager, component::bitboard},
+        game::{
+            actor::game_manager,
+            component::bitboard::{self, zobrist},
+        },
         uci::actor::uci_entity,
     },
     monitoring::debug,
@@ -96,9 +103,10 @@ impl Handler<EngineGetBestMove> for EngineDispatcher {
     }
 }
 
+// Best move and hash of the position searched
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
-pub struct EngineEndOfAnalysis(pub bitboard::BitBoardMove);
+pub struct EngineEndOfAnalysis(pub bitboard::BitBoardMove, pub zobrist::ZobristHash);
 impl Handler<EngineEndOfAnalysis> for EngineDispatcher {
     type Result = ();
 
@@ -113,12 +121,15 @@ impl Handler<EngineEndOfAnalysis> for EngineDispatcher {
             )));
         }
         // send best move to game manager
+        let game_manager_actor = self.game_manager_actor_opt.as_ref().unwrap().clone();
         send_best_move(
             msg.0,
             self.engine.id(),
             self.debug_actor_opt.as_ref(),
-            self.game_manager_actor_opt.as_ref().unwrap().clone(),
+            game_manager_actor.clone(),
         );
+        // the game manager plays it if it drives the game
+        game_manager_actor.do_send(game_manager::handler_engine::EngineMoveFound(msg.0, msg.1));
         // display bestmove in uci console
         let ts_best_move = ts_bitboard_move::TimestampedBitBoardMove::new(msg.0, self.engine.id());
         let ts_best_move_cast = ts_best_move::TimestampedBestMove::build(
@@ -161,12 +172,15 @@ impl Handler<EngineSendBestMove> for EngineDispatcher {
                 msg
             )));
         }
-        send_best_move(
-            msg.0,
-            self.engine.id(),
-            self.debug_actor_opt.as_ref(),
-            self.game_manager_actor_opt.as_ref().unwrap().clone(),
-        );
+        // no game manager when the engine is driven directly (benchmark, datagen)
+        if let Some(game_manager_actor) = self.game_manager_actor_opt.as_ref() {
+            send_best_move(
+                msg.0,
+                self.engine.id(),
+                self.debug_actor_opt.as_ref(),
+                game_manager_actor.clone(),
+            );
+        }
         self.set_best_move(Some(msg.0));
     }
 }
@@ -207,12 +221,27 @@ impl Handler<EngineSetStatus> for EngineDispatcher {
 
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
-pub struct EngineInit;
+pub struct EngineInit {
+    time_manager_opt: Option<time_allocation::TimeManager>,
+    thinking_id: u64,
+}
+impl EngineInit {
+    pub fn new(time_manager_opt: Option<time_allocation::TimeManager>, thinking_id: u64) -> Self {
+        Self {
+            time_manager_opt,
+            thinking_id,
+        }
+    }
+}
 
 impl Handler<EngineInit> for EngineDispatcher {
     type Result = ();
 
-    fn handle(&mut self, _msg: EngineInit, _ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: EngineInit, _ctx: &mut Self::Context) -> Self::Result {
+        if msg.thinking_id != self.thinking_id || !self.is_thinking() {
+            tracing::debug!("Thinking {} has been stopped before starting", msg.thinking_id);
+            return;
+        }
         let stop_flag = Arc::new(AtomicBool::new(false));
         let stop_flag_clone = Arc::clone(&stop_flag);
         // start non blocking task find_best_move
@@ -220,29 +249,34 @@ impl Handler<EngineInit> for EngineDispatcher {
         let stat_actor_opt = self.stat_actor_opt.as_ref().cloned();
         let game_clone = self.game_opt.as_ref().unwrap().clone();
         let engine = self.engine.clone();
+        let time_manager_opt = msg.time_manager_opt;
         tracing::debug!("Calling engine");
         actix::Arbiter::spawn(&Arbiter::new(), async move {
             tracing::debug!("Start computing");
-            engine.find_best_move(self_actor, stat_actor_opt, game_clone, &stop_flag_clone);
-            //thread::sleep(std::time::Duration::from_secs(10));
+            engine.find_best_move(
+                self_actor,
+                stat_actor_opt,
+                game_clone,
+                time_manager_opt,
+                &stop_flag_clone,
+            );
             tracing::debug!("End computing");
+            // one thread per search: release it
+            Arbiter::current().stop();
         });
         self.stop_flag = stop_flag;
     }
 }
 
+// Hard limit of the time manager: the search is stopped whatever its state
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct TimeoutCheck {
-    timeout: std::time::Duration,
-    thinking_id: u64,
+    deadline: Instant,
 }
 impl TimeoutCheck {
-    pub fn new(timeout: std::time::Duration, thinking_id: u64) -> Self {
-        Self {
-            timeout,
-            thinking_id,
-        }
+    pub fn new(deadline: Instant) -> Self {
+        Self { deadline }
     }
 }
 
@@ -251,20 +285,14 @@ impl Handler<TimeoutCheck> for EngineDispatcher {
 
     fn handle(&mut self, msg: TimeoutCheck, _ctx: &mut Self::Context) -> Self::Result {
         tracing::debug!("Timeout check triggered");
+        // the stop flag belongs to the current search only
         let stop_flag = Arc::clone(&self.stop_flag);
-        let current_thinking_id = self.thinking_id;
         actix::Arbiter::spawn(&Arbiter::new(), async move {
-            tracing::debug!("Sleeping {:?}", msg.timeout);
-            tokio::time::sleep(msg.timeout).await;
-            tracing::debug!(
-                "current thinking_id {} vs previous thinking_id {}",
-                current_thinking_id,
-                msg.thinking_id
-            );
-            if msg.thinking_id == current_thinking_id {
-                tracing::debug!("Timeout reached: stopping the engine.");
-                stop_flag.store(true, Ordering::SeqCst);
-            }
+            tracing::debug!("Sleeping until {:?}", msg.deadline);
+            tokio::time::sleep_until(tokio::time::Instant::from_std(msg.deadline)).await;
+            tracing::debug!("Hard time limit reached: stopping the engine.");
+            stop_flag.store(true, Ordering::SeqCst);
+            Arbiter::current().stop();
         });
     }
 }
@@ -273,11 +301,15 @@ impl Handler<TimeoutCheck> for EngineDispatcher {
 #[rtype(result = "()")]
 pub struct EngineInitTimeLimit {
     game: game_state::GameState,
+    start: Instant,
 }
 
 impl EngineInitTimeLimit {
     pub fn new(game: &game_state::GameState) -> Self {
-        Self { game: game.clone() }
+        Self {
+            game: game.clone(),
+            start: Instant::now(),
+        }
     }
 }
 
@@ -292,40 +324,201 @@ impl Handler<EngineInitTimeLimit> for EngineDispatcher {
         let game_manager_actor = self.game_manager_actor_opt.as_ref().unwrap().clone();
         let player_turn = msg.game.bit_position().bit_position_status().player_turn();
 
-        // Schedule the timeout check
-        let fut = Self::get_max_time_for_move(game_manager_actor, msg.game.clone(), player_turn);
+        // Compute the time limits from the clock, then start the search
+        let fut =
+            Self::get_time_manager(game_manager_actor, msg.game.clone(), player_turn, msg.start);
         let ctx_addr = ctx.address();
         let thinking_id = self.thinking_id;
         actix::spawn(async move {
-            if let Some(max_time) = fut.await {
-                tracing::debug!("Scheduling timeout for {} seconds", max_time.as_secs());
-                ctx_addr.do_send(TimeoutCheck::new(max_time, thinking_id));
-            } else {
-                tracing::warn!("Could not determine max time for move.");
+            let time_manager_opt = fut.await;
+            let deadline_opt = time_manager_opt
+                .as_ref()
+                .map(|time_manager| time_manager.hard_deadline());
+            match time_manager_opt.as_ref() {
+                Some(time_manager) => tracing::debug!(
+                    "Time limits: soft {:?}, hard {:?}",
+                    time_manager.limits().soft,
+                    time_manager.limits().hard
+                ),
+                None => tracing::warn!("Could not determine max time for move."),
+            }
+            ctx_addr.do_send(EngineInit::new(time_manager_opt, thinking_id));
+            if let Some(deadline) = deadline_opt {
+                ctx_addr.do_send(TimeoutCheck::new(deadline));
             }
         });
     }
 }
 
+// Bounded search for a hint: the game and the current search are not affected
+#[derive(Debug, Message)]
+#[rtype(result = "Option<score::BitboardMoveScore>")]
+pub struct EngineHint {
+    game: game_state::GameState,
+    max_time_ms: u64,
+}
+impl EngineHint {
+    pub fn new(game: game_state::GameState, max_time_ms: u64) -> Self {
+        Self { game, max_time_ms }
+    }
+}
+
+impl Handler<EngineHint> for EngineDispatcher {
+    type Result = ResponseFuture<Option<score::BitboardMoveScore>>;
+
+    fn handle(&mut self, msg: EngineHint, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "EngineDispatcher for engine id {:?} receive EngineHint",
+                self.engine.id(),
+            )));
+        }
+        let engine = self.engine.clone();
+        // the best moves found during the hint search are sent to a dispatcher without game
+        let hint_actor = EngineDispatcher::new(engine.clone(), None, None).start();
+        let time_manager = time_allocation::TimeManager::new(
+            time_allocation::TimeLimits::move_time(msg.max_time_ms),
+            Instant::now(),
+        );
+        let deadline = time_manager.hard_deadline();
+        let is_stop = Arc::new(AtomicBool::new(false));
+        let is_stop_clone = Arc::clone(&is_stop);
+        actix::spawn(async move {
+            tokio::time::sleep_until(tokio::time::Instant::from_std(deadline)).await;
+            is_stop_clone.store(true, Ordering::SeqCst);
+        });
+        let game = msg.game;
+        Box::pin(async move {
+            actix_rt::task::spawn_blocking(move || {
+                engine.analyse(hint_actor, &game, Some(time_manager), &is_stop)
+            })
+            .await
+            .ok()
+            .flatten()
+        })
+    }
+}
+
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct EngineSetAdjudication(pub adjudication::AdjudicationConf);
+impl Handler<EngineSetAdjudication> for EngineDispatcher {
+    type Result = ();
+
+    fn handle(&mut self, msg: EngineSetAdjudication, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "EngineDispatcher for engine id {:?} receive {:?}",
+                self.engine.id(),
+                msg
+            )));
+        }
+        self.adjudicator = adjudication::Adjudicator::new(msg.0);
+    }
+}
+
+// Score of the move found by the engine, from the point of view of color
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct EngineSearchScore {
+    color: square::Color,
+    score: i32,
+    move_number: u32,
+}
+impl EngineSearchScore {
+    pub fn new(color: square::Color, score: i32, move_number: u32) -> Self {
+        Self {
+            color,
+            score,
+            move_number,
+        }
+    }
+}
+impl Handler<EngineSearchScore> for EngineDispatcher {
+    type Result = ();
+
+    fn handle(&mut self, msg: EngineSearchScore, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "EngineDispatcher for engine id {:?} receive {:?}",
+                self.engine.id(),
+                msg
+            )));
+        }
+        self.adjudicator.record(msg.score);
+        if let Some(game_manager_actor) = &self.game_manager_actor_opt {
+            if self.adjudicator.should_resign() {
+                tracing::info!("Engine {} resigns", self.engine.id().name());
+                game_manager_actor.do_send(game_manager::handler_game::Resign(msg.color));
+            } else if self.adjudicator.accept_draw(msg.move_number) {
+                // the engine offers the draw it would accept
+                game_manager_actor.do_send(game_manager::handler_game::OfferDraw(msg.color));
+            }
+        }
+    }
+}
+
+// The opponent of color offers a draw to the engine playing color
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct EngineDrawOffer {
+    color: square::Color,
+    move_number: u32,
+    game_manager_actor: game_manager::GameManagerActor,
+}
+impl EngineDrawOffer {
+    pub fn new(
+        color: square::Color,
+        move_number: u32,
+        game_manager_actor: game_manager::GameManagerActor,
+    ) -> Self {
+        Self {
+            color,
+            move_number,
+            game_manager_actor,
+        }
+    }
+}
+impl Handler<EngineDrawOffer> for EngineDispatcher {
+    type Result = ();
+
+    fn handle(&mut self, msg: EngineDrawOffer, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "EngineDispatcher for engine id {:?} receive {:?}",
+                self.engine.id(),
+                msg
+            )));
+        }
+        if self.adjudicator.accept_draw(msg.move_number) {
+            msg.game_manager_actor
+                .do_send(game_manager::handler_game::AcceptDraw(msg.color));
+        } else {
+            msg.game_manager_actor
+                .do_send(game_manager::handler_game::DeclineDraw(msg.color));
+        }
+    }
+}
+
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct EngineStartThinking {
     game: game_state::GameState,
     game_manager_actor: game_manager::GameManagerActor,
-    uci_caller: uci_entity::UciActor,
+    uci_caller_opt: Option<uci_entity::UciActor>, // None: the best move is not displayed
     stat_actor_opt: Option<stat_entity::StatActor>,
 }
 impl EngineStartThinking {
     pub fn new(
         game: game_state::GameState,
         game_manager_actor: game_manager::GameManagerActor,
-        uci_caller: uci_entity::UciActor,
+        uci_caller_opt: Option<uci_entity::UciActor>,
         stat_actor_opt: Option<stat_entity::StatActor>,
     ) -> Self {
         EngineStartThinking {
             game,
             game_manager_actor,
-            uci_caller,
+            uci_caller_opt,
             stat_actor_opt,
         }
     }
@@ -334,7 +527,7 @@ impl Handler<EngineStartThinking> for EngineDispatcher {
     type Result = ();
 
     fn handle(&mut self, msg: EngineStartThinking, _ctx: &mut Self::Context) {
-        self.uci_caller_opt = Some(msg.uci_caller.clone());
+        self.uci_caller_opt = msg.uci_caller_opt.clone();
         self.stat_actor_opt = msg.stat_actor_opt.clone();
         if let Some(debug_actor) = &self.debug_actor_opt {
             debug_actor.do_send(debug::AddMessage(format!(
@@ -1,13 +1,18 @@
 pub mod engine_alphabeta;
 pub mod engine_dummy;
+pub mod engine_external;
 pub mod engine_iddfs;
 pub mod engine_logic;
 pub mod engine_mat;
 pub mod engine_mcts;
 pub mod engine_minimax;
+pub mod engine_pns;
+pub mod adjudication;
+pub mod bitbase;
 pub mod config;
 pub mod evaluation;
 pub mod feature;
+pub mod mcts_export;
 pub mod mcts_tree;
 pub mod search_state;
 pub mod time_allocation;
@@ -0,0 +1,113 @@
+// Engine side adjudication: when the engine resigns or accepts a draw offer.
+// Scores are from the point of view of the engine (1000 = one pawn).
+
+// resign when the score stays below -score for n_moves moves in a row
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResignRule {
+    pub score: i32,
+    pub n_moves: u32,
+}
+
+// accept a draw when |score| < score from move min_move_number
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DrawRule {
+    pub score: i32,
+    pub min_move_number: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AdjudicationConf {
+    pub resign_opt: Option<ResignRule>,
+    pub draw_opt: Option<DrawRule>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Adjudicator {
+    conf: AdjudicationConf,
+    last_score_opt: Option<i32>,
+    // number of moves in a row below the resign score
+    n_moves_lost: u32,
+}
+impl Adjudicator {
+    pub fn new(conf: AdjudicationConf) -> Self {
+        Self {
+            conf,
+            ..Default::default()
+        }
+    }
+    pub fn conf(&self) -> &AdjudicationConf {
+        &self.conf
+    }
+    // score of the move played by the engine
+    pub fn record(&mut self, score: i32) {
+        self.last_score_opt = Some(score);
+        match self.conf.resign_opt {
+            Some(rule) if score < rule.score.saturating_neg() => self.n_moves_lost += 1,
+            _ => self.n_moves_lost = 0,
+        }
+    }
+    pub fn should_resign(&self) -> bool {
+        self.conf
+            .resign_opt
+            .is_some_and(|rule| self.n_moves_lost >= rule.n_moves.max(1))
+    }
+    pub fn accept_draw(&self, move_number: u32) -> bool {
+        match (self.conf.draw_opt, self.last_score_opt) {
+            (Some(rule), Some(score)) => {
+                move_number >= rule.min_move_number && score.saturating_abs() < rule.score
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conf() -> AdjudicationConf {
+        AdjudicationConf {
+            resign_opt: Some(ResignRule {
+                score: 5_000,
+                n_moves: 3,
+            }),
+            draw_opt: Some(DrawRule {
+                score: 100,
+                min_move_number: 40,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_resign() {
+        let mut adjudicator = Adjudicator::new(conf());
+        adjudicator.record(-6_000);
+        adjudicator.record(-6_000);
+        assert!(!adjudicator.should_resign());
+        // the score goes up again: the count restarts
+        adjudicator.record(-1_000);
+        adjudicator.record(-6_000);
+        adjudicator.record(-7_000);
+        assert!(!adjudicator.should_resign());
+        adjudicator.record(-8_000);
+        assert!(adjudicator.should_resign());
+        // never resign without a rule
+        let mut adjudicator = Adjudicator::default();
+        for _ in 0..10 {
+            adjudicator.record(-100_000);
+        }
+        assert!(!adjudicator.should_resign());
+    }
+
+    #[test]
+    fn test_accept_draw() {
+        let mut adjudicator = Adjudicator::new(conf());
+        // no score yet
+        assert!(!adjudicator.accept_draw(50));
+        adjudicator.record(-50);
+        assert!(!adjudicator.accept_draw(39));
+        assert!(adjudicator.accept_draw(40));
+        adjudicator.record(300);
+        assert!(!adjudicator.accept_draw(50));
+    }
+}
@@ -0,0 +1,770 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::entity::game::component::bitboard::piece_move::GenMoves;
+use crate::entity::game::component::bitboard::zobrist;
+use crate::entity::game::component::{bitboard, game_state, square};
+use crate::ui::board;
+use crate::ui::notation::fen;
+
+// Win / draw / loss bitbases for endgames with a lone king, generated by retrograde analysis.
+// The strong side is white in the tables. Positions of the black strong side are probed
+// with the colors switched.
+// Index: side to move, white king (reduced by symmetry), black king, white pieces.
+// File: one header line followed by 2 bits per position.
+const FILE_HEADER: &str = "chess_rust-bitbase-v1";
+const FILE_EXTENSION: &str = "bb";
+
+const VALUE_DRAW: u8 = 0;
+const VALUE_WIN: u8 = 1;
+const VALUE_LOSS: u8 = 2;
+const VALUE_INVALID: u8 = 3;
+// generation only: not yet known
+const VALUE_UNKNOWN: u8 = 4;
+
+// largest number of pieces (kings included) of a table
+const MAX_PIECES: u32 = 4;
+
+// white king squares for tables without pawns: a1-d1-d4 triangle
+const KING_TRIANGLE: [u8; 10] = [0, 1, 2, 3, 9, 10, 11, 18, 19, 27];
+
+// result from the side to move perspective
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wdl {
+    Win,
+    Draw,
+    Loss,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BitbaseKind {
+    Kpk,
+    Krk,
+    Kqk,
+    Kbnk,
+}
+impl BitbaseKind {
+    // generation order: KPK needs KQK and KRK for promotions
+    pub const ALL: [BitbaseKind; 4] = [
+        BitbaseKind::Kqk,
+        BitbaseKind::Krk,
+        BitbaseKind::Kpk,
+        BitbaseKind::Kbnk,
+    ];
+    pub fn name(&self) -> &'static str {
+        match self {
+            BitbaseKind::Kpk => "KPK",
+            BitbaseKind::Krk => "KRK",
+            BitbaseKind::Kqk => "KQK",
+            BitbaseKind::Kbnk => "KBNK",
+        }
+    }
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                format!(
+                    "Unknown bitbase '{}'. Expected one of: {}",
+                    name,
+                    Self::ALL.map(|kind| kind.name()).join(", ")
+                )
+            })
+    }
+    // white pieces except the king
+    fn pieces(&self) -> &'static [square::TypePiece] {
+        match self {
+            BitbaseKind::Kpk => &[square::TypePiece::Pawn],
+            BitbaseKind::Krk => &[square::TypePiece::Rook],
+            BitbaseKind::Kqk => &[square::TypePiece::Queen],
+            BitbaseKind::Kbnk => &[square::TypePiece::Bishop, square::TypePiece::Knight],
+        }
+    }
+    // material of the strong side, the weak side has only its king
+    fn material_key(&self) -> u32 {
+        self.pieces()
+            .iter()
+            .map(|type_piece| 1 << (4 * *type_piece as u32))
+            .sum()
+    }
+    fn has_pawns(&self) -> bool {
+        self.pieces().contains(&square::TypePiece::Pawn)
+    }
+    fn n_king_squares(&self) -> usize {
+        if self.has_pawns() {
+            32
+        } else {
+            KING_TRIANGLE.len()
+        }
+    }
+    pub fn size(&self) -> usize {
+        2 * self.n_king_squares() * 64 * 64usize.pow(self.pieces().len() as u32)
+    }
+    fn symmetries(&self) -> &'static [u8] {
+        if self.has_pawns() {
+            // vertical axis only
+            &[0, 1]
+        } else {
+            &[0, 1, 2, 3, 4, 5, 6, 7]
+        }
+    }
+    fn king_square_index(&self, wk: u8) -> Option<usize> {
+        if self.has_pawns() {
+            (wk % 8 < 4).then_some((wk / 8 * 4 + wk % 8) as usize)
+        } else {
+            KING_TRIANGLE.iter().position(|sq| *sq == wk)
+        }
+    }
+    fn king_square(&self, idx: usize) -> u8 {
+        if self.has_pawns() {
+            (idx / 4 * 8 + idx % 4) as u8
+        } else {
+            KING_TRIANGLE[idx]
+        }
+    }
+}
+impl fmt::Display for BitbaseKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// Squares of a position: white king, black king, white pieces in BitbaseKind::pieces order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Placement {
+    white_to_move: bool,
+    squares: [u8; 4],
+}
+impl Placement {
+    fn wk(&self) -> u8 {
+        self.squares[0]
+    }
+    fn bk(&self) -> u8 {
+        self.squares[1]
+    }
+    fn with_square(&self, i: usize, sq: u8, white_to_move: bool) -> Self {
+        let mut squares = self.squares;
+        squares[i] = sq;
+        Self {
+            white_to_move,
+            squares,
+        }
+    }
+}
+
+// t: bit 0 mirror files, bit 1 mirror ranks, bit 2 mirror along the a1-h8 diagonal
+fn transform(t: u8, sq: u8) -> u8 {
+    let (mut row, mut col) = (sq / 8, sq % 8);
+    if t & 1 != 0 {
+        col = 7 - col;
+    }
+    if t & 2 != 0 {
+        row = 7 - row;
+    }
+    if t & 4 != 0 {
+        std::mem::swap(&mut row, &mut col);
+    }
+    row * 8 + col
+}
+
+fn distance(a: u8, b: u8) -> u8 {
+    let d_row = (a / 8).abs_diff(b / 8);
+    let d_col = (a % 8).abs_diff(b % 8);
+    d_row.max(d_col)
+}
+
+fn king_neighbours(sq: u8) -> impl Iterator<Item = u8> {
+    (0..64u8).filter(move |other| distance(sq, *other) == 1)
+}
+
+fn encode(kind: BitbaseKind, placement: &Placement) -> Option<usize> {
+    let mut idx = if placement.white_to_move { 0 } else { 1 };
+    idx = idx * kind.n_king_squares() + kind.king_square_index(placement.wk())?;
+    for sq in &placement.squares[1..2 + kind.pieces().len()] {
+        idx = idx * 64 + *sq as usize;
+    }
+    Some(idx)
+}
+
+fn decode(kind: BitbaseKind, mut idx: usize) -> Placement {
+    let n_pieces = kind.pieces().len();
+    let mut squares = [0u8; 4];
+    for i in (1..2 + n_pieces).rev() {
+        squares[i] = (idx % 64) as u8;
+        idx /= 64;
+    }
+    squares[0] = kind.king_square(idx % kind.n_king_squares());
+    idx /= kind.n_king_squares();
+    Placement {
+        white_to_move: idx == 0,
+        squares,
+    }
+}
+
+// the smallest index among the symmetrical positions
+fn canonical_index(kind: BitbaseKind, placement: &Placement) -> Option<usize> {
+    let n_squares = 2 + kind.pieces().len();
+    kind.symmetries()
+        .iter()
+        .filter_map(|t| {
+            let mut squares = placement.squares;
+            squares[..n_squares]
+                .iter_mut()
+                .for_each(|sq| *sq = transform(*t, *sq));
+            encode(
+                kind,
+                &Placement {
+                    white_to_move: placement.white_to_move,
+                    squares,
+                },
+            )
+        })
+        .min()
+}
+
+fn build_game(
+    kind: BitbaseKind,
+    placement: &Placement,
+    zobrist_table: &zobrist::Zobrist,
+) -> game_state::GameState {
+    let mut squares = [[square::Square::Empty; 8]; 8];
+    let mut add = |sq: u8, type_piece: square::TypePiece, color: square::Color| {
+        squares[(sq / 8) as usize][(sq % 8) as usize] =
+            square::Square::build_piece(type_piece, color);
+    };
+    add(
+        placement.wk(),
+        square::TypePiece::King,
+        square::Color::White,
+    );
+    add(
+        placement.bk(),
+        square::TypePiece::King,
+        square::Color::Black,
+    );
+    for (sq, type_piece) in placement.squares[2..].iter().zip(kind.pieces()) {
+        add(*sq, *type_piece, square::Color::White);
+    }
+    let mut status = fen::PositionStatus::default();
+    status.set_player_turn(if placement.white_to_move {
+        square::Color::White
+    } else {
+        square::Color::Black
+    });
+    let position = fen::Position::build(board::ChessBoard::build(squares), status);
+    game_state::GameState::new(position, zobrist_table)
+}
+
+// the side not to move is not in check
+fn is_valid(placement: &Placement, game: &game_state::GameState) -> bool {
+    let side_not_to_move = if placement.white_to_move {
+        square::Color::Black
+    } else {
+        square::Color::White
+    };
+    !game
+        .bit_position()
+        .bit_boards_white_and_black()
+        .check_status(&side_not_to_move)
+        .is_check()
+}
+
+// squares all different, pawns on ranks 2 to 7
+fn has_distinct_squares(kind: BitbaseKind, placement: &Placement) -> bool {
+    let squares = &placement.squares[..2 + kind.pieces().len()];
+    let pawns_ok = squares[2..]
+        .iter()
+        .zip(kind.pieces())
+        .all(|(sq, type_piece)| *type_piece != square::TypePiece::Pawn || (8..56).contains(sq));
+    let mut bits = 0u64;
+    squares.iter().for_each(|sq| bits |= 1 << sq);
+    pawns_ok && bits.count_ones() as usize == squares.len()
+}
+
+#[derive(Debug, Clone)]
+pub struct Bitbase {
+    kind: BitbaseKind,
+    data: Vec<u8>,
+}
+impl Bitbase {
+    fn new(kind: BitbaseKind) -> Self {
+        Self {
+            kind,
+            data: vec![0; kind.size().div_ceil(4)],
+        }
+    }
+    pub fn kind(&self) -> BitbaseKind {
+        self.kind
+    }
+    fn get(&self, idx: usize) -> u8 {
+        (self.data[idx / 4] >> (2 * (idx % 4))) & 3
+    }
+    fn set(&mut self, idx: usize, value: u8) {
+        let shift = 2 * (idx % 4);
+        self.data[idx / 4] = (self.data[idx / 4] & !(3 << shift)) | (value << shift);
+    }
+    fn probe_placement(&self, placement: &Placement) -> Option<Wdl> {
+        let idx = canonical_index(self.kind, placement)?;
+        match self.get(idx) {
+            VALUE_WIN => Some(Wdl::Win),
+            VALUE_LOSS => Some(Wdl::Loss),
+            VALUE_DRAW => Some(Wdl::Draw),
+            _ => None,
+        }
+    }
+    // (positions won for the side to move, lost, drawn)
+    pub fn count(&self) -> (usize, usize, usize) {
+        (0..self.kind.size()).fold((0, 0, 0), |(w, l, d), idx| match self.get(idx) {
+            VALUE_WIN => (w + 1, l, d),
+            VALUE_LOSS => (w, l + 1, d),
+            VALUE_DRAW => (w, l, d + 1),
+            _ => (w, l, d),
+        })
+    }
+
+    // Retrograde analysis: starting from the mates, positions are resolved backward
+    // by unmoving the pieces. A position with black to move is lost when all its moves
+    // lead to won positions. Tables needed for promotions are taken from 'bitbases'.
+    pub fn generate(kind: BitbaseKind, bitbases: &Bitbases) -> Self {
+        let zobrist_table = zobrist::Zobrist::new();
+        let mut values = vec![VALUE_INVALID; kind.size()];
+        let mut queue = VecDeque::new();
+        for (idx, value) in values.iter_mut().enumerate() {
+            let placement = decode(kind, idx);
+            if !has_distinct_squares(kind, &placement)
+                || canonical_index(kind, &placement) != Some(idx)
+            {
+                continue;
+            }
+            let game = build_game(kind, &placement, &zobrist_table);
+            if !is_valid(&placement, &game) {
+                continue;
+            }
+            *value = VALUE_UNKNOWN;
+            let moves = game.gen_moves();
+            if placement.white_to_move {
+                let is_win_by_promotion = moves
+                    .iter()
+                    .any(|m| promotion_result(kind, &placement, m, bitbases) == Some(Wdl::Loss));
+                if is_win_by_promotion {
+                    *value = VALUE_WIN;
+                    queue.push_back(idx);
+                }
+            } else if moves.is_empty() {
+                if game.check_status().is_check() {
+                    *value = VALUE_LOSS;
+                    queue.push_back(idx);
+                } else {
+                    *value = VALUE_DRAW;
+                }
+            }
+        }
+        while let Some(idx) = queue.pop_front() {
+            let placement = decode(kind, idx);
+            if placement.white_to_move {
+                // the position is won: was black lost before its last move ?
+                for bk in king_neighbours(placement.bk()) {
+                    let previous = placement.with_square(1, bk, false);
+                    if let Some(previous_idx) = canonical_index(kind, &previous) {
+                        if values[previous_idx] == VALUE_UNKNOWN
+                            && is_lost(kind, &previous, &values, &zobrist_table)
+                        {
+                            values[previous_idx] = VALUE_LOSS;
+                            queue.push_back(previous_idx);
+                        }
+                    }
+                }
+            } else {
+                // the position is lost: the previous positions are won
+                let game = build_game(kind, &placement, &zobrist_table);
+                for previous in white_unmoves(kind, &placement, &game) {
+                    if let Some(previous_idx) = canonical_index(kind, &previous) {
+                        if values[previous_idx] == VALUE_UNKNOWN {
+                            values[previous_idx] = VALUE_WIN;
+                            queue.push_back(previous_idx);
+                        }
+                    }
+                }
+            }
+        }
+        let mut bitbase = Self::new(kind);
+        for (idx, value) in values.into_iter().enumerate() {
+            bitbase.set(
+                idx,
+                if value == VALUE_UNKNOWN {
+                    VALUE_DRAW
+                } else {
+                    value
+                },
+            );
+        }
+        bitbase
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut content =
+            format!("{} {} {}\n", FILE_HEADER, self.kind, self.kind.size()).into_bytes();
+        content.extend_from_slice(&self.data);
+        fs::write(path, content)
+            .map_err(|err| format!("Cannot write '{}': {}", path.display(), err))
+    }
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content =
+            fs::read(path).map_err(|err| format!("Cannot read '{}': {}", path.display(), err))?;
+        let error = |msg: &str| format!("Invalid bitbase file '{}': {}", path.display(), msg);
+        let end_header = content
+            .iter()
+            .position(|b| *b == b'\n')
+            .ok_or_else(|| error("no header"))?;
+        let header = String::from_utf8_lossy(&content[..end_header]).to_string();
+        let fields: Vec<&str> = header.split_whitespace().collect();
+        if fields.len() != 3 || fields[0] != FILE_HEADER {
+            return Err(error("bad header"));
+        }
+        let kind = BitbaseKind::from_name(fields[1]).map_err(|err| error(&err))?;
+        if fields[2].parse::<usize>().ok() != Some(kind.size()) {
+            return Err(error("bad size"));
+        }
+        let data = content[end_header + 1..].to_vec();
+        if data.len() != kind.size().div_ceil(4) {
+            return Err(error("truncated data"));
+        }
+        Ok(Self { kind, data })
+    }
+}
+
+// result after a promotion from the side to move perspective (black)
+fn promotion_result(
+    kind: BitbaseKind,
+    placement: &Placement,
+    b_move: &bitboard::BitBoardMove,
+    bitbases: &Bitbases,
+) -> Option<Wdl> {
+    let type_piece = b_move.promotion()?.as_type_piece();
+    let sub_kind = match type_piece {
+        square::TypePiece::Queen => BitbaseKind::Kqk,
+        square::TypePiece::Rook => BitbaseKind::Krk,
+        _ => return Some(Wdl::Draw),
+    };
+    if kind != BitbaseKind::Kpk {
+        return None;
+    }
+    let promoted = placement.with_square(2, b_move.end().value(), false);
+    bitbases.get(sub_kind)?.probe_placement(&promoted)
+}
+
+// black to move: lost if each move leads to a won position (for white)
+fn is_lost(
+    kind: BitbaseKind,
+    placement: &Placement,
+    values: &[u8],
+    zobrist_table: &zobrist::Zobrist,
+) -> bool {
+    let game = build_game(kind, placement, zobrist_table);
+    let moves = game.gen_moves();
+    !moves.is_empty()
+        && moves.iter().all(|m| {
+            m.capture().is_none()
+                && canonical_index(kind, &placement.with_square(1, m.end().value(), true))
+                    .is_some_and(|idx| values[idx] == VALUE_WIN)
+        })
+}
+
+// positions (white to move) from which a white move leads to 'placement'
+fn white_unmoves(
+    kind: BitbaseKind,
+    placement: &Placement,
+    game: &game_state::GameState,
+) -> Vec<Placement> {
+    let bit_boards = game.bit_position().bit_boards_white_and_black();
+    let occupied = bit_boards.bit_board_white().concat_bit_boards().value()
+        | bit_boards.bit_board_black().concat_bit_boards().value();
+    let is_empty = |sq: u8| occupied & (1u64 << sq) == 0;
+    let mut previous = vec![];
+    for sq in king_neighbours(placement.wk()).filter(|sq| is_empty(*sq)) {
+        previous.push(placement.with_square(0, sq, true));
+    }
+    let control_squares = bit_boards.gen_square_control(&square::Color::White);
+    for piece_moves in control_squares.moves() {
+        let from = piece_moves.index().value();
+        let i = match placement.squares[2..2 + kind.pieces().len()]
+            .iter()
+            .position(|sq| *sq == from)
+        {
+            Some(i) => 2 + i,
+            None => continue,
+        };
+        for to in piece_moves.moves().iter() {
+            if is_empty(to.value()) {
+                previous.push(placement.with_square(i, to.value(), true));
+            }
+        }
+    }
+    for (i, type_piece) in kind.pieces().iter().enumerate() {
+        if *type_piece != square::TypePiece::Pawn {
+            continue;
+        }
+        let sq = placement.squares[2 + i];
+        // a pawn on the 2nd rank has not moved
+        if sq >= 16 && is_empty(sq - 8) {
+            previous.push(placement.with_square(2 + i, sq - 8, true));
+            if sq / 8 == 3 && is_empty(sq - 16) {
+                previous.push(placement.with_square(2 + i, sq - 16, true));
+            }
+        }
+    }
+    previous
+}
+
+// number of pieces of each type except the king, 4 bits per type
+fn material_key(bit_board: &bitboard::BitBoards) -> u32 {
+    [
+        square::TypePiece::Rook,
+        square::TypePiece::Knight,
+        square::TypePiece::Bishop,
+        square::TypePiece::Queen,
+        square::TypePiece::Pawn,
+    ]
+    .into_iter()
+    .map(|type_piece| bit_board.get_bitboard(type_piece).count_ones() << (4 * type_piece as u32))
+    .sum()
+}
+
+#[derive(Debug, Default)]
+pub struct Bitbases {
+    // key: material of the strong side
+    tables: HashMap<u32, Bitbase>,
+}
+impl Bitbases {
+    pub fn get(&self, kind: BitbaseKind) -> Option<&Bitbase> {
+        self.tables.get(&kind.material_key())
+    }
+    pub fn insert(&mut self, bitbase: Bitbase) {
+        self.tables.insert(bitbase.kind().material_key(), bitbase);
+    }
+    pub fn kinds(&self) -> Vec<BitbaseKind> {
+        BitbaseKind::ALL
+            .into_iter()
+            .filter(|kind| self.get(*kind).is_some())
+            .collect()
+    }
+    // generate the missing tables (and the tables they depend on)
+    pub fn generate(kinds: &[BitbaseKind]) -> Self {
+        let mut bitbases = Self::default();
+        let needs_promotion_tables = kinds.contains(&BitbaseKind::Kpk);
+        for kind in BitbaseKind::ALL {
+            let is_needed = kinds.contains(&kind)
+                || needs_promotion_tables && matches!(kind, BitbaseKind::Kqk | BitbaseKind::Krk);
+            if is_needed {
+                let bitbase = Bitbase::generate(kind, &bitbases);
+                bitbases.insert(bitbase);
+            }
+        }
+        bitbases
+    }
+    pub fn file_name(dir: &Path, kind: BitbaseKind) -> std::path::PathBuf {
+        dir.join(format!("{}.{}", kind.name().to_lowercase(), FILE_EXTENSION))
+    }
+    pub fn save_dir(&self, dir: &Path) -> Result<(), String> {
+        fs::create_dir_all(dir)
+            .map_err(|err| format!("Cannot create '{}': {}", dir.display(), err))?;
+        for bitbase in self.tables.values() {
+            bitbase.save(&Self::file_name(dir, bitbase.kind()))?;
+        }
+        Ok(())
+    }
+    // load the tables found in 'dir'
+    pub fn load_dir(dir: &Path) -> Result<Self, String> {
+        let mut bitbases = Self::default();
+        for kind in BitbaseKind::ALL {
+            let path = Self::file_name(dir, kind);
+            if path.exists() {
+                bitbases.insert(Bitbase::load(&path)?);
+            }
+        }
+        Ok(bitbases)
+    }
+
+    // exact result from the side to move perspective
+    pub fn probe(&self, bit_position: &bitboard::BitPosition) -> Option<Wdl> {
+        let bit_boards = bit_position.bit_boards_white_and_black();
+        let n_pieces = (bit_boards.bit_board_white().concat_bit_boards()
+            | bit_boards.bit_board_black().concat_bit_boards())
+        .count_ones();
+        if n_pieces > MAX_PIECES {
+            return None;
+        }
+        let white_to_move =
+            bit_position.bit_position_status().player_turn() == square::Color::White;
+        let (bitbase, white_is_strong) = match (
+            material_key(bit_boards.bit_board_white()),
+            material_key(bit_boards.bit_board_black()),
+        ) {
+            (key, 0) => (self.tables.get(&key)?, true),
+            (0, key) => (self.tables.get(&key)?, false),
+            _ => return None,
+        };
+        let placement = placement_from(bitbase.kind(), bit_boards, white_to_move, white_is_strong);
+        bitbase.probe_placement(&placement)
+    }
+}
+
+// switch the colors if black is the strong side
+fn placement_from(
+    kind: BitbaseKind,
+    bit_boards: &bitboard::BitBoardsWhiteAndBlack,
+    white_to_move: bool,
+    white_is_strong: bool,
+) -> Placement {
+    let (strong, weak, flip) = if white_is_strong {
+        (square::Color::White, square::Color::Black, 0)
+    } else {
+        (square::Color::Black, square::Color::White, 56)
+    };
+    let mut squares = [0u8; 4];
+    squares[0] = bit_boards
+        .bit_board(&strong)
+        .king()
+        .bitboard()
+        .index()
+        .value()
+        ^ flip;
+    squares[1] = bit_boards
+        .bit_board(&weak)
+        .king()
+        .bitboard()
+        .index()
+        .value()
+        ^ flip;
+    for (i, type_piece) in kind.pieces().iter().enumerate() {
+        squares[2 + i] = bit_boards
+            .bit_board(&strong)
+            .get_bitboard(*type_piece)
+            .index()
+            .value()
+            ^ flip;
+    }
+    Placement {
+        white_to_move: white_to_move == white_is_strong,
+        squares,
+    }
+}
+
+static BITBASES: OnceLock<Bitbases> = OnceLock::new();
+
+// set the tables used by the engines (once, at start-up)
+pub fn init(bitbases: Bitbases) -> Result<(), String> {
+    BITBASES
+        .set(bitbases)
+        .map_err(|_| "Bitbases already initialized".to_string())
+}
+
+pub fn probe(bit_position: &bitboard::BitPosition) -> Option<Wdl> {
+    BITBASES.get()?.probe(bit_position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::notation::fen::EncodeUserInput;
+
+    fn probe_fen(bitbases: &Bitbases, fen_str: &str) -> Option<Wdl> {
+        let position = fen::Fen::decode(fen_str).expect("Failed to decode FEN");
+        bitbases.probe(&bitboard::BitPosition::from(position))
+    }
+
+    #[test]
+    fn test_index() {
+        for kind in [BitbaseKind::Kpk, BitbaseKind::Kbnk] {
+            for idx in [0, 12345, kind.size() - 1] {
+                assert_eq!(encode(kind, &decode(kind, idx)), Some(idx));
+            }
+        }
+        assert_eq!(transform(4, 1), 8);
+        assert_eq!(transform(3, 0), 63);
+    }
+
+    #[test]
+    fn test_krk() {
+        let bitbases = Bitbases::generate(&[BitbaseKind::Krk]);
+        assert_eq!(
+            probe_fen(&bitbases, "8/8/8/3k4/8/8/8/R3K3 w - - 0 1"),
+            Some(Wdl::Win)
+        );
+        // black to move captures the rook
+        assert_eq!(
+            probe_fen(&bitbases, "8/8/8/8/8/8/k7/R1K5 b - - 0 1"),
+            Some(Wdl::Draw)
+        );
+        // black is the strong side
+        assert_eq!(
+            probe_fen(&bitbases, "r3k3/8/8/8/8/8/8/3K4 w - - 0 1"),
+            Some(Wdl::Loss)
+        );
+        // stalemate
+        assert_eq!(
+            probe_fen(&bitbases, "7k/5KR1/8/8/8/8/8/8 b - - 0 1"),
+            Some(Wdl::Draw)
+        );
+        assert_eq!(probe_fen(&bitbases, fen::FEN_START_POSITION), None);
+        // no table for 4 pieces with material on both sides
+        assert_eq!(
+            probe_fen(&bitbases, "r3k3/8/8/8/8/8/8/R3K3 w - - 0 1"),
+            None
+        );
+        let (_, n_loss, _) = bitbases.get(BitbaseKind::Krk).unwrap().count();
+        assert!(n_loss > 0);
+    }
+
+    // slow in debug mode (KQK and KRK are generated for the promotions)
+    #[test]
+    #[ignore]
+    fn test_kpk() {
+        let bitbases = Bitbases::generate(&[BitbaseKind::Kpk]);
+        // the white king in front of its pawn on the 6th rank
+        assert_eq!(
+            probe_fen(&bitbases, "3k4/8/3K4/3P4/8/8/8/8 w - - 0 1"),
+            Some(Wdl::Win)
+        );
+        assert_eq!(
+            probe_fen(&bitbases, "3k4/8/3K4/3P4/8/8/8/8 b - - 0 1"),
+            Some(Wdl::Loss)
+        );
+        // the black king blocks the pawn
+        assert_eq!(
+            probe_fen(&bitbases, "8/8/8/8/8/4k3/4P3/4K3 w - - 0 1"),
+            Some(Wdl::Draw)
+        );
+        // rook pawn
+        assert_eq!(
+            probe_fen(&bitbases, "k7/8/8/P7/8/8/8/4K3 w - - 0 1"),
+            Some(Wdl::Draw)
+        );
+        // black pawn: white is stalemated
+        assert_eq!(
+            probe_fen(&bitbases, "8/8/8/8/8/3k4/3p4/3K4 w - - 0 1"),
+            Some(Wdl::Draw)
+        );
+        assert_eq!(
+            probe_fen(&bitbases, "8/8/8/8/8/3k4/3p4/5K2 b - - 0 1"),
+            Some(Wdl::Win)
+        );
+    }
+
+    #[test]
+    fn test_save_load() {
+        let bitbases = Bitbases::generate(&[BitbaseKind::Kqk]);
+        let dir = std::env::temp_dir().join(format!("chess_rust_bitbases_{}", std::process::id()));
+        bitbases.save_dir(&dir).unwrap();
+        let loaded = Bitbases::load_dir(&dir).unwrap();
+        assert_eq!(loaded.kinds(), vec![BitbaseKind::Kqk]);
+        assert_eq!(
+            loaded.get(BitbaseKind::Kqk).unwrap().data,
+            bitbases.get(BitbaseKind::Kqk).unwrap().data
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -37,6 +37,7 @@ pub struct AlphabetaFeatureConf {
     pub f_capture_horizon: bool,
     pub f_check_horizon: bool,
     pub f_cannot_win_force_null: bool,
+    pub f_bitbase: bool,
 }
 impl fmt::Display for AlphabetaFeatureConf {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -47,7 +48,8 @@ impl fmt::Display for AlphabetaFeatureConf {
         writeln!(f, "f_killer_move: {}", self.f_killer_move)?;
         writeln!(f, "f_capture_horizon: {}", self.f_capture_horizon)?;
         writeln!(f, "f_check_horizon: {}", self.f_check_horizon)?;
-        writeln!(f, "f_cannot_win_force_null: {}", self.f_cannot_win_force_null)
+        writeln!(f, "f_cannot_win_force_null: {}", self.f_cannot_win_force_null)?;
+        writeln!(f, "f_bitbase: {}", self.f_bitbase)
     }
 }impl AlphabetaFeatureConf {
     pub fn default() -> Self {
@@ -60,9 +62,45 @@ impl fmt::Display for AlphabetaFeatureConf {
             f_capture_horizon: feature::FEATURE_CAPTURE_HORIZON,
             f_check_horizon: feature::FEATURE_CHECK_HORIZON,
             f_cannot_win_force_null: feature::FEATURE_CANNOT_WIN_FORCE_NULL,
+            f_bitbase: feature::FEATURE_BITBASE,
         }
     }
 }
+impl AlphabetaFeatureConf {
+    pub fn set_feature(&mut self, feature: &str, is_on: bool) -> Result<(), String> {
+        let flag = match feature {
+            "null_move_pruning" => &mut self.f_null_move_pruning,
+            "transposition_table" => &mut self.f_transposition_table,
+            "preorder" => &mut self.f_preorder,
+            "lmr" => &mut self.f_lmr,
+            "killer_move" => &mut self.f_killer_move,
+            "capture_horizon" => &mut self.f_capture_horizon,
+            "check_horizon" => &mut self.f_check_horizon,
+            "cannot_win_force_null" => &mut self.f_cannot_win_force_null,
+            "bitbase" => &mut self.f_bitbase,
+            _ => {
+                return Err(format!(
+                    "Unknown alphabeta feature '{}'. Expected one of: {}",
+                    feature,
+                    ALPHABETA_FEATURES.join(", ")
+                ))
+            }
+        };
+        *flag = is_on;
+        Ok(())
+    }
+}
+pub const ALPHABETA_FEATURES: [&str; 9] = [
+    "null_move_pruning",
+    "transposition_table",
+    "preorder",
+    "lmr",
+    "killer_move",
+    "capture_horizon",
+    "check_horizon",
+    "cannot_win_force_null",
+    "bitbase",
+];
 impl AlphabetaConf {
     pub fn new(max_depth: u8, features: AlphabetaFeatureConf) -> Self {
         Self {
@@ -107,6 +145,24 @@ impl IddfsFeatureConf {
         }
     }
 }
+impl IddfsFeatureConf {
+    pub fn set_feature(&mut self, feature: &str, is_on: bool) -> Result<(), String> {
+        let flag = match feature {
+            "mat_solver" => &mut self.f_mat_solver,
+            "aspiration_window" => &mut self.f_aspiration_window,
+            _ => {
+                return Err(format!(
+                    "Unknown iddfs feature '{}'. Expected one of: {}",
+                    feature,
+                    IDDFS_FEATURES.join(", ")
+                ))
+            }
+        };
+        *flag = is_on;
+        Ok(())
+    }
+}
+pub const IDDFS_FEATURES: [&str; 2] = ["mat_solver", "aspiration_window"];
 impl fmt::Display for IddfsFeatureConf {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "f_mat_solver: {}", self.f_mat_solver)?;
@@ -131,15 +187,256 @@ impl MatConfig {
     }
 }
 
+// the search depth in half moves (2 * max_mate_moves - 1) must fit in a u8
+pub const MAX_PNS_MATE_MOVES: u8 = 127;
+
+// Proof-number search of a mate: mate in at most max_mate_moves moves
+#[derive(Debug, Clone)]
+pub struct PnsConfig {
+    pub max_mate_moves: u8,
+    // the search gives up after this number of expanded nodes
+    pub max_nodes: u64,
+}
+impl fmt::Display for PnsConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "max_mate_moves: {}", self.max_mate_moves)?;
+        writeln!(f, "max_nodes: {}", self.max_nodes)
+    }
+}
+impl PnsConfig {
+    pub fn new(max_mate_moves: u8) -> Self {
+        assert!(
+            max_mate_moves <= MAX_PNS_MATE_MOVES,
+            "mate in {} moves is beyond the limit of {}",
+            max_mate_moves,
+            MAX_PNS_MATE_MOVES
+        );
+        Self {
+            max_mate_moves,
+            max_nodes: 200_000,
+        }
+    }
+}
+
+// How moves are chosen during a MCTS playout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayoutPolicy {
+    Random,
+    // captures, promotions and checks are more likely to be played
+    CaptureCheckBiased,
+    // softmax over the evaluation of each move
+    Softmax,
+}
+impl PlayoutPolicy {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "random" => Ok(Self::Random),
+            "capture_check" => Ok(Self::CaptureCheckBiased),
+            "softmax" => Ok(Self::Softmax),
+            _ => Err(format!(
+                "Unknown playout policy '{}'. Expected one of: random, capture_check, softmax",
+                name
+            )),
+        }
+    }
+}
+impl fmt::Display for PlayoutPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Random => "random",
+            Self::CaptureCheckBiased => "capture_check",
+            Self::Softmax => "softmax",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// How a child is selected in the MCTS tree
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SelectionFormula {
+    Ucb1,
+    // Q + c_puct * prior * sqrt(N) / (1 + n)
+    Puct,
+}
+impl SelectionFormula {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "ucb1" => Ok(Self::Ucb1),
+            "puct" => Ok(Self::Puct),
+            _ => Err(format!(
+                "Unknown selection formula '{}'. Expected one of: ucb1, puct",
+                name
+            )),
+        }
+    }
+}
+impl fmt::Display for SelectionFormula {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Ucb1 => "ucb1",
+            Self::Puct => "puct",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Prior probabilities of the moves of a MCTS node
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PriorSource {
+    // same prior for all moves, untried moves are expanded at random
+    Uniform,
+    // move ordering: captures (most valuable victim first), promotions and checks
+    Heuristic,
+    // softmax over the evaluation of each move
+    Evaluator,
+}
+impl PriorSource {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "uniform" => Ok(Self::Uniform),
+            "heuristic" => Ok(Self::Heuristic),
+            "evaluator" => Ok(Self::Evaluator),
+            _ => Err(format!(
+                "Unknown prior source '{}'. Expected one of: uniform, heuristic, evaluator",
+                name
+            )),
+        }
+    }
+}
+impl fmt::Display for PriorSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Uniform => "uniform",
+            Self::Heuristic => "heuristic",
+            Self::Evaluator => "evaluator",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TreeExportFormat {
+    // Graphviz
+    Dot,
+    Json,
+}
+impl TreeExportFormat {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "dot" => Ok(Self::Dot),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "Unknown tree export format '{}'. Expected one of: dot, json",
+                name
+            )),
+        }
+    }
+}
+impl fmt::Display for TreeExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Dot => "dot",
+            Self::Json => "json",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeExportConf {
+    pub path: String,
+    pub format: TreeExportFormat,
+    // depth of the deepest nodes exported (the root is at depth 0)
+    pub max_depth: usize,
+    // nodes with fewer visits are not exported, nor their subtree
+    pub min_visits: u64,
+}
+impl fmt::Display for TreeExportConf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}, max_depth: {}, min_visits: {})",
+            self.path, self.format, self.max_depth, self.min_visits
+        )
+    }
+}
+impl TreeExportConf {
+    // the format is deduced from the file extension: json for '.json', dot otherwise
+    pub fn new(path: &str) -> Self {
+        let format = if path.to_lowercase().ends_with(".json") {
+            TreeExportFormat::Json
+        } else {
+            TreeExportFormat::Dot
+        };
+        Self {
+            path: path.to_string(),
+            format,
+            max_depth: 3,
+            min_visits: 1,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MctsConfig {
     pub iterations_per_move: u64,
     pub c: f64,
+    pub playout: PlayoutPolicy,
+    // the playout is stopped after this number of half moves and the position is evaluated (0: play until the end)
+    pub playout_cutoff_plies: u64,
+    // evaluation converted to a win probability: 1 / (1 + exp(-eval / sigmoid_scale))
+    pub sigmoid_scale: f64,
+    // in evaluation unit: the lower, the greedier the softmax playout
+    pub softmax_temperature: f64,
+    // weights added to the weight 1 of a move in a capture/check biased playout
+    pub capture_weight: f64,
+    pub check_weight: f64,
+    pub selection: SelectionFormula,
+    pub c_puct: f64,
+    pub prior: PriorSource,
+    // in evaluation unit, for the evaluator priors
+    pub prior_temperature: f64,
+    // progressive widening: a node has at most max(1, widening_k * n_visits^widening_alpha) children (0: no limit)
+    pub widening_k: f64,
+    pub widening_alpha: f64,
+    // the tree of the previous move is kept if the position is found in it
+    pub f_tree_reuse: bool,
+    // maximum number of tree iterations per move, the search also stops at the soft time limit
+    pub node_budget_opt: Option<u64>,
+    // number of threads searching the same tree
+    pub threads: usize,
+    // visits counted as losses for a node being searched by another thread
+    pub virtual_loss: u64,
+    // seed of the random generators (the search is deterministic with one thread and a node budget)
+    pub seed_opt: Option<u64>,
+    // the tree is written to a file after each search
+    pub tree_export_opt: Option<TreeExportConf>,
 }
 impl fmt::Display for MctsConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "iterations_per_move: {}", self.iterations_per_move)?;
-        writeln!(f, "c: {}", self.c)
+        writeln!(f, "c: {}", self.c)?;
+        writeln!(f, "playout: {}", self.playout)?;
+        writeln!(f, "playout_cutoff_plies: {}", self.playout_cutoff_plies)?;
+        writeln!(f, "sigmoid_scale: {}", self.sigmoid_scale)?;
+        writeln!(f, "softmax_temperature: {}", self.softmax_temperature)?;
+        writeln!(f, "capture_weight: {}", self.capture_weight)?;
+        writeln!(f, "check_weight: {}", self.check_weight)?;
+        writeln!(f, "selection: {}", self.selection)?;
+        writeln!(f, "c_puct: {}", self.c_puct)?;
+        writeln!(f, "prior: {}", self.prior)?;
+        writeln!(f, "prior_temperature: {}", self.prior_temperature)?;
+        writeln!(f, "widening_k: {}", self.widening_k)?;
+        writeln!(f, "widening_alpha: {}", self.widening_alpha)?;
+        writeln!(f, "f_tree_reuse: {}", self.f_tree_reuse)?;
+        writeln!(f, "node_budget: {:?}", self.node_budget_opt)?;
+        writeln!(f, "threads: {}", self.threads)?;
+        writeln!(f, "virtual_loss: {}", self.virtual_loss)?;
+        writeln!(f, "seed: {:?}", self.seed_opt)?;
+        match &self.tree_export_opt {
+            Some(tree_export) => writeln!(f, "tree_export: {}", tree_export),
+            None => writeln!(f, "tree_export: none"),
+        }
     }
 }
 impl MctsConfig {
@@ -147,6 +444,117 @@ impl MctsConfig {
         Self {
             iterations_per_move,
             c: 1.0,
+            playout: PlayoutPolicy::Random,
+            playout_cutoff_plies: 0,
+            sigmoid_scale: 1500.0,
+            softmax_temperature: 300.0,
+            capture_weight: 4.0,
+            check_weight: 2.0,
+            selection: SelectionFormula::Ucb1,
+            c_puct: 1.5,
+            prior: PriorSource::Uniform,
+            prior_temperature: 500.0,
+            widening_k: 0.0,
+            widening_alpha: 0.5,
+            f_tree_reuse: true,
+            node_budget_opt: None,
+            threads: 1,
+            virtual_loss: 3,
+            seed_opt: None,
+            tree_export_opt: None,
+        }
+    }
+}
+// Evaluation terms: each term can be switched off and weighted.
+// Weights are expressed with the evaluation unit (1000 = 1 pawn), mobility in percent of the mobility tables.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalConf {
+    pub f_mobility: bool,
+    pub f_bishop_pair: bool,
+    pub f_rook_open_file: bool,
+    pub f_rook_7th_rank: bool,
+    pub f_knight_outpost: bool,
+    pub f_bad_bishop: bool,
+    pub f_endgame: bool,
+    pub w_mobility: i32,
+    pub w_bishop_pair: i32,
+    pub w_rook_open_file: i32,
+    pub w_rook_semi_open_file: i32,
+    pub w_rook_7th_rank: i32,
+    pub w_knight_outpost: i32,
+    pub w_bad_bishop: i32,
+}
+impl Default for EvalConf {
+    fn default() -> Self {
+        Self {
+            f_mobility: feature::FEATURE_EVAL_MOBILITY,
+            f_bishop_pair: feature::FEATURE_EVAL_BISHOP_PAIR,
+            f_rook_open_file: feature::FEATURE_EVAL_ROOK_OPEN_FILE,
+            f_rook_7th_rank: feature::FEATURE_EVAL_ROOK_7TH_RANK,
+            f_knight_outpost: feature::FEATURE_EVAL_KNIGHT_OUTPOST,
+            f_bad_bishop: feature::FEATURE_EVAL_BAD_BISHOP,
+            f_endgame: feature::FEATURE_EVAL_ENDGAME,
+            w_mobility: 100,
+            w_bishop_pair: 400,
+            w_rook_open_file: 250,
+            w_rook_semi_open_file: 100,
+            w_rook_7th_rank: 200,
+            w_knight_outpost: 250,
+            w_bad_bishop: 30,
         }
     }
-}
\ No newline at end of file
+}
+impl EvalConf {
+    // all terms switched off: material and control squares only
+    pub fn none() -> Self {
+        let mut conf = Self::default();
+        for term in EVAL_TERMS {
+            conf.set_term(term, false).unwrap();
+        }
+        conf
+    }
+    pub fn set_term(&mut self, term: &str, is_on: bool) -> Result<(), String> {
+        let flag = match term {
+            "mobility" => &mut self.f_mobility,
+            "bishop_pair" => &mut self.f_bishop_pair,
+            "rook_open_file" => &mut self.f_rook_open_file,
+            "rook_7th_rank" => &mut self.f_rook_7th_rank,
+            "knight_outpost" => &mut self.f_knight_outpost,
+            "bad_bishop" => &mut self.f_bad_bishop,
+            "endgame" => &mut self.f_endgame,
+            _ => {
+                return Err(format!(
+                    "Unknown evaluation term '{}'. Expected one of: {}",
+                    term,
+                    EVAL_TERMS.join(", ")
+                ))
+            }
+        };
+        *flag = is_on;
+        Ok(())
+    }
+}
+pub const EVAL_TERMS: [&str; 7] = [
+    "mobility",
+    "bishop_pair",
+    "rook_open_file",
+    "rook_7th_rank",
+    "knight_outpost",
+    "bad_bishop",
+    "endgame",
+];
+impl fmt::Display for EvalConf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "f_mobility: {} ({}%)", self.f_mobility, self.w_mobility)?;
+        writeln!(f, "f_bishop_pair: {} ({})", self.f_bishop_pair, self.w_bishop_pair)?;
+        writeln!(
+            f,
+            "f_rook_open_file: {} ({} / semi-open {})",
+            self.f_rook_open_file, self.w_rook_open_file, self.w_rook_semi_open_file
+        )?;
+        writeln!(f, "f_rook_7th_rank: {} ({})", self.f_rook_7th_rank, self.w_rook_7th_rank)?;
+        writeln!(f, "f_knight_outpost: {} ({})", self.f_knight_outpost, self.w_knight_outpost)?;
+        writeln!(f, "f_bad_bishop: {} ({})", self.f_bad_bishop, self.w_bad_bishop)?;
+        writeln!(f, "f_endgame: {}", self.f_endgame)
+    }
+}
@@ -3,9 +3,11 @@ use std::sync::Arc;
 
 use actix::Addr;
 
+use super::bitbase;
 use super::config::config;
 use super::engine_logic::{self as logic, Engine};
-use super::evaluation::{self, score, stat_eval};
+use super::time_allocation;
+use super::evaluation::{self, evaluator, score, stat_eval};
 use super::search_state;
 use crate::entity::engine::actor::engine_dispatcher as dispatcher;
 use crate::entity::engine::component::engine_mat;
@@ -24,6 +26,7 @@ pub struct EngineAlphaBeta {
     conf: config::AlphabetaConf,
     engine_mat_solver: engine_mat::EngineMat,
     is_send_best_move: bool,
+    evaluator: Arc<dyn evaluator::Evaluator>,
 }
 impl EngineAlphaBeta {
     pub fn new(
@@ -46,11 +49,36 @@ impl EngineAlphaBeta {
                 &config::MatConfig::new(2*max_depth -1),
             ),
             is_send_best_move,
+            evaluator: Arc::new(evaluator::ClassicEvaluator::default()),
         }
     }
     pub fn set_id_number(&mut self, id_number: &str) {
         self.id_number = id_number.to_string();
     }
+    pub fn set_evaluator(&mut self, evaluator: Arc<dyn evaluator::Evaluator>) {
+        self.evaluator = evaluator;
+    }
+    pub fn zobrist_table(&self) -> &zobrist::Zobrist {
+        &self.zobrist_table
+    }
+    pub fn evaluator(&self) -> &Arc<dyn evaluator::Evaluator> {
+        &self.evaluator
+    }
+
+    // play a move and keep the evaluator state in sync
+    fn play_move(
+        &self,
+        game: &mut game_state::GameState,
+        m: bitboard::BitBoardMove,
+        stat_eval: &mut stat_eval::StatEval,
+    ) {
+        game.make_move(&m, &self.zobrist_table);
+        self.evaluator.make_move(&m, stat_eval);
+    }
+    fn play_back(&self, game: &mut game_state::GameState, stat_eval: &mut stat_eval::StatEval) {
+        game.unmake_move();
+        self.evaluator.unmake_move(stat_eval);
+    }
 
     fn set_preorder(
         m: &bitboard::BitBoardMove,
@@ -99,10 +127,7 @@ impl EngineAlphaBeta {
         }
         let mut moves_status_with_preorder: Vec<(score::MoveStatus, score::PreOrder)> = vec![];
         for m in moves {
-            let long_algebric_move = long_notation::LongAlgebricNotationMove::build_from_b_move(*m);
-            //println!("playing level 0: {}", long_algebraic_move.cast());
-            game.play_moves(&[long_algebric_move], &self.zobrist_table, None, false)
-                .unwrap();
+            game.make_move(m, &self.zobrist_table);
             game.update_endgame_status();
             let move_info_opt = transposition_table.get_move_info(&game.last_hash(), 0);
             let preorder = match move_info_opt.map(|move_info| move_info.move_score().clone()) {
@@ -121,7 +146,7 @@ impl EngineAlphaBeta {
             };
             let move_status = score::MoveStatus::from_move(*m);
             moves_status_with_preorder.push((move_status, preorder));
-            game.play_back()
+            game.unmake_move()
         }
         moves_status_with_preorder.sort_by(|a, b| score::preorder_compare(&a.1, &b.1, is_asc));
         moves_status_with_preorder.into_iter().collect()
@@ -154,6 +179,7 @@ impl EngineAlphaBeta {
         if let Some(mat_move) = mat_move_opt {
             return *mat_move.bitboard_move();
         }
+        self.evaluator.init(&game_clone, &mut stat_eval);
 
         let b_move_score = self.alphabeta_inc_rec(
             "",
@@ -221,7 +247,7 @@ impl EngineAlphaBeta {
         let mut best_move_score_opt: Option<score::BitboardMoveScore> = None;
 
         let mut moves = game.gen_moves();
-        if is_stop.load(Ordering::Relaxed) {
+        if is_stop.load(Ordering::Relaxed) || stat_eval.is_node_limit_reached() {
             tracing::debug!("Interrupt alphabeta for current_depth: {}", current_depth);
             let mv = score::BitboardMoveScore::new(
                 *moves.first().unwrap(),
@@ -248,7 +274,7 @@ impl EngineAlphaBeta {
 
         // alpha beta
         for (idx, (m_status, preorder)) in moves_status.iter().enumerate() {
-            if idx > 1 && is_stop.load(Ordering::Relaxed) {
+            if idx > 1 && (is_stop.load(Ordering::Relaxed) || stat_eval.is_node_limit_reached()) {
                 tracing::debug!(
                     "Loop interrupt alphabeta for current_depth: {}",
                     current_depth
@@ -427,12 +453,10 @@ impl EngineAlphaBeta {
         state: &mut search_state::SearchState,
         is_stop: &Arc<AtomicBool>,
     ) -> score::Score {
-        let long_algebraic_move = long_notation::LongAlgebricNotationMove::build_from_b_move(m);
         // if current_depth >= 0 {
         //    println!("{}", variant);
         // }
-        game.play_moves(&[long_algebraic_move], &self.zobrist_table, None, false)
-            .unwrap();
+        self.play_move(game, m, stat_eval);
         // check if the current position has been already evaluated
         let hash = game.last_hash();
         if let Some(move_info) = transposition_table.get_move_info(&hash, max_depth - current_depth)
@@ -441,13 +465,19 @@ impl EngineAlphaBeta {
             if stat_eval.inc_n_transposition_hit() % 1_000_000 == 0 {
                 tracing::debug!("hits: {}", stat_eval.n_transposition_hit());
             }
-            game.play_back();
+            self.play_back(game, stat_eval);
             //println!("transposition {}: {} / {} =>  {}: {}", long_algebraic_move.cast(), current_depth, max_depth, move_score.get_variant(), move_score.score());
             return *move_info.move_score().score();
         };
 
         game.update_endgame_status();
-        let score = if game.end_game() == game_state::EndGame::None {
+        // exact draw known from the bitbases: no need to search further
+        let is_bitbase_draw = self.conf.alpha_beta_features.f_bitbase
+            && game.end_game() == game_state::EndGame::None
+            && bitbase::probe(game.bit_position()) == Some(bitbase::Wdl::Draw);
+        let score = if is_bitbase_draw {
+            score::Score::new(0, current_depth, max_depth)
+        } else if game.end_game() == game_state::EndGame::None {
             if !Self::goal_is_reached(current_depth >= max_depth, game.end_game()) {
                 //println!("Rec analysis of: {} - {} {} {:?}", variant, current_depth, max_depth, m.capture());
                 // null move pruning
@@ -484,12 +514,12 @@ impl EngineAlphaBeta {
                         //println!("end null move {}", variant);
                         game.play_back_null_move();
                         if is_max && score_after_null_move.score().value() >= beta_opt.unwrap() {
-                            game.play_back();
+                            self.play_back(game, stat_eval);
                             return score::Score::new(beta_opt.unwrap(), current_depth, max_depth);
                         } else if !is_max
                             && score_after_null_move.score().value() <= alpha_opt.unwrap()
                         {
-                            game.play_back();
+                            self.play_back(game, stat_eval);
                             return score::Score::new(alpha_opt.unwrap(), current_depth, max_depth);
                         }
                     } else {
@@ -564,7 +594,8 @@ impl EngineAlphaBeta {
                     score
                 } else {
                     score::Score::new(
-                        evaluation::evaluate_position(game, stat_eval, &stat_actor_opt, self.id()),
+                        self.evaluator
+                            .evaluate(game, stat_eval, &stat_actor_opt, self.id()),
                         current_depth,
                         max_depth,
                     )
@@ -581,7 +612,7 @@ impl EngineAlphaBeta {
         } else {
             evaluation::handle_end_game_scenario(game, current_depth, max_depth)
         };
-        game.play_back();
+        self.play_back(game, stat_eval);
         score
     }
 
@@ -610,8 +641,7 @@ impl EngineAlphaBeta {
                 long_notation::LongAlgebricNotationMove::build_from_b_move(*m_status.get_move());
             let updated_variant = format!("{} {}", variant, long_algebraic_move.cast());
             //println!("capture {}", updated_variant);
-            game.play_moves(&[long_algebraic_move], &self.zobrist_table, None, false)
-                .unwrap();
+            self.play_move(game, *m_status.get_move(), stat_eval);
             game.update_endgame_status();
             let score_opt = self.evalutate_capture(
                 &updated_variant,
@@ -628,7 +658,8 @@ impl EngineAlphaBeta {
                 *sc
             } else {
                 score::Score::new(
-                    evaluation::evaluate_position(game, stat_eval, &stat_actor_opt, self.id()),
+                    self.evaluator
+                        .evaluate(game, stat_eval, &stat_actor_opt, self.id()),
                     current_depth,
                     max_depth,
                 )
@@ -644,7 +675,7 @@ impl EngineAlphaBeta {
                 }
                 None => best_score_opt = Some(score),
             }
-            game.play_back();
+            self.play_back(game, stat_eval);
         }
         best_score_opt
     }
@@ -672,6 +703,7 @@ impl logic::Engine for EngineAlphaBeta {
         self_actor: Addr<dispatcher::EngineDispatcher>,
         stat_actor_opt: Option<stat_entity::StatActor>,
         game: game_state::GameState,
+        _time_manager_opt: Option<time_allocation::TimeManager>,
         is_stop: &Arc<AtomicBool>,
     ) {
         // First generate moves
@@ -682,7 +714,8 @@ impl logic::Engine for EngineAlphaBeta {
             self_actor.do_send(dispatcher::handler_engine::EngineStopThinking::new(
                 stat_actor_opt,
             ));
-            let reply = dispatcher::handler_engine::EngineEndOfAnalysis(best_move);
+            let reply =
+                dispatcher::handler_engine::EngineEndOfAnalysis(best_move, game.last_hash());
             if let Some(debug_actor) = &self.debug_actor_opt {
                 debug_actor.do_send(debug::AddMessage(format!(
                     "Engine of id {:?} reply is: '{:?}'",
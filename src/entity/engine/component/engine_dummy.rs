@@ -7,6 +7,7 @@ use rand::SeedableRng;
 use rand_chacha::ChaCha12Rng;
 
 use super::engine_logic as logic;
+use super::time_allocation;
 use crate::entity::engine::actor::engine_dispatcher as dispatcher;
 use crate::entity::game::component::game_state;
 use crate::entity::stat::actor::stat_entity;
@@ -46,6 +47,7 @@ impl logic::Engine for EngineDummy {
         self_actor: Addr<dispatcher::EngineDispatcher>,
         _stat_actor_opt: Option<stat_entity::StatActor>,
         game: game_state::GameState,
+        _time_manager_opt: Option<time_allocation::TimeManager>,
         _is_stop: &Arc<AtomicBool>,
     ) {
         let moves = game.gen_moves();
@@ -53,7 +55,8 @@ impl logic::Engine for EngineDummy {
         let best_move_opt = moves.choose(&mut rng).cloned();
         if let Some(best_move) = best_move_opt {
             self_actor.do_send(dispatcher::handler_engine::EngineStopThinking::new(None));
-            let reply = dispatcher::handler_engine::EngineEndOfAnalysis(best_move);
+            let reply =
+                dispatcher::handler_engine::EngineEndOfAnalysis(best_move, game.last_hash());
             if let Some(debug_actor) = &self.debug_actor_opt {
                 debug_actor.do_send(debug::AddMessage(format!(
                     "EngineDummy of id {:?} reply is: '{:?}'",
@@ -0,0 +1,488 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use actix::Addr;
+
+use super::engine_logic as logic;
+use super::evaluation::score;
+use super::time_allocation;
+use crate::entity::engine::actor::engine_dispatcher as dispatcher;
+use crate::entity::game::component::{bitboard, game_state, square};
+use crate::entity::stat::actor::stat_entity;
+use crate::monitoring::debug;
+use crate::ui::notation::fen::{self, EncodeUserInput};
+use crate::ui::notation::long_notation;
+
+// the engine must answer 'uci' and 'isready' within this delay
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+// delay to answer 'stop' with a best move
+const STOP_TIMEOUT: Duration = Duration::from_secs(2);
+const POLLING: Duration = Duration::from_millis(10);
+// 1 centipawn in our score unit (1000 = one pawn)
+const CENTIPAWN: i32 = 10;
+// search depth when there is no clock
+const DEFAULT_DEPTH: u8 = 8;
+
+// Score of an 'info' line, from the point of view of the side to move
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UciScore {
+    Cp(i32),
+    Mate(i32), // number of moves to mate, negative when the engine is mated
+}
+impl UciScore {
+    pub fn value(&self) -> i32 {
+        match self {
+            UciScore::Cp(cp) => cp.saturating_mul(CENTIPAWN),
+            UciScore::Mate(n) if *n > 0 => score::SCORE_MAT_WHITE - n,
+            UciScore::Mate(n) => score::SCORE_MAT_BLACK + 1 - n,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UciInfo {
+    pub depth_opt: Option<u32>,
+    pub score_opt: Option<UciScore>,
+    pub pv: Vec<String>,
+}
+
+// Parse the fields of an 'info' line used to follow the search. None for another line.
+pub fn parse_info(line: &str) -> Option<UciInfo> {
+    let mut tokens = line.split_whitespace();
+    if tokens.next() != Some("info") {
+        return None;
+    }
+    let tokens: Vec<&str> = tokens.collect();
+    let mut info = UciInfo::default();
+    let mut idx = 0;
+    while idx < tokens.len() {
+        match tokens[idx] {
+            "depth" => {
+                info.depth_opt = tokens.get(idx + 1).and_then(|value| value.parse().ok());
+                idx += 1;
+            }
+            "score" => {
+                let value_opt = tokens.get(idx + 2).and_then(|value| value.parse().ok());
+                info.score_opt = match (tokens.get(idx + 1), value_opt) {
+                    (Some(&"cp"), Some(value)) => Some(UciScore::Cp(value)),
+                    (Some(&"mate"), Some(value)) => Some(UciScore::Mate(value)),
+                    _ => None,
+                };
+                idx += 2;
+            }
+            // pv is always the last field
+            "pv" => {
+                info.pv = tokens[idx + 1..].iter().map(|m| m.to_string()).collect();
+                break;
+            }
+            // free text until the end of the line
+            "string" => break,
+            _ => {}
+        }
+        idx += 1;
+    }
+    Some(info)
+}
+
+// Move of a 'bestmove' line. Some engines add text after the move.
+pub fn parse_bestmove(line: &str) -> Option<String> {
+    let mut tokens = line.split_whitespace();
+    if tokens.next() != Some("bestmove") {
+        return None;
+    }
+    tokens
+        .next()
+        .map(|best_move| best_move.trim_end_matches(',').to_string())
+}
+
+// The legal move of the game written in long algebraic notation
+fn find_move(game: &game_state::GameState, move_str: &str) -> Option<bitboard::BitBoardMove> {
+    game.gen_moves().into_iter().find(|b_move| {
+        long_notation::LongAlgebricNotationMove::build_from_b_move(*b_move).cast() == move_str
+    })
+}
+
+// Child process speaking UCI. The lines of its stdout are read by a thread.
+#[derive(Debug)]
+struct UciProcess {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+impl UciProcess {
+    fn spawn(path: &str, args: &[String]) -> Result<Self, String> {
+        let mut child = Command::new(path)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| format!("Cannot start '{}': {}", path, err))?;
+        let stdin = child.stdin.take().ok_or("No stdin for the engine")?;
+        let stdout = child.stdout.take().ok_or("No stdout for the engine")?;
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            child,
+            stdin,
+            lines,
+        })
+    }
+    fn send(&mut self, command: &str) -> Result<(), String> {
+        tracing::debug!("external engine <- {}", command);
+        writeln!(self.stdin, "{}", command)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|err| format!("Cannot write to the engine: {}", err))
+    }
+    fn recv(&self, timeout: Duration) -> Result<Option<String>, String> {
+        match self.lines.recv_timeout(timeout) {
+            Ok(line) => {
+                tracing::debug!("external engine -> {}", line);
+                Ok(Some(line))
+            }
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err("The engine has terminated".to_string()),
+        }
+    }
+    // lines received until the one starting with token (excluded)
+    fn wait_for(&self, token: &str, timeout: Duration) -> Result<Vec<String>, String> {
+        let deadline = Instant::now() + timeout;
+        let mut lines = vec![];
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            match self.recv(remaining)? {
+                Some(line) if line.split_whitespace().next() == Some(token) => return Ok(lines),
+                Some(line) => lines.push(line),
+                None => break,
+            }
+        }
+        Err(format!("No '{}' received from the engine", token))
+    }
+    // lines left by a previous search
+    fn discard_pending(&self) {
+        while self.lines.try_recv().is_ok() {}
+    }
+}
+impl Drop for UciProcess {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(POLLING);
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// Any UCI executable driven through its stdin and stdout
+#[derive(Debug)]
+pub struct ExternalUciEngine {
+    id_number: String,
+    debug_actor_opt: Option<debug::DebugActor>,
+    name: String,
+    author: String,
+    depth: u8,
+    process: Mutex<UciProcess>,
+}
+impl ExternalUciEngine {
+    // start the process and set its options (name, value)
+    pub fn start(
+        path: &str,
+        args: &[String],
+        options: &[(String, String)],
+        debug_actor_opt: Option<debug::DebugActor>,
+    ) -> Result<Self, String> {
+        let mut process = UciProcess::spawn(path, args)?;
+        process.send("uci")?;
+        let lines = process.wait_for("uciok", HANDSHAKE_TIMEOUT)?;
+        let id = |key: &str| {
+            lines
+                .iter()
+                .find_map(|line| line.strip_prefix(&format!("id {} ", key)))
+                .map(|value| value.trim().to_string())
+        };
+        let name = id("name").unwrap_or(path.to_string());
+        let author = id("author").unwrap_or_default();
+        for (option, value) in options {
+            process.send(&format!("setoption name {} value {}", option, value))?;
+        }
+        process.send("isready")?;
+        process.wait_for("readyok", HANDSHAKE_TIMEOUT)?;
+        process.send("ucinewgame")?;
+        Ok(Self {
+            id_number: "".to_string(),
+            debug_actor_opt,
+            name,
+            author,
+            depth: DEFAULT_DEPTH,
+            process: Mutex::new(process),
+        })
+    }
+    pub fn set_id_number(&mut self, id_number: &str) {
+        self.id_number = id_number.to_string();
+    }
+    pub fn set_depth(&mut self, depth: u8) {
+        self.depth = depth;
+    }
+    fn go_command(
+        &self,
+        player_turn: square::Color,
+        time_manager_opt: Option<&time_allocation::TimeManager>,
+    ) -> String {
+        match time_manager_opt {
+            Some(time_manager) => match time_manager.time_info() {
+                Some(time_info) => go_command_with_clocks(player_turn, time_info),
+                None => format!("go movetime {}", time_manager.limits().hard.as_millis()),
+            },
+            None => format!("go depth {}", self.depth),
+        }
+    }
+    fn search(
+        &self,
+        self_actor: &Addr<dispatcher::EngineDispatcher>,
+        game: &game_state::GameState,
+        time_manager_opt: Option<&time_allocation::TimeManager>,
+        is_stop: &Arc<AtomicBool>,
+    ) -> Result<bitboard::BitBoardMove, String> {
+        let mut process = self
+            .process
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        process.discard_pending();
+        process.send(&position_command(game)?)?;
+        let player_turn = game.bit_position().bit_position_status().player_turn();
+        process.send(&self.go_command(player_turn, time_manager_opt))?;
+        let mut score_opt = None;
+        let mut stop_sent_opt: Option<Instant> = None;
+        loop {
+            let line = match process.recv(POLLING)? {
+                Some(line) => line,
+                None => {
+                    match stop_sent_opt {
+                        None if is_stop.load(Ordering::Relaxed) => {
+                            process.send("stop")?;
+                            stop_sent_opt = Some(Instant::now());
+                        }
+                        Some(stop_sent) if stop_sent.elapsed() > STOP_TIMEOUT => {
+                            return Err("No best move after 'stop'".to_string());
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+            };
+            if let Some(move_str) = parse_bestmove(&line) {
+                let best_move = find_move(game, &move_str)
+                    .ok_or(format!("Illegal best move '{}' from the engine", move_str))?;
+                if let Some(score) = score_opt {
+                    let bit_position_status = game.bit_position().bit_position_status();
+                    self_actor.do_send(dispatcher::handler_engine::EngineSearchScore::new(
+                        bit_position_status.player_turn(),
+                        score,
+                        bit_position_status.n_moves() as u32,
+                    ));
+                }
+                return Ok(best_move);
+            }
+            if let Some(info) = parse_info(&line) {
+                if let Some(uci_score) = info.score_opt {
+                    score_opt = Some(uci_score.value());
+                }
+                // best move so far: played if the search is stopped by the clock
+                if let Some(b_move) = info.pv.first().and_then(|m| find_move(game, m)) {
+                    self_actor.do_send(dispatcher::handler_engine::EngineSendBestMove(b_move));
+                }
+            }
+        }
+    }
+}
+
+impl logic::Engine for ExternalUciEngine {
+    fn id(&self) -> logic::EngineId {
+        let name = format!("{} {}", self.name, self.id_number)
+            .trim()
+            .to_string();
+        logic::EngineId::new(name, self.author.clone())
+    }
+    fn find_best_move(
+        &self,
+        self_actor: Addr<dispatcher::EngineDispatcher>,
+        stat_actor_opt: Option<stat_entity::StatActor>,
+        game: game_state::GameState,
+        time_manager_opt: Option<time_allocation::TimeManager>,
+        is_stop: &Arc<AtomicBool>,
+    ) {
+        let best_move_opt =
+            match self.search(&self_actor, &game, time_manager_opt.as_ref(), is_stop) {
+                Ok(best_move) => Some(best_move),
+                // crash, timeout or illegal move: the game goes on instead of waiting for the flag
+                Err(err) => {
+                    tracing::error!(
+                        "External engine {}: {}. Playing a fallback move",
+                        self.id().name(),
+                        err
+                    );
+                    logic::fallback_move(&game)
+                }
+            };
+        self_actor.do_send(dispatcher::handler_engine::EngineStopThinking::new(
+            stat_actor_opt,
+        ));
+        if let Some(best_move) = best_move_opt {
+            let reply =
+                dispatcher::handler_engine::EngineEndOfAnalysis(best_move, game.last_hash());
+            if let Some(debug_actor) = &self.debug_actor_opt {
+                debug_actor.do_send(debug::AddMessage(format!(
+                    "ExternalUciEngine of id {:?} reply is: '{:?}'",
+                    self.id(),
+                    reply
+                )));
+            }
+            self_actor.do_send(reply);
+        }
+    }
+}
+
+// the moves since the initial position let the engine detect repetitions
+fn position_command(game: &game_state::GameState) -> Result<String, String> {
+    let fen =
+        fen::Fen::encode(&game.initial_position().to()).map_err(|err| format!("{:?}", err))?;
+    let moves: Vec<String> = game
+        .moves()
+        .into_iter()
+        .map(|b_move| long_notation::LongAlgebricNotationMove::build_from_b_move(b_move).cast())
+        .collect();
+    if moves.is_empty() {
+        Ok(format!("position fen {}", fen))
+    } else {
+        Ok(format!("position fen {} moves {}", fen, moves.join(" ")))
+    }
+}
+
+// without the clock of the opponent, the engine is given its own clock for both players
+fn go_command_with_clocks(
+    player_turn: square::Color,
+    time_info: &time_allocation::TimeInfo,
+) -> String {
+    let own = (time_info.remaining_ms, time_info.inc_ms);
+    let opponent = match time_info.opponent_remaining_ms_opt {
+        Some(remaining_ms) => (remaining_ms, time_info.opponent_inc_ms),
+        None => own,
+    };
+    let ((wtime, winc), (btime, binc)) = match player_turn {
+        square::Color::White => (own, opponent),
+        square::Color::Black => (opponent, own),
+    };
+    let mut go = format!(
+        "go wtime {} btime {} winc {} binc {}",
+        wtime, btime, winc, binc
+    );
+    if let Some(moves_to_go) = time_info.moves_to_go {
+        go.push_str(&format!(" movestogo {}", moves_to_go));
+    }
+    go
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::game::component::bitboard::zobrist;
+
+    #[test]
+    fn test_parse_info() {
+        let info =
+            parse_info("info depth 12 seldepth 18 multipv 1 score cp -35 nodes 1000 pv e7e5 g1f3")
+                .unwrap();
+        assert_eq!(info.depth_opt, Some(12));
+        assert_eq!(info.score_opt, Some(UciScore::Cp(-35)));
+        assert_eq!(info.pv, vec!["e7e5".to_string(), "g1f3".to_string()]);
+        assert_eq!(UciScore::Cp(-35).value(), -350);
+        let info = parse_info("info depth 5 score mate -2 pv e8d8").unwrap();
+        assert_eq!(info.score_opt, Some(UciScore::Mate(-2)));
+        assert!(UciScore::Mate(-2).value() < UciScore::Mate(-3).value());
+        assert!(UciScore::Mate(2).value() > UciScore::Mate(3).value());
+        let info = parse_info("info string score cp 10 pv e2e4").unwrap();
+        assert_eq!(info, UciInfo::default());
+        assert_eq!(parse_info("bestmove e2e4"), None);
+    }
+
+    #[test]
+    fn test_parse_bestmove() {
+        assert_eq!(
+            parse_bestmove("bestmove e7e8q ponder d1d8"),
+            Some("e7e8q".to_string())
+        );
+        assert_eq!(
+            parse_bestmove("bestmove d7d5, timestamp: 2026-10-18"),
+            Some("d7d5".to_string())
+        );
+        assert_eq!(parse_bestmove("info depth 1"), None);
+    }
+
+    #[test]
+    fn test_position_command() {
+        let zobrist_table = zobrist::Zobrist::new();
+        let position = fen::Fen::decode(fen::FEN_START_POSITION).unwrap();
+        let mut game = game_state::GameState::new(position, &zobrist_table);
+        assert_eq!(
+            position_command(&game).unwrap(),
+            format!("position fen {}", fen::FEN_START_POSITION)
+        );
+        let moves: Vec<long_notation::LongAlgebricNotationMove> = ["e2e4", "e7e5", "g1f3"]
+            .iter()
+            .map(|m| long_notation::LongAlgebricNotationMove::build_from_str(m).unwrap())
+            .collect();
+        game.play_moves(&moves, &zobrist_table, None, true).unwrap();
+        assert_eq!(
+            position_command(&game).unwrap(),
+            format!(
+                "position fen {} moves e2e4 e7e5 g1f3",
+                fen::FEN_START_POSITION
+            )
+        );
+    }
+
+    #[test]
+    fn test_go_command_with_clocks() {
+        let mut time_info = time_allocation::TimeInfo {
+            remaining_ms: 10_000,
+            inc_ms: 100,
+            ..Default::default()
+        };
+        assert_eq!(
+            go_command_with_clocks(square::Color::Black, &time_info),
+            "go wtime 10000 btime 10000 winc 100 binc 100"
+        );
+        time_info.opponent_remaining_ms_opt = Some(3_000);
+        time_info.opponent_inc_ms = 50;
+        time_info.moves_to_go = Some(12);
+        assert_eq!(
+            go_command_with_clocks(square::Color::Black, &time_info),
+            "go wtime 3000 btime 10000 winc 50 binc 100 movestogo 12"
+        );
+        assert_eq!(
+            go_command_with_clocks(square::Color::White, &time_info),
+            "go wtime 10000 btime 3000 winc 100 binc 50 movestogo 12"
+        );
+    }
+
+    #[test]
+    fn test_start_failure() {
+        let result = ExternalUciEngine::start("/nonexistent/engine", &[], &[], None);
+        assert!(result.is_err());
+    }
+}
@@ -5,12 +5,14 @@ use actix::Addr;
 
 use super::config::config;
 use super::engine_logic::{self as logic, Engine};
-use super::evaluation::{score, stat_eval};
-use super::{engine_alphabeta, engine_mat, search_state};
+use super::evaluation::{evaluator, score, stat_eval};
+use super::{engine_alphabeta, engine_pns, search_state, time_allocation};
 use crate::entity::engine::actor::engine_dispatcher as dispatcher;
 use crate::entity::engine::component::evaluation;
 use crate::entity::game::component::bitboard::zobrist;
 use crate::entity::game::component::game_state;
+use crate::entity::game::component::square;
+use crate::ui::notation::long_notation;
 use crate::entity::stat::actor::stat_entity;
 use crate::span_debug;
 use crate::{entity::game::component::bitboard, monitoring::debug};
@@ -25,7 +27,9 @@ pub struct EngineIddfs {
     debug_actor_opt: Option<debug::DebugActor>,
     conf:  config::IDDFSConfig,
     engine_alphabeta: engine_alphabeta::EngineAlphaBeta,
-    engine_mat_solver: engine_mat::EngineMat,
+    engine_mat_solver: engine_pns::EnginePns,
+    // stop deepening once this number of positions has been evaluated
+    max_nodes_opt: Option<u64>,
 }
 impl EngineIddfs {
     pub fn new(
@@ -46,17 +50,27 @@ impl EngineIddfs {
                 conf.alphabeta_feature_conf.clone(),
                 false,
             ),
-            engine_mat_solver: engine_mat::EngineMat::new(
-                // fIXME: max_depth here should be dynamic
+            engine_mat_solver: engine_pns::EnginePns::new(
                 None,
                 zobrist_table,
-                &config::MatConfig::new(8),
+                &config::PnsConfig::new(
+                    conf.max_depth
+                        .div_ceil(2)
+                        .min(config::MAX_PNS_MATE_MOVES),
+                ),
             ),
+            max_nodes_opt: None,
         }
     }
     pub fn set_id_number(&mut self, id_number: &str) {
         self.id_number = id_number.to_string();
     }
+    pub fn set_max_nodes(&mut self, max_nodes_opt: Option<u64>) {
+        self.max_nodes_opt = max_nodes_opt;
+    }
+    pub fn set_evaluator(&mut self, evaluator: Arc<dyn evaluator::Evaluator>) {
+        self.engine_alphabeta.set_evaluator(evaluator);
+    }
 
     fn aspiration_window(
         &self,
@@ -106,8 +120,48 @@ impl EngineIddfs {
         game: &game_state::GameState,
         self_actor: Addr<dispatcher::EngineDispatcher>,
         stat_actor_opt: Option<stat_entity::StatActor>,
+        time_manager_opt: Option<time_allocation::TimeManager>,
         is_stop: &Arc<AtomicBool>,
     ) -> bitboard::BitBoardMove {
+        match self.iddfs_search(
+            game,
+            self_actor.clone(),
+            stat_actor_opt,
+            time_manager_opt,
+            is_stop,
+        ) {
+            Some(score) => {
+                tracing::info!("Final selected move: {:?}", score.bitboard_move());
+                // score from the point of view of the engine for the adjudication
+                let color = game.bit_position().bit_position_status().player_turn();
+                let score_value = match color {
+                    square::Color::White => score.score().value(),
+                    square::Color::Black => score.score().value().saturating_neg(),
+                };
+                let move_number = game.bit_position().bit_position_status().n_moves() as u32;
+                self_actor.do_send(dispatcher::handler_engine::EngineSearchScore::new(
+                    color,
+                    score_value,
+                    move_number,
+                ));
+                *score.bitboard_move()
+            }
+            None => {
+                tracing::warn!("No iteration completed in IDDFS: playing a fallback move");
+                logic::fallback_move(game).expect("No legal move in the position")
+            }
+        }
+    }
+
+    // return the best move with its score (white perspective)
+    pub fn iddfs_search(
+        &self,
+        game: &game_state::GameState,
+        self_actor: Addr<dispatcher::EngineDispatcher>,
+        stat_actor_opt: Option<stat_entity::StatActor>,
+        mut time_manager_opt: Option<time_allocation::TimeManager>,
+        is_stop: &Arc<AtomicBool>,
+    ) -> Option<score::BitboardMoveScore> {
         let span = span_debug();
         let _enter = span.enter();
 
@@ -118,32 +172,55 @@ impl EngineIddfs {
         let mut state = search_state::SearchState::new();
 
         let mut game_clone = game.clone();
+        // almost no time left: the move of a shallow search is played at once
+        let is_emergency = time_manager_opt
+            .as_ref()
+            .is_some_and(|time_manager| time_manager.is_emergency());
+        let max_depth_search = if is_emergency {
+            tracing::info!("Emergency mode: shallow search only");
+            1
+        } else {
+            self.conf.max_depth
+        };
 
         // Vérifiez si un coup rapide est possible avec le mat solver
-        if let Some(mat_move) = if self.conf.iddfs_feature_conf.f_mat_solver {
+        if let Some(mat_move) = if self.conf.iddfs_feature_conf.f_mat_solver && !is_emergency {
             tracing::debug!("Attempting mat solver");
-            self.engine_mat_solver.mat_solver_init(
+            // mate within the search depth (in half moves), before the target time of the move
+            self.engine_mat_solver.solve(
                 game,
-                self_actor.clone(),
-                stat_actor_opt.clone(),
-                &config::MatConfig::new(self.conf.max_depth),
-                &mut stat_eval,
+                self.conf.max_depth.div_ceil(2),
                 is_stop,
+                time_manager_opt
+                    .as_ref()
+                    .map(|time_manager| time_manager.soft_deadline()),
             )
         } else {
             None
         } {
-            tracing::info!("Mat solver found a move: {:?}", mat_move.bitboard_move());
-            return *mat_move.bitboard_move();
+            tracing::info!("Mat solver found a mate in {}: {}", mat_move.mat_in(), mat_move.variant());
+            let score_mat = if game.bit_position().bit_position_status().player_turn_white() {
+                score::SCORE_MAT_WHITE
+            } else {
+                score::SCORE_MAT_BLACK
+            };
+            return Some(score::BitboardMoveScore::new(
+                *mat_move.bitboard_move(),
+                score::Score::new(score_mat, 0, 0),
+                mat_move.variant(),
+            ));
         }
 
+        self.engine_alphabeta
+            .evaluator()
+            .init(&game_clone, &mut stat_eval);
         let mut b_move_score_opt: Option<score::BitboardMoveScore> = None;
         let mut alpha_opt: Option<i32> = None;
         let mut beta_opt: Option<i32> = None;
 
         tracing::info!("Starting iterative deepening search");
         // Boucle principale
-        for max_depth in 1..=self.conf.max_depth {
+        for max_depth in 1..=max_depth_search {
             if is_stop.load(Ordering::Relaxed) {
                 tracing::debug!(
                     "Iddf detected interrupt before evaluation at max_depth {}",
@@ -214,6 +291,11 @@ impl EngineIddfs {
                 );
                 break;
             }
+            // the iteration may be incomplete: the move of the previous one is kept
+            if stat_eval.is_node_limit_reached() {
+                tracing::debug!("Node limit reached: {}", stat_eval.n_nodes());
+                break;
+            }
             //println!("best variant found: {}", b_move_score.get_variant());
             send_best_move(self_actor.clone(), *b_move_score.bitboard_move());
             tracing::info!(
@@ -232,7 +314,36 @@ impl EngineIddfs {
                 );
             }
 
+            // the time manager decides if a new iteration is worth starting
+            let is_time_over = time_manager_opt.as_mut().is_some_and(|time_manager| {
+                let score = b_move_score.score().value();
+                let score = if game.bit_position().bit_position_status().player_turn_white() {
+                    score
+                } else {
+                    score.saturating_neg()
+                };
+                time_manager.end_of_iteration(
+                    *b_move_score.bitboard_move(),
+                    score,
+                    std::time::Instant::now(),
+                )
+            });
+            if self.conf.alphabeta_feature_conf.f_transposition_table {
+                let variant = principal_variation(
+                    &game_clone,
+                    &transposition_table,
+                    *b_move_score.bitboard_move(),
+                    self.engine_alphabeta.zobrist_table(),
+                );
+                b_move_score.set_variant(&variant);
+            }
             b_move_score_opt = Some(b_move_score);
+            // the node limit stops alphabeta itself, once a move is known
+            stat_eval.set_max_nodes(self.max_nodes_opt);
+            if is_time_over {
+                tracing::debug!("Time manager stops the search after depth {}", max_depth);
+                break;
+            }
         }
         if is_stop.load(Ordering::Relaxed) {
             tracing::debug!("IDDFS interrupted.");
@@ -245,16 +356,7 @@ impl EngineIddfs {
         } else {
             tracing::debug!("IDDFS completed all iterations");
         }
-        b_move_score_opt.map_or_else(
-            || {
-                tracing::error!("No valid move found in IDDFS!");
-                panic!("No valid move found before timeout!")
-            },
-            |score| {
-                tracing::info!("Final selected move: {:?}", score.bitboard_move());
-                *score.bitboard_move()
-            },
-        )
+        b_move_score_opt
     }
 }
 
@@ -281,6 +383,7 @@ impl logic::Engine for EngineIddfs {
         self_actor: Addr<dispatcher::EngineDispatcher>,
         stat_actor_opt: Option<stat_entity::StatActor>,
         game: game_state::GameState,
+        time_manager_opt: Option<time_allocation::TimeManager>,
         is_stop: &Arc<AtomicBool>,
     ) {
         // First generate moves
@@ -291,13 +394,20 @@ impl logic::Engine for EngineIddfs {
             panic!("To be implemented. When EndGame detected in game_manager, stop the engines");
         }
         //tracing::info!(max_time = max_time.as_secs());
-        let best_move = self.iddfs_init(&game, self_actor.clone(), stat_actor_opt.clone(), is_stop);
+        let best_move = self.iddfs_init(
+            &game,
+            self_actor.clone(),
+            stat_actor_opt.clone(),
+            time_manager_opt,
+            is_stop,
+        );
         tracing::debug!("Send EngineStopThinking");
         self_actor.do_send(dispatcher::handler_engine::EngineStopThinking::new(
             stat_actor_opt,
         ));
         tracing::debug!("Send EngineEndOfAnalysis");
-        let reply = dispatcher::handler_engine::EngineEndOfAnalysis(best_move);
+        let reply =
+            dispatcher::handler_engine::EngineEndOfAnalysis(best_move, game.last_hash());
         if let Some(debug_actor) = &self.debug_actor_opt {
             debug_actor.do_send(debug::AddMessage(format!(
                 "Engine of id {:?} reply is: '{:?}'",
@@ -307,6 +417,53 @@ impl logic::Engine for EngineIddfs {
         }
         self_actor.do_send(reply);
     }
+    fn analyse(
+        &self,
+        self_actor: Addr<dispatcher::EngineDispatcher>,
+        game: &game_state::GameState,
+        time_manager_opt: Option<time_allocation::TimeManager>,
+        is_stop: &Arc<AtomicBool>,
+    ) -> Option<score::BitboardMoveScore> {
+        self.iddfs_search(game, self_actor, None, time_manager_opt, is_stop)
+    }
+}
+
+const PV_MAX_LENGTH: usize = 8;
+
+// Variant (long notation) starting with the best move, followed through the transposition table
+fn principal_variation(
+    game: &game_state::GameState,
+    transposition_table: &score::TranspositionScore,
+    best_move: bitboard::BitBoardMove,
+    zobrist_table: &zobrist::Zobrist,
+) -> String {
+    let mut game_clone = game.clone();
+    let mut variant = vec![];
+    let mut b_move = best_move;
+    loop {
+        let long_move = long_notation::LongAlgebricNotationMove::build_from_b_move(b_move);
+        if game_clone
+            .play_moves(&[long_move], zobrist_table, None, false)
+            .is_err()
+        {
+            break;
+        }
+        variant.push(long_move.cast());
+        game_clone.update_endgame_status();
+        if variant.len() >= PV_MAX_LENGTH || game_clone.end_game() != game_state::EndGame::None {
+            break;
+        }
+        // the entry must be a legal move of the position
+        let next_move_opt = transposition_table
+            .get_move_info(&game_clone.last_hash(), 0)
+            .map(|entry| *entry.move_score().bitboard_move())
+            .filter(|m| game_clone.gen_moves().contains(m));
+        match next_move_opt {
+            Some(next_move) => b_move = next_move,
+            None => break,
+        }
+    }
+    variant.join(" ")
 }
 
 fn send_best_move(
@@ -316,3 +473,81 @@ fn send_best_move(
     let msg = dispatcher::handler_engine::EngineSendBestMove(best_move);
     self_actor.do_send(msg);
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    use actix::Actor;
+
+    use super::*;
+    use crate::entity::game::component::square::TypePiece;
+    use crate::ui::notation::fen::{self, EncodeUserInput};
+
+    #[actix::test]
+    async fn test_fallback_move_when_stopped() {
+        let zobrist_table = zobrist::Zobrist::new();
+        let conf = config::IDDFSConfig::new(
+            4,
+            config::IddfsFeatureConf::default(),
+            config::AlphabetaFeatureConf::default(),
+        );
+        let engine = EngineIddfs::new(None, zobrist_table.clone(), &conf);
+        let self_actor =
+            dispatcher::EngineDispatcher::new(Arc::new(engine.clone()), None, None).start();
+        // the knight can take the queen
+        let fen = "4k3/8/8/3q4/8/4N3/8/4K3 w - - 0 1";
+        let position = fen::Fen::decode(fen).expect("Failed to decode FEN");
+        let game = game_state::GameState::new(position, &zobrist_table);
+        // stopped before the first iteration
+        let is_stop = Arc::new(AtomicBool::new(true));
+        let b_move = engine.iddfs_init(&game, self_actor, None, None, &is_stop);
+        assert_eq!(b_move.capture(), Some(TypePiece::Queen));
+    }
+
+    #[actix::test]
+    async fn test_mat_solver_quiet_move() {
+        let zobrist_table = zobrist::Zobrist::new();
+        let mut iddfs_features = config::IddfsFeatureConf::default();
+        iddfs_features.f_mat_solver = true;
+        let conf =
+            config::IDDFSConfig::new(3, iddfs_features, config::AlphabetaFeatureConf::default());
+        let engine = EngineIddfs::new(None, zobrist_table.clone(), &conf);
+        let self_actor =
+            dispatcher::EngineDispatcher::new(Arc::new(engine.clone()), None, None).start();
+        // mate in 2 starting with the quiet Kf7
+        let fen = "7k/8/5K2/8/8/8/8/6R1 w - - 0 1";
+        let position = fen::Fen::decode(fen).expect("Failed to decode FEN");
+        let game = game_state::GameState::new(position, &zobrist_table);
+        let is_stop = Arc::new(AtomicBool::new(false));
+        let b_move = engine.iddfs_init(&game, self_actor, None, None, &is_stop);
+        assert_eq!(b_move.type_piece(), TypePiece::King);
+    }
+
+    #[actix::test]
+    async fn test_node_limit() {
+        let zobrist_table = zobrist::Zobrist::new();
+        // the depth is never reached: the node limit stops the search
+        let conf = config::IDDFSConfig::new(
+            30,
+            config::IddfsFeatureConf::default(),
+            config::AlphabetaFeatureConf::default(),
+        );
+        let mut engine = EngineIddfs::new(None, zobrist_table.clone(), &conf);
+        let position = fen::Fen::decode(fen::FEN_START_POSITION).expect("Failed to decode FEN");
+        let game = game_state::GameState::new(position, &zobrist_table);
+        let is_stop = Arc::new(AtomicBool::new(false));
+        let mut b_moves = vec![];
+        for max_nodes in [1, 5_000, 5_000] {
+            engine.set_max_nodes(Some(max_nodes));
+            let self_actor =
+                dispatcher::EngineDispatcher::new(Arc::new(engine.clone()), None, None).start();
+            let b_move_score = engine.iddfs_search(&game, self_actor, None, None, &is_stop);
+            // the first iteration is always completed
+            b_moves.push(*b_move_score.unwrap().bitboard_move());
+        }
+        // same budget, same move
+        assert_eq!(b_moves[1], b_moves[2]);
+    }
+}
@@ -4,7 +4,10 @@ use std::sync::Arc;
 use actix::prelude::*;
 
 use crate::entity::engine::actor::engine_dispatcher as dispatcher;
+use crate::entity::engine::component::evaluation::score;
+use crate::entity::engine::component::time_allocation;
 use crate::entity::game::component::bitboard::piece_move::GenMoves;
+use crate::entity::game::component::square::TypePiece;
 use crate::entity::game::component::{bitboard, game_state};
 use crate::entity::stat::actor::stat_entity;
 
@@ -32,8 +35,21 @@ pub trait Engine {
         self_actor: Addr<dispatcher::EngineDispatcher>,
         stat_actor_opt: Option<stat_entity::StatActor>,
         game: game_state::GameState,
+        time_manager_opt: Option<time_allocation::TimeManager>,
         is_stop: &Arc<AtomicBool>,
     );
+
+    // Search without any effect on the game (hint): best move with its score (white perspective)
+    // and its variant. None if the engine cannot analyse a position.
+    fn analyse(
+        &self,
+        _self_actor: Addr<dispatcher::EngineDispatcher>,
+        _game: &game_state::GameState,
+        _time_manager_opt: Option<time_allocation::TimeManager>,
+        _is_stop: &Arc<AtomicBool>,
+    ) -> Option<score::BitboardMoveScore> {
+        None
+    }
 }
 
 pub fn gen_moves(bit_position: &bitboard::BitPosition) -> Vec<bitboard::BitBoardMove> {
@@ -49,3 +65,18 @@ pub fn gen_moves(bit_position: &bitboard::BitPosition) -> Vec<bitboard::BitBoard
         bit_position_status,
     )
 }
+
+// guaranteed reply when no search result is available: the best capture or the first legal move
+pub fn fallback_move(game: &game_state::GameState) -> Option<bitboard::BitBoardMove> {
+    gen_moves(game.bit_position())
+        .into_iter()
+        .rev()
+        .max_by_key(|b_move| match b_move.capture() {
+            None => 0,
+            Some(TypePiece::Pawn) => 1,
+            Some(TypePiece::Knight) | Some(TypePiece::Bishop) => 3,
+            Some(TypePiece::Rook) => 5,
+            Some(TypePiece::Queen) => 9,
+            Some(TypePiece::King) => 10,
+        })
+}
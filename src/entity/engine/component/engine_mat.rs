@@ -5,6 +5,7 @@ use actix::Addr;
 
 use super::config::config;
 use super::engine_logic::{self as logic, Engine};
+use super::time_allocation;
 use super::evaluation::{score, stat_eval};
 use crate::entity::engine::actor::engine_dispatcher as dispatcher;
 use crate::entity::game::component::bitboard::zobrist;
@@ -166,15 +167,11 @@ impl EngineMat {
         let mut v = vec![];
         let mut game_clone = game.clone();
         for m in moves {
-            let long_algebraic_move =
-                long_notation::LongAlgebricNotationMove::build_from_b_move(*m);
-            game_clone
-                .play_moves(&[long_algebraic_move], &self.zobrist_table, None, false)
-                .unwrap();
+            game_clone.make_move(m, &self.zobrist_table);
             if game_clone.check_status().is_check() {
                 v.push(*m);
             }
-            game_clone.play_back();
+            game_clone.unmake_move();
         }
         stat_eval.inc_n_check(v.len() as u64);
         v
@@ -216,8 +213,7 @@ impl EngineMat {
             .trim()
             .to_string();
         println!("variant mat ? {}", updated_variant);
-        game.play_moves(&[long_algebraic_move], &self.zobrist_table, None, false)
-            .unwrap();
+        game.make_move(&m, &self.zobrist_table);
         game.update_endgame_status();
 
         let result = match &game.end_game() {
@@ -256,7 +252,7 @@ impl EngineMat {
                 None
             }
         };
-        game.play_back();
+        game.unmake_move();
         result
     }
 }
@@ -283,6 +279,7 @@ impl logic::Engine for EngineMat {
         self_actor: Addr<dispatcher::EngineDispatcher>,
         stat_actor_opt: Option<stat_entity::StatActor>,
         game: game_state::GameState,
+        _time_manager_opt: Option<time_allocation::TimeManager>,
         is_stop: &Arc<AtomicBool>,
     ) {
         let span = span_debug();
@@ -303,7 +300,8 @@ impl logic::Engine for EngineMat {
                 stat_actor_opt,
             ));
             if let Some(best_move) = best_move_opt {
-                let reply = dispatcher::handler_engine::EngineEndOfAnalysis(best_move);
+                let reply =
+                    dispatcher::handler_engine::EngineEndOfAnalysis(best_move, game.last_hash());
                 if let Some(debug_actor) = &self.debug_actor_opt {
                     debug_actor.do_send(debug::AddMessage(format!(
                         "Engine of id {:?} reply is: '{:?}'",
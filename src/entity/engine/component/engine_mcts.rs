@@ -1,22 +1,34 @@
 use actix::Addr;
-use rand::Rng;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use std::fmt;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::Instant;
 
 use crate::entity::engine::actor::engine_dispatcher as dispatcher;
-use crate::entity::engine::component::{engine_logic as logic, mcts_tree};
+use crate::entity::engine::component::engine_logic::{self as logic, Engine};
+use crate::entity::engine::component::evaluation::score;
+use crate::entity::engine::component::{mcts_export, mcts_tree, time_allocation};
 use crate::entity::game::component::bitboard::zobrist;
 use crate::entity::game::component::square::Switch;
 use crate::entity::game::component::{game_state, square};
 use crate::entity::stat::actor::stat_entity;
 use crate::span_debug;
-use crate::ui::notation::long_notation;
 use crate::{entity::game::component::bitboard, monitoring::debug};
 
 use super::config::config;
+use super::evaluation::{evaluator, stat_eval};
 
-const MAX_TREE_ITERATION: u64 = 1000;
+// number of tree iterations without time limit nor node budget
+const DEFAULT_TREE_ITERATIONS: u64 = 1000;
+// a position of the previous tree is looked for up to this depth
+const TREE_REUSE_MAX_DEPTH: usize = 2;
+// bonus of the heuristic priors (a capture of a pawn by a pawn is 1)
+const PRIOR_CHECK_BONUS: f64 = 2.0;
+const PRIOR_PROMOTION_BONUS: f64 = 8.0;
 
 fn span_debug() -> tracing::Span {
     span_debug!("engine_mcts")
@@ -27,6 +39,7 @@ struct MctsStat {
     n_simulation: u64,
     n_moves_per_game: u64,
     n_moves_gen: u64,
+    stat_eval: stat_eval::StatEval,
 }
 impl fmt::Display for MctsStat {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -41,12 +54,22 @@ impl MctsStat {
         self.n_moves_gen += n_moves_gen;
         self.n_moves_per_game += n_moves_per_game;
     }
+    pub fn add(&mut self, other: &MctsStat) {
+        self.inc(
+            other.n_simulation,
+            other.n_moves_per_game,
+            other.n_moves_gen,
+        );
+    }
 }
 pub struct EngineMcts {
     id_number: String,
     debug_actor_opt: Option<debug::DebugActor>,
     zobrist_table: zobrist::Zobrist,
     conf: config::MctsConfig,
+    evaluator: Arc<dyn evaluator::Evaluator>,
+    // tree of the last search with its root
+    tree_opt: Mutex<Option<(mcts_tree::Graph, mcts_tree::NodeIdx)>>,
 }
 impl EngineMcts {
     pub fn new(
@@ -59,43 +82,112 @@ impl EngineMcts {
             debug_actor_opt,
             zobrist_table,
             conf: conf.clone(),
+            evaluator: Arc::new(evaluator::ClassicEvaluator::default()),
+            tree_opt: Mutex::new(None),
         }
     }
     pub fn set_id_number(&mut self, id_number: &str) {
         self.id_number = id_number.to_string();
     }
+    pub fn set_evaluator(&mut self, evaluator: Arc<dyn evaluator::Evaluator>) {
+        self.evaluator = evaluator;
+    }
 
     pub fn mcts(
         &self,
         game: &game_state::GameState,
+        time_manager_opt: Option<&time_allocation::TimeManager>,
         is_stop: &Arc<AtomicBool>,
     ) -> bitboard::BitBoardMove {
         let span = span_debug();
         let _enter = span.enter();
 
-        let mut graph = mcts_tree::Graph::new();
-        let moves = game.gen_moves();
-        let root = mcts_tree::Node::build_root(game.clone(), &moves);
-        let root_id = mcts_tree::add_node_to_graph(&mut graph, root.clone());
-        let mut mcts_stat = MctsStat::default();
-        for i in 0..MAX_TREE_ITERATION {
-            if is_stop.load(Ordering::Relaxed) {
-                break;
+        let (graph, root_id) = match self.reused_tree(game) {
+            Some((graph, root_id)) => {
+                tracing::debug!("tree reused with {} visits", graph[root_id].visits());
+                (graph, root_id)
             }
-            if i % 100 == 0 {
-                tracing::debug!("tree iteration number: {}", i);
+            None => {
+                let mut graph = mcts_tree::Graph::new();
+                let moves = game.gen_moves();
+                let root = mcts_tree::Node::build_root(game.clone(), &moves);
+                let root_id = mcts_tree::add_node_to_graph(&mut graph, root);
+                let mut stat_eval = stat_eval::StatEval::default();
+                self.set_untried_moves(&mut graph, root_id, moves, &mut stat_eval);
+                (graph, root_id)
             }
-            self.mcts_run(&mut graph, root_id, &mut mcts_stat);
-        }
-        if let Some(idx) = mcts_tree::Node::argmax(&graph, graph[root_id].children(), self.conf.c) {
-            let best_move_id = graph[root_id].children().get(idx).unwrap();
-            let best_move = if let Some(edge_index) = graph.find_edge(root_id, *best_move_id) {
+        };
+        let deadline_opt = time_manager_opt.map(|time_manager| time_manager.soft_deadline());
+        let node_budget = match (self.conf.node_budget_opt, deadline_opt) {
+            (Some(node_budget), _) => node_budget,
+            (None, Some(_)) => u64::MAX,
+            (None, None) => DEFAULT_TREE_ITERATIONS,
+        };
+        // the tree is shared by the search threads
+        let graph = RwLock::new(graph);
+        let n_iterations = AtomicU64::new(0);
+        let mcts_stat = thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.conf.threads.max(1) as u64)
+                .map(|worker| {
+                    let (graph, n_iterations) = (&graph, &n_iterations);
+                    scope.spawn(move || {
+                        let mut rng = self.rng(worker);
+                        let mut mcts_stat = MctsStat::default();
+                        loop {
+                            let i = n_iterations.fetch_add(1, Ordering::Relaxed);
+                            let (is_expanded, is_proven) = {
+                                let graph = graph.read().unwrap();
+                                (
+                                    !graph[root_id].children().is_empty(),
+                                    graph[root_id].is_proven(),
+                                )
+                            };
+                            // at least one move is expanded, no search once the root is solved
+                            if is_expanded
+                                && (i >= node_budget
+                                    || is_proven
+                                    || is_stop.load(Ordering::Relaxed)
+                                    || deadline_opt
+                                        .is_some_and(|deadline| Instant::now() >= deadline))
+                            {
+                                break;
+                            }
+                            if i.is_multiple_of(100) {
+                                tracing::debug!("tree iteration number: {}", i);
+                            }
+                            self.mcts_run(graph, root_id, &mut mcts_stat, &mut rng);
+                        }
+                        mcts_stat
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .fold(MctsStat::default(), |mut mcts_stat, worker| {
+                    mcts_stat.add(&worker.join().unwrap());
+                    mcts_stat
+                })
+        });
+        let graph = graph.into_inner().unwrap();
+        if let Some(best_move_id) = self.best_child(&graph, root_id) {
+            let best_move = if let Some(edge_index) = graph.find_edge(root_id, best_move_id) {
                 let edge = graph.edge_weight(edge_index).unwrap();
                 edge.0
             } else {
                 panic!("Graph error: edge not found");
             };
             mcts_tree::display_tree(&graph, root_id, 0, 0);
+            if let Some(tree_export) = &self.conf.tree_export_opt {
+                if let Err(err) = mcts_export::export_tree(
+                    &graph,
+                    root_id,
+                    &self.conf,
+                    tree_export,
+                    &self.zobrist_table,
+                ) {
+                    tracing::warn!("{}", err);
+                }
+            }
             let total: u64 = graph[root_id]
                 .children()
                 .iter()
@@ -104,166 +196,393 @@ impl EngineMcts {
             tracing::debug!("total visits in children level 1: {}", total);
             tracing::debug!("total visits root: {}", graph[root_id].visits());
             tracing::debug!("{}", mcts_stat);
+            if self.conf.f_tree_reuse {
+                *self.tree_opt.lock().unwrap() = Some((graph, root_id));
+            }
             best_move
         } else {
             panic!("No move found")
         }
     }
+    // proven wins first (mate in one before the others), then the best move not proven,
+    // the most visited move if all the moves are lost
+    fn best_child(
+        &self,
+        graph: &mcts_tree::Graph,
+        root_id: mcts_tree::NodeIdx,
+    ) -> Option<mcts_tree::NodeIdx> {
+        let children = graph[root_id].children();
+        let win_opt = children
+            .iter()
+            .filter(|n| graph[**n].proof() == mcts_tree::Proof::Win)
+            .min_by_key(|n| graph[**n].game().end_game() == game_state::EndGame::None);
+        if let Some(win) = win_opt {
+            tracing::debug!("proven win found");
+            return Some(*win);
+        }
+        match mcts_tree::Node::argmax(graph, children, &self.conf) {
+            Some(idx) => Some(children[idx]),
+            None => children.iter().max_by_key(|n| graph[**n].visits()).copied(),
+        }
+    }
+    // subtree of the last search whose root is the current position
+    fn reused_tree(
+        &self,
+        game: &game_state::GameState,
+    ) -> Option<(mcts_tree::Graph, mcts_tree::NodeIdx)> {
+        if !self.conf.f_tree_reuse {
+            return None;
+        }
+        let (graph, root_id) = self.tree_opt.lock().unwrap().take()?;
+        let hash = game.last_hash();
+        let mut nodes = vec![root_id];
+        for depth in 0..=TREE_REUSE_MAX_DEPTH {
+            if let Some(node_id) = nodes.iter().find(|n| graph[**n].game().last_hash() == hash) {
+                tracing::debug!("position found in the previous tree at depth {}", depth);
+                return Some(mcts_tree::subtree(&graph, *node_id));
+            }
+            nodes = nodes
+                .iter()
+                .flat_map(|n| graph[*n].children().clone())
+                .collect();
+        }
+        None
+    }
+    // random generator of a search thread
+    fn rng(&self, worker: u64) -> ChaCha8Rng {
+        match self.conf.seed_opt {
+            Some(seed) => ChaCha8Rng::seed_from_u64(seed.wrapping_add(worker)),
+            None => ChaCha8Rng::from_entropy(),
+        }
+    }
+    // one tree iteration: selection, expansion, simulation and back propagation
     fn mcts_run(
         &self,
-        graph: &mut mcts_tree::Graph,
-        node_id: mcts_tree::NodeIdx,
+        graph: &RwLock<mcts_tree::Graph>,
+        root_id: mcts_tree::NodeIdx,
         mcts_stat: &mut MctsStat,
+        rng: &mut ChaCha8Rng,
     ) {
         let span = span_debug();
         let _enter = span.enter();
 
-        if graph[node_id].is_terminal() {
-            let (n_white_wins, n_black_wins) = Self::evaluate_end_game(graph[node_id].game());
-            self.mcts_back_propagation(graph, node_id, n_white_wins, n_black_wins);
-        } else {
+        // selection: select the best ucb1 or puct score while the node cannot be expanded.
+        // The selected nodes get a virtual loss so that the other threads search elsewhere.
+        let mut path = vec![];
+        let mut node_id = root_id;
+        loop {
+            let graph = graph.read().unwrap();
             let node = &graph[node_id];
-            if node.untried_moves().is_empty() {
-                tracing::debug!("selection");
-                if node.children().is_empty() {
-                    // generate moves
-                    let moves = graph[node_id].game().gen_moves();
-                    let invalid_move: Vec<_> = moves
-                        .iter()
-                        .filter(|m| m.capture() == Some(square::TypePiece::King))
-                        .collect();
-                    if !invalid_move.is_empty() {
-                        tracing::debug!(
-                            "{}",
-                            graph[node_id].game().bit_position().to().chessboard()
-                        );
-                    }
-                    graph[node_id].set_untried_moves(moves);
-                } else {
-                    // selection: all moves have been expanded: select the best ucb1 score
-                    match mcts_tree::Node::argmax(graph, node.children(), self.conf.c) {
-                        None => tracing::debug!("not found"),
-                        Some(idx) => {
-                            tracing::debug!("found");
-                            let selected_node_idx = node.children().get(idx).unwrap();
-                            self.mcts_run(graph, *selected_node_idx, mcts_stat)
-                        }
-                    }
-                }
             // expand untried moves first
-            } else {
-                // expansion: add an untried move as a child
-                let expanded_node_idx = self.exploration(graph, node_id);
-                tracing::debug!("simulation");
-                let (n_white_wins, n_black_wins) =
-                    self.mcts_simulation(graph, expanded_node_idx, mcts_stat);
-                self.mcts_back_propagation(graph, expanded_node_idx, n_white_wins, n_black_wins);
+            if node.children().is_empty() || node.is_expandable(&graph, &self.conf) {
+                break;
+            }
+            match mcts_tree::Node::argmax(&graph, node.children(), &self.conf) {
+                None => break,
+                Some(idx) => {
+                    node_id = node.children()[idx];
+                    graph[node_id].add_virtual_loss();
+                    path.push(node_id);
+                }
             }
         }
+        let leaf_id = self.expansion(graph, node_id, mcts_stat, rng);
+        let game = graph.read().unwrap()[leaf_id].game().clone();
+        let n_playouts = self.conf.iterations_per_move;
+        let white_score = if game.end_game() == game_state::EndGame::None {
+            tracing::debug!("simulation");
+            self.mcts_simulation(&game, mcts_stat, rng)
+        } else {
+            // a terminal node counts as many visits as a simulated one
+            Self::evaluate_end_game(&game) * n_playouts as f64
+        };
+        let graph = graph.read().unwrap();
+        self.mcts_back_propagation(&graph, leaf_id, white_score, n_playouts);
+        for node_id in path {
+            graph[node_id].remove_virtual_loss();
+        }
+    }
+    // add a child to the node, return the node itself if no move can be expanded
+    fn expansion(
+        &self,
+        graph: &RwLock<mcts_tree::Graph>,
+        node_id: mcts_tree::NodeIdx,
+        mcts_stat: &mut MctsStat,
+        rng: &mut ChaCha8Rng,
+    ) -> mcts_tree::NodeIdx {
+        let is_generated = {
+            let graph = graph.read().unwrap();
+            !graph[node_id].untried_moves().is_empty() || !graph[node_id].children().is_empty()
+        };
+        if !is_generated {
+            // generate moves without locking the tree
+            let mut game = graph.read().unwrap()[node_id].game().clone();
+            if game.end_game() != game_state::EndGame::None {
+                return node_id;
+            }
+            let moves = game.gen_moves();
+            if moves
+                .iter()
+                .any(|m| m.capture() == Some(square::TypePiece::King))
+            {
+                tracing::debug!("{}", game.bit_position().to().chessboard());
+            }
+            let priors = self.priors(&mut game, &moves, &mut mcts_stat.stat_eval);
+            let mut graph = graph.write().unwrap();
+            // the moves may have been generated by another thread meanwhile
+            let node = &mut graph[node_id];
+            if node.untried_moves().is_empty() && node.children().is_empty() {
+                node.set_untried_moves(moves, priors);
+            }
+        }
+        let mut graph = graph.write().unwrap();
+        if graph[node_id].is_expandable(&graph, &self.conf) {
+            self.exploration(&mut graph, node_id, rng)
+        } else {
+            node_id
+        }
     }
     fn exploration(
         &self,
         graph: &mut mcts_tree::Graph,
         node_id: mcts_tree::NodeIdx,
+        rng: &mut ChaCha8Rng,
     ) -> mcts_tree::NodeIdx {
-        let mut rng = rand::thread_rng();
         let node = &graph[node_id];
         tracing::debug!("exploration / {}", node.untried_moves().len());
-        let random_index = rng.gen_range(0..node.untried_moves().len()); // Random index
-        mcts_tree::Node::exploration(graph, node_id, random_index, &self.zobrist_table)
+        let index = match self.conf.prior {
+            config::PriorSource::Uniform => rng.gen_range(0..node.untried_moves().len()),
+            // most probable move first
+            _ => node
+                .untried_priors()
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(i, _)| i)
+                .unwrap(),
+        };
+        mcts_tree::Node::exploration(graph, node_id, index, &self.zobrist_table)
+    }
+    fn set_untried_moves(
+        &self,
+        graph: &mut mcts_tree::Graph,
+        node_id: mcts_tree::NodeIdx,
+        moves: Vec<bitboard::BitBoardMove>,
+        stat_eval: &mut stat_eval::StatEval,
+    ) {
+        let mut game = graph[node_id].game().clone();
+        let priors = self.priors(&mut game, &moves, stat_eval);
+        graph[node_id].set_untried_moves(moves, priors);
     }
+    // prior probabilities of the moves
+    fn priors(
+        &self,
+        game: &mut game_state::GameState,
+        moves: &[bitboard::BitBoardMove],
+        stat_eval: &mut stat_eval::StatEval,
+    ) -> Vec<f64> {
+        match self.conf.prior {
+            config::PriorSource::Uniform => vec![1.0 / moves.len().max(1) as f64; moves.len()],
+            config::PriorSource::Heuristic => {
+                let logits: Vec<f64> = moves
+                    .iter()
+                    .map(|m| {
+                        let capture = score::biased_capture(m.type_piece(), m.capture());
+                        let mut logit = capture.max(0) as f64;
+                        if m.promotion().is_some() {
+                            logit += PRIOR_PROMOTION_BONUS;
+                        }
+                        self.play_move(game, *m);
+                        if game.check_status().is_check() {
+                            logit += PRIOR_CHECK_BONUS;
+                        }
+                        game.unmake_move();
+                        logit
+                    })
+                    .collect();
+                softmax(&logits, 1.0)
+            }
+            config::PriorSource::Evaluator => {
+                let scores = self.move_scores(game, moves, stat_eval);
+                softmax(&scores, self.conf.prior_temperature)
+            }
+        }
+    }
+    // sum of the white scores of the playouts
     fn mcts_simulation(
         &self,
-        graph: &mcts_tree::Graph,
-        expanded_node_idx: mcts_tree::NodeIdx,
+        game: &game_state::GameState,
         mcts_stat: &mut MctsStat,
-    ) -> (u64, u64) {
-        let mut n_white_wins: u64 = 0;
-        let mut n_black_wins: u64 = 0;
-        for _i in 0..self.conf.iterations_per_move {
-            let (n_white_win, n_black_win) =
-                self.mcts_one_simulation(graph, expanded_node_idx, mcts_stat);
-            n_white_wins += n_white_win;
-            n_black_wins += n_black_win;
-        }
-        (n_white_wins, n_black_wins)
+        rng: &mut ChaCha8Rng,
+    ) -> f64 {
+        (0..self.conf.iterations_per_move)
+            .map(|_| self.mcts_one_simulation(game, mcts_stat, rng))
+            .sum()
     }
-    // return None if Draw game, else return the winner
+    // return the white score of one playout: 1 for a white win, 0.5 for a draw, 0 for a black win
     fn mcts_one_simulation(
         &self,
-        graph: &mcts_tree::Graph,
-        node_id: mcts_tree::NodeIdx,
+        game: &game_state::GameState,
         mcts_stat: &mut MctsStat,
-    ) -> (u64, u64) {
-        let mut rng = rand::thread_rng();
-        let mut game = graph[node_id].game().clone();
+        rng: &mut ChaCha8Rng,
+    ) -> f64 {
+        let mut game = game.clone();
         let mut n_moves_gen: u64 = 0;
+        let mut n_plies: u64 = 0;
         while game.end_game() == game_state::EndGame::None {
+            if self.conf.playout_cutoff_plies > 0 && n_plies >= self.conf.playout_cutoff_plies {
+                break;
+            }
             let moves = game.gen_moves();
             n_moves_gen += moves.len() as u64;
-            let random_index = rng.gen_range(0..moves.len());
-            let m = moves[random_index];
-            let long_algebraic_move = long_notation::LongAlgebricNotationMove::build_from_b_move(m);
-            let _ = game.play_moves(&[long_algebraic_move], &self.zobrist_table, None, false);
+            let m = self.playout_move(&mut game, &moves, rng, &mut mcts_stat.stat_eval);
+            self.play_move(&mut game, m);
             game.update_endgame_status();
+            n_plies += 1;
+        }
+        mcts_stat.inc(1, n_plies, n_moves_gen);
+        if game.end_game() == game_state::EndGame::None {
+            let score = self.evaluate(&game, &mut mcts_stat.stat_eval);
+            win_probability(score, self.conf.sigmoid_scale)
+        } else {
+            Self::evaluate_end_game(&game)
+        }
+    }
+    fn playout_move(
+        &self,
+        game: &mut game_state::GameState,
+        moves: &[bitboard::BitBoardMove],
+        rng: &mut impl Rng,
+        stat_eval: &mut stat_eval::StatEval,
+    ) -> bitboard::BitBoardMove {
+        let weights: Vec<f64> = match self.conf.playout {
+            config::PlayoutPolicy::Random => return moves[rng.gen_range(0..moves.len())],
+            config::PlayoutPolicy::CaptureCheckBiased => moves
+                .iter()
+                .map(|m| {
+                    let mut weight = 1.0;
+                    if m.capture().is_some() || m.promotion().is_some() {
+                        weight += self.conf.capture_weight;
+                    }
+                    self.play_move(game, *m);
+                    if game.check_status().is_check() {
+                        weight += self.conf.check_weight;
+                    }
+                    game.unmake_move();
+                    weight
+                })
+                .collect(),
+            config::PlayoutPolicy::Softmax => {
+                let scores = self.move_scores(game, moves, stat_eval);
+                softmax(&scores, self.conf.softmax_temperature)
+            }
+        };
+        match WeightedIndex::new(&weights) {
+            Ok(distribution) => moves[distribution.sample(rng)],
+            Err(_) => moves[rng.gen_range(0..moves.len())],
         }
-        mcts_stat.inc(
-            1,
-            game.bit_position().bit_position_status().n_moves() as u64,
-            n_moves_gen,
-        );
-        Self::evaluate_end_game(&game)
     }
-    pub fn evaluate_end_game(game: &game_state::GameState) -> (u64, u64) {
+    // one ply evaluation of each move from the perspective of the player to move
+    fn move_scores(
+        &self,
+        game: &mut game_state::GameState,
+        moves: &[bitboard::BitBoardMove],
+        stat_eval: &mut stat_eval::StatEval,
+    ) -> Vec<f64> {
+        let player_turn = game.bit_position().bit_position_status().player_turn();
+        moves
+            .iter()
+            .map(|m| {
+                self.play_move(game, *m);
+                let score = self.evaluate(game, stat_eval) as f64;
+                game.unmake_move();
+                if player_turn == square::Color::White {
+                    score
+                } else {
+                    -score
+                }
+            })
+            .collect()
+    }
+    fn play_move(&self, game: &mut game_state::GameState, m: bitboard::BitBoardMove) {
+        game.make_move(&m, &self.zobrist_table);
+    }
+    // score from white perspective
+    fn evaluate(&self, game: &game_state::GameState, stat_eval: &mut stat_eval::StatEval) -> i32 {
+        self.evaluator.evaluate(game, stat_eval, &None, self.id())
+    }
+    // white score of a finished game
+    pub fn evaluate_end_game(game: &game_state::GameState) -> f64 {
         let color_win_opt = match game.end_game() {
             game_state::EndGame::Mat(lost_color) => Some(lost_color.switch()),
             game_state::EndGame::TimeOutLost(lost_color) => Some(lost_color.switch()),
             _ => None,
         };
         match color_win_opt {
-            Some(square::Color::White) => (1, 0),
-            Some(square::Color::Black) => (0, 1),
-            None => (0, 0),
+            Some(square::Color::White) => 1.0,
+            Some(square::Color::Black) => 0.0,
+            None => 0.5,
         }
     }
+    // white_score is the sum of the white scores of n_playouts playouts
     pub fn mcts_back_propagation(
         &self,
-        graph: &mut mcts_tree::Graph,
+        graph: &mcts_tree::Graph,
         node_id: mcts_tree::NodeIdx,
-        n_white_wins: u64,
-        n_black_wins: u64,
+        white_score: f64,
+        n_playouts: u64,
     ) {
         let span = span_debug();
         let _enter = span.enter();
 
         tracing::debug!("back propagation\n");
-        // FIXME: check if player_turm is the opposite
-        let player_turn = graph[node_id]
-            .game()
-            .bit_position()
-            .bit_position_status()
-            .player_turn();
-        let n_wins = if player_turn == square::Color::White {
-            n_white_wins
-        } else {
-            n_black_wins
-        };
-        graph[node_id].inc_stat(n_wins, self.conf.iterations_per_move);
-        let mut node_iter = node_id;
-        while let Some(node_id) = graph[node_iter].parent() {
+        // a proof is propagated to the ancestors as long as they get proven
+        let mut is_proof_updated = graph[node_id].is_proven();
+        let mut node_iter_opt = Some(node_id);
+        while let Some(node_iter) = node_iter_opt {
+            // the node value is seen by the player who moved into the node,
+            // i.e. the opponent of the player to move
+            let player_turn = graph[node_iter]
+                .game()
+                .bit_position()
+                .bit_position_status()
+                .player_turn();
+            let value = if player_turn == square::Color::White {
+                n_playouts as f64 - white_score
+            } else {
+                white_score
+            };
+            graph[node_iter].inc_stat(value, n_playouts);
             tracing::debug!(
-                "inc {:?} {}/{} -> node updated = {}/{}",
-                node_id,
-                n_wins,
-                self.conf.iterations_per_move,
-                graph[node_id].n_wins(),
-                graph[node_id].visits()
+                "inc {:?} {}/{} -> node updated = {}",
+                node_iter,
+                value,
+                n_playouts,
+                graph[node_iter]
             );
-            graph[node_id].inc_stat(n_wins, self.conf.iterations_per_move);
-            node_iter = node_id;
+            node_iter_opt = graph[node_iter].parent();
+            if let Some(parent) = node_iter_opt.filter(|_| is_proof_updated) {
+                is_proof_updated = mcts_tree::Node::update_proof(graph, parent);
+            }
         }
     }
 }
+
+// probabilities proportional to exp(logit / temperature)
+pub fn softmax(logits: &[f64], temperature: f64) -> Vec<f64> {
+    let max_logit = logits.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let weights: Vec<f64> = logits
+        .iter()
+        .map(|logit| ((logit - max_logit) / temperature).exp())
+        .collect();
+    let sum: f64 = weights.iter().sum();
+    weights.iter().map(|weight| weight / sum).collect()
+}
+
+// evaluation (1000 = 1 pawn) converted to a win probability
+pub fn win_probability(score: i32, sigmoid_scale: f64) -> f64 {
+    1.0 / (1.0 + (-(score as f64) / sigmoid_scale).exp())
+}
 unsafe impl Send for EngineMcts {}
 
 const MCTS_ENGINE_ID_NAME: &str = "MCTS engine";
@@ -287,15 +606,17 @@ impl logic::Engine for EngineMcts {
         self_actor: Addr<dispatcher::EngineDispatcher>,
         stat_actor_opt: Option<stat_entity::StatActor>,
         game: game_state::GameState,
+        time_manager_opt: Option<time_allocation::TimeManager>,
         is_stop: &Arc<AtomicBool>,
     ) {
         let moves = logic::gen_moves(game.bit_position());
         if !moves.is_empty() {
-            let best_move = self.mcts(&game, is_stop);
+            let best_move = self.mcts(&game, time_manager_opt.as_ref(), is_stop);
             self_actor.do_send(dispatcher::handler_engine::EngineStopThinking::new(
                 stat_actor_opt,
             ));
-            let reply = dispatcher::handler_engine::EngineEndOfAnalysis(best_move);
+            let reply =
+                dispatcher::handler_engine::EngineEndOfAnalysis(best_move, game.last_hash());
             if let Some(debug_actor) = &self.debug_actor_opt {
                 debug_actor.do_send(debug::AddMessage(format!(
                     "Engine of id {:?} reply is: '{:?}'",
@@ -310,3 +631,261 @@ impl logic::Engine for EngineMcts {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::notation::fen::{self, EncodeUserInput};
+    use crate::ui::notation::long_notation;
+
+    fn game(fen: &str, zobrist_table: &zobrist::Zobrist) -> game_state::GameState {
+        let position = fen::Fen::decode(fen).expect("Failed to decode FEN");
+        game_state::GameState::new(position, zobrist_table)
+    }
+
+    #[test]
+    fn test_win_probability() {
+        assert_eq!(win_probability(0, 1500.0), 0.5);
+        assert!(win_probability(3000, 1500.0) > 0.85);
+        let p = win_probability(1000, 1500.0) + win_probability(-1000, 1500.0);
+        assert!((p - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_back_propagation() {
+        let zobrist_table = zobrist::Zobrist::new();
+        let engine = EngineMcts::new(None, zobrist_table.clone(), &config::MctsConfig::new(1));
+        let game = game(fen::FEN_START_POSITION, &zobrist_table);
+        let mut graph = mcts_tree::Graph::new();
+        let root = mcts_tree::Node::build_root(game.clone(), &game.gen_moves());
+        let root_id = mcts_tree::add_node_to_graph(&mut graph, root);
+        let child_id = mcts_tree::Node::exploration(&mut graph, root_id, 0, &zobrist_table);
+        // a white win is good for the white move leading to the child
+        engine.mcts_back_propagation(&mut graph, child_id, 1.0, 1);
+        engine.mcts_back_propagation(&mut graph, child_id, 0.5, 1);
+        assert_eq!(graph[child_id].value(), 1.5);
+        assert_eq!(graph[child_id].visits(), 2);
+        assert_eq!(graph[root_id].value(), 0.5);
+        assert_eq!(graph[root_id].visits(), 2);
+    }
+
+    #[test]
+    fn test_priors() {
+        let probabilities = softmax(&[0.0, 1000.0, 1000.0], 500.0);
+        assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(probabilities[1], probabilities[2]);
+        assert!(probabilities[0] < probabilities[1]);
+        let zobrist_table = zobrist::Zobrist::new();
+        let mut conf = config::MctsConfig::new(1);
+        conf.prior = config::PriorSource::Heuristic;
+        let engine = EngineMcts::new(None, zobrist_table.clone(), &conf);
+        let mut game = game("4k3/8/8/3q4/2P5/8/8/4K3 w - - 0 1", &zobrist_table);
+        let moves = game.gen_moves();
+        let priors = engine.priors(&mut game, &moves, &mut stat_eval::StatEval::default());
+        let best = (0..moves.len())
+            .max_by(|a, b| priors[*a].total_cmp(&priors[*b]))
+            .unwrap();
+        assert_eq!(moves[best].capture(), Some(square::TypePiece::Queen));
+    }
+
+    #[test]
+    fn test_puct_selection() {
+        let zobrist_table = zobrist::Zobrist::new();
+        let mut conf = config::MctsConfig::new(1);
+        conf.selection = config::SelectionFormula::Puct;
+        conf.prior = config::PriorSource::Heuristic;
+        conf.widening_k = 1.0;
+        let engine = EngineMcts::new(None, zobrist_table.clone(), &conf);
+        let game = game("4k3/8/8/3q4/2P5/8/8/4K3 w - - 0 1", &zobrist_table);
+        let mut graph = mcts_tree::Graph::new();
+        let root = mcts_tree::Node::build_root(game.clone(), &[]);
+        let root_id = mcts_tree::add_node_to_graph(&mut graph, root);
+        let mut mcts_stat = MctsStat::default();
+        engine.set_untried_moves(
+            &mut graph,
+            root_id,
+            game.gen_moves(),
+            &mut mcts_stat.stat_eval,
+        );
+        let graph = RwLock::new(graph);
+        let mut rng = engine.rng(0);
+        // the capture has the highest prior and is expanded first
+        engine.mcts_run(&graph, root_id, &mut mcts_stat, &mut rng);
+        {
+            let graph = graph.read().unwrap();
+            let child_id = graph[root_id].children()[0];
+            let edge = graph.find_edge(root_id, child_id).unwrap();
+            assert_eq!(graph[edge].0.capture(), Some(square::TypePiece::Queen));
+        }
+        // progressive widening: a single child until the root has 4 visits
+        engine.mcts_run(&graph, root_id, &mut mcts_stat, &mut rng);
+        engine.mcts_run(&graph, root_id, &mut mcts_stat, &mut rng);
+        let graph = graph.into_inner().unwrap();
+        assert_eq!(graph[root_id].children().len(), 1);
+        assert_eq!(graph[root_id].max_children(&conf), 1);
+        let graph = RwLock::new(graph);
+        for _ in 0..20 {
+            engine.mcts_run(&graph, root_id, &mut mcts_stat, &mut rng);
+        }
+        let graph = graph.into_inner().unwrap();
+        let n_children = graph[root_id].children().len();
+        assert!(n_children > 1 && n_children <= graph[root_id].max_children(&conf));
+    }
+
+    fn subtree_size(graph: &mcts_tree::Graph, node_id: mcts_tree::NodeIdx) -> usize {
+        1 + graph[node_id]
+            .children()
+            .iter()
+            .map(|n| subtree_size(graph, *n))
+            .sum::<usize>()
+    }
+
+    #[test]
+    fn test_tree_reuse() {
+        let zobrist_table = zobrist::Zobrist::new();
+        let mut conf = config::MctsConfig::new(1);
+        conf.node_budget_opt = Some(200);
+        let engine = EngineMcts::new(None, zobrist_table.clone(), &conf);
+        let mut game = game(fen::FEN_START_POSITION, &zobrist_table);
+        let is_stop = Arc::new(AtomicBool::new(false));
+        // a position not in the tree is searched from scratch
+        engine.mcts(&game, None, &is_stop);
+        let unknown = self::game("4k3/8/8/3q4/2P5/8/8/4K3 w - - 0 1", &zobrist_table);
+        assert!(engine.reused_tree(&unknown).is_none());
+        engine.mcts(&game, None, &is_stop);
+        let (graph, root_id) = engine.tree_opt.lock().unwrap().take().unwrap();
+        // iterations generating the moves of a leaf do not visit the root
+        assert!(graph[root_id].visits() > 100 && graph[root_id].visits() <= 200);
+        // most visited move and reply
+        let most_visited = |node_id: mcts_tree::NodeIdx| {
+            *graph[node_id]
+                .children()
+                .iter()
+                .max_by_key(|n| graph[**n].visits())
+                .unwrap()
+        };
+        let child_id = most_visited(root_id);
+        let reply_id = most_visited(child_id);
+        let played = graph[graph.find_edge(root_id, child_id).unwrap()].0;
+        let reply = graph[graph.find_edge(child_id, reply_id).unwrap()].0;
+        let reply_visits = graph[reply_id].visits();
+        let reply_subtree_size = subtree_size(&graph, reply_id);
+        assert!(reply_visits > 0);
+        *engine.tree_opt.lock().unwrap() = Some((graph, root_id));
+        engine.play_move(&mut game, played);
+        engine.play_move(&mut game, reply);
+        let (graph, root_id) = engine.reused_tree(&game).unwrap();
+        assert!(graph[root_id].is_root());
+        assert_eq!(graph[root_id].visits(), reply_visits);
+        assert_eq!(graph.node_count(), reply_subtree_size);
+        assert_eq!(subtree_size(&graph, root_id), reply_subtree_size);
+    }
+
+    #[test]
+    fn test_time_budget() {
+        let zobrist_table = zobrist::Zobrist::new();
+        let engine = EngineMcts::new(None, zobrist_table.clone(), &config::MctsConfig::new(1));
+        let game = game(fen::FEN_START_POSITION, &zobrist_table);
+        let start = Instant::now();
+        let time_manager =
+            time_allocation::TimeManager::new(time_allocation::TimeLimits::move_time(400), start);
+        let is_stop = Arc::new(AtomicBool::new(false));
+        engine.mcts(&game, Some(&time_manager), &is_stop);
+        // the search stops at the soft limit
+        assert!(start.elapsed() >= time_manager.limits().soft);
+        assert!(start.elapsed() < time_manager.limits().hard * 2);
+    }
+
+    // visits of the moves of the root
+    fn root_visits(engine: &EngineMcts) -> Vec<(String, u64)> {
+        let tree = engine.tree_opt.lock().unwrap();
+        let (graph, root_id) = tree.as_ref().unwrap();
+        let mut visits: Vec<(String, u64)> = graph[*root_id]
+            .children()
+            .iter()
+            .map(|n| {
+                let edge = graph.find_edge(*root_id, *n).unwrap();
+                let m = long_notation::LongAlgebricNotationMove::build_from_b_move(graph[edge].0);
+                (m.cast(), graph[*n].visits())
+            })
+            .collect();
+        visits.sort();
+        visits
+    }
+
+    #[test]
+    fn test_parallel_search() {
+        let zobrist_table = zobrist::Zobrist::new();
+        let game = game(fen::FEN_START_POSITION, &zobrist_table);
+        let is_stop = Arc::new(AtomicBool::new(false));
+        let mut conf = config::MctsConfig::new(1);
+        conf.playout_cutoff_plies = 20;
+        conf.node_budget_opt = Some(300);
+        conf.seed_opt = Some(42);
+        // deterministic with one thread and a seed
+        let engine1 = EngineMcts::new(None, zobrist_table.clone(), &conf);
+        let engine2 = EngineMcts::new(None, zobrist_table.clone(), &conf);
+        let best_move = engine1.mcts(&game, None, &is_stop);
+        assert_eq!(engine2.mcts(&game, None, &is_stop), best_move);
+        assert_eq!(root_visits(&engine1), root_visits(&engine2));
+        // the threads share the node budget and all virtual losses are removed
+        conf.threads = 4;
+        let engine = EngineMcts::new(None, zobrist_table.clone(), &conf);
+        engine.mcts(&game, None, &is_stop);
+        let tree = engine.tree_opt.lock().unwrap();
+        let (graph, root_id) = tree.as_ref().unwrap();
+        assert!(graph[*root_id].visits() > 0 && graph[*root_id].visits() <= 300);
+        assert!(graph.node_indices().all(|n| graph[n].virtual_losses() == 0));
+    }
+
+    #[test]
+    fn test_solver() {
+        let zobrist_table = zobrist::Zobrist::new();
+        let is_stop = Arc::new(AtomicBool::new(false));
+        let mut conf = config::MctsConfig::new(1);
+        conf.playout_cutoff_plies = 10;
+        conf.node_budget_opt = Some(5000);
+        conf.prior = config::PriorSource::Heuristic;
+        conf.seed_opt = Some(1);
+        let engine = EngineMcts::new(None, zobrist_table.clone(), &conf);
+        // mate in one: Ra8#
+        let game_mat_1 = game("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", &zobrist_table);
+        let best_move = engine.mcts(&game_mat_1, None, &is_stop);
+        let best_move = long_notation::LongAlgebricNotationMove::build_from_b_move(best_move);
+        assert_eq!(best_move.cast(), "a1a8");
+        // mate in two: Kf7 Kh7 Rh1#
+        let game_mat_2 = game("7k/8/5K2/8/8/8/8/6R1 w - - 0 1", &zobrist_table);
+        engine.mcts(&game_mat_2, None, &is_stop);
+        let tree = engine.tree_opt.lock().unwrap();
+        let (graph, root_id) = tree.as_ref().unwrap();
+        // the root is lost by black, who moved into it
+        assert_eq!(graph[*root_id].proof(), mcts_tree::Proof::Loss);
+        // the search stops once the root is solved
+        assert!(graph[*root_id].visits() < 5000);
+        let best_child = engine.best_child(graph, *root_id).unwrap();
+        assert_eq!(graph[best_child].proof(), mcts_tree::Proof::Win);
+        let reply = graph[best_child].children()[0];
+        assert_eq!(graph[reply].proof(), mcts_tree::Proof::Loss);
+    }
+
+    #[test]
+    fn test_softmax_playout() {
+        let zobrist_table = zobrist::Zobrist::new();
+        let mut conf = config::MctsConfig::new(1);
+        conf.playout = config::PlayoutPolicy::Softmax;
+        conf.softmax_temperature = 1.0;
+        conf.playout_cutoff_plies = 2;
+        let engine = EngineMcts::new(None, zobrist_table.clone(), &conf);
+        // the black queen is not protected
+        let mut game = game("4k3/8/8/3q4/8/8/3Q4/4K3 w - - 0 1", &zobrist_table);
+        let moves = game.gen_moves();
+        let mut stat_eval = stat_eval::StatEval::default();
+        let m = engine.playout_move(&mut game, &moves, &mut rand::thread_rng(), &mut stat_eval);
+        assert_eq!(m.capture(), Some(square::TypePiece::Queen));
+        // the position is unchanged by the one ply evaluation
+        assert_eq!(game.gen_moves().len(), moves.len());
+        let white_score =
+            engine.mcts_one_simulation(&game, &mut MctsStat::default(), &mut engine.rng(0));
+        assert!(white_score > 0.9);
+    }
+}
@@ -6,7 +6,8 @@ use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use super::config::config;
 use super::engine_logic::{self as logic, Engine};
-use super::evaluation::{self, score, stat_eval};
+use super::time_allocation;
+use super::evaluation::{self, evaluator, score, stat_eval};
 use crate::entity::engine::actor::engine_dispatcher as dispatcher;
 use crate::entity::game::component::bitboard::zobrist;
 use crate::entity::game::component::game_state;
@@ -27,6 +28,7 @@ pub struct EngineMinimax {
     debug_actor_opt: Option<debug::DebugActor>,
     zobrist_table: zobrist::Zobrist,
     conf: config::MinimaxConf,
+    evaluator: Arc<dyn evaluator::Evaluator>,
 }
 impl EngineMinimax {
     pub fn new(
@@ -40,11 +42,15 @@ impl EngineMinimax {
             debug_actor_opt,
             zobrist_table,
             conf: conf.clone(),
+            evaluator: Arc::new(evaluator::ClassicEvaluator::default()),
         }
     }
     pub fn set_id_number(&mut self, id_number: &str) {
         self.id_number = id_number.to_string();
     }
+    pub fn set_evaluator(&mut self, evaluator: Arc<dyn evaluator::Evaluator>) {
+        self.evaluator = evaluator;
+    }
     fn minimax(
         &self,
         game: &game_state::GameState,
@@ -181,8 +187,7 @@ impl EngineMinimax {
         if current_depth == 0 {
             println!("{}", updated_variant);
         }
-        game.play_moves(&[long_algebraic_move], &self.zobrist_table, None, false)
-            .unwrap();
+        game.make_move(&m, &self.zobrist_table);
         game.update_endgame_status();
 
         let score = if game.end_game() == game_state::EndGame::None {
@@ -202,7 +207,8 @@ impl EngineMinimax {
                 score::Score::new(-score.value(), current_depth, self.conf.max_depth)
             } else {
                 score::Score::new(
-                    evaluation::evaluate_position(game, stat_eval, &stat_actor_opt, self.id()),
+                    self.evaluator
+                        .evaluate(game, stat_eval, &stat_actor_opt, self.id()),
                     current_depth,
                     self.conf.max_depth,
                 )
@@ -211,7 +217,7 @@ impl EngineMinimax {
             evaluation::handle_end_game_scenario(game, current_depth, self.conf.max_depth)
         };
 
-        game.play_back();
+        game.unmake_move();
         score
     }
 }
@@ -238,6 +244,7 @@ impl logic::Engine for EngineMinimax {
         self_actor: Addr<dispatcher::EngineDispatcher>,
         stat_actor_opt: Option<stat_entity::StatActor>,
         game: game_state::GameState,
+        _time_manager_opt: Option<time_allocation::TimeManager>,
         is_stop: &Arc<AtomicBool>,
     ) {
         // First generate moves
@@ -248,7 +255,8 @@ impl logic::Engine for EngineMinimax {
             self_actor.do_send(dispatcher::handler_engine::EngineStopThinking::new(
                 stat_actor_opt,
             ));
-            let reply = dispatcher::handler_engine::EngineEndOfAnalysis(best_move);
+            let reply =
+                dispatcher::handler_engine::EngineEndOfAnalysis(best_move, game.last_hash());
             if let Some(debug_actor) = &self.debug_actor_opt {
                 debug_actor.do_send(debug::AddMessage(format!(
                     "Engine of id {:?} reply is: '{:?}'",
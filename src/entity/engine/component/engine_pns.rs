@@ -0,0 +1,526 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use actix::Addr;
+
+use super::config::config;
+use super::engine_logic as logic;
+use super::evaluation::score;
+use super::time_allocation;
+use crate::entity::engine::actor::engine_dispatcher as dispatcher;
+use crate::entity::game::component::bitboard::zobrist;
+use crate::entity::game::component::game_state;
+use crate::entity::stat::actor::stat_entity;
+use crate::ui::notation::long_notation;
+use crate::{entity::game::component::bitboard, monitoring::debug};
+
+use crate::span_debug;
+
+fn span_debug() -> tracing::Span {
+    span_debug!("engine_pns")
+}
+
+// proof and disproof numbers of a solved node
+const INFINITY: u64 = 1 << 40;
+
+// Depth-first proof-number search (df-pn) of a mate.
+// OR nodes: the attacker is to move, AND nodes: the defender is to move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TtEntry {
+    // proof number: 0 if the attacker mates
+    pn: u64,
+    // disproof number: 0 if there is no mate
+    dn: u64,
+    // half moves left to mate when the entry was computed
+    remaining: u8,
+    // half moves to mate for a proven node
+    mate_plies: u8,
+}
+impl TtEntry {
+    fn new(pn: u64, dn: u64, remaining: u8) -> Self {
+        Self {
+            pn,
+            dn,
+            remaining,
+            mate_plies: 0,
+        }
+    }
+    fn proven() -> Self {
+        Self::new(0, INFINITY, 0)
+    }
+    fn disproven(remaining: u8) -> Self {
+        Self::new(INFINITY, 0, remaining)
+    }
+}
+
+struct Solver<'a> {
+    zobrist_table: &'a zobrist::Zobrist,
+    tt: HashMap<zobrist::ZobristHash, TtEntry>,
+    n_nodes: u64,
+    max_nodes: u64,
+    is_stop: &'a Arc<AtomicBool>,
+    deadline_opt: Option<Instant>,
+}
+impl<'a> Solver<'a> {
+    fn new(
+        zobrist_table: &'a zobrist::Zobrist,
+        max_nodes: u64,
+        is_stop: &'a Arc<AtomicBool>,
+        deadline_opt: Option<Instant>,
+    ) -> Self {
+        Self {
+            zobrist_table,
+            tt: HashMap::new(),
+            n_nodes: 0,
+            max_nodes,
+            is_stop,
+            deadline_opt,
+        }
+    }
+    fn is_aborted(&self) -> bool {
+        self.n_nodes >= self.max_nodes
+            || self.is_stop.load(Ordering::Relaxed)
+            || self
+                .deadline_opt
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+    // a mate found with fewer half moves left is still a mate, no mate with more half moves
+    // left means no mate with fewer
+    fn lookup(&self, hash: &zobrist::ZobristHash, remaining: u8) -> Option<TtEntry> {
+        let entry = self.tt.get(hash)?;
+        let is_valid = (entry.pn == 0 && entry.remaining <= remaining)
+            || (entry.dn == 0 && entry.remaining >= remaining)
+            || entry.remaining == remaining;
+        is_valid.then_some(*entry)
+    }
+    fn play(&self, game: &mut game_state::GameState, b_move: bitboard::BitBoardMove) {
+        game.make_move(&b_move, self.zobrist_table);
+        game.update_endgame_status();
+    }
+    // initial numbers of a node just played: the fewer defender moves, the easier the proof
+    fn evaluate_leaf(&self, game: &game_state::GameState, is_or: bool, remaining: u8) -> TtEntry {
+        match game.end_game() {
+            game_state::EndGame::Mat(_) if !is_or => TtEntry::proven(),
+            game_state::EndGame::None if remaining > 0 => {
+                if is_or {
+                    TtEntry::new(1, 1, remaining)
+                } else {
+                    TtEntry::new(game.gen_moves().len() as u64, 1, remaining)
+                }
+            }
+            // attacker mated, draw, or no half move left to mate
+            _ => TtEntry::disproven(remaining),
+        }
+    }
+    // multiple iterative deepening: search the node until one of its numbers reaches its threshold
+    fn mid(
+        &mut self,
+        game: &mut game_state::GameState,
+        is_or: bool,
+        remaining: u8,
+        th_pn: u64,
+        th_dn: u64,
+    ) -> TtEntry {
+        self.n_nodes += 1;
+        let hash = game.last_hash();
+        let mut children: Vec<(bitboard::BitBoardMove, zobrist::ZobristHash, TtEntry)> = vec![];
+        for b_move in game.gen_moves() {
+            self.play(game, b_move);
+            let child_hash = game.last_hash();
+            let leaf = self.evaluate_leaf(game, !is_or, remaining - 1);
+            if leaf.pn == 0 || leaf.dn == 0 {
+                self.tt.insert(child_hash.clone(), leaf);
+            }
+            game.unmake_move();
+            children.push((b_move, child_hash, leaf));
+        }
+        loop {
+            let entries: Vec<TtEntry> = children
+                .iter()
+                .map(|(_, child_hash, leaf)| {
+                    self.lookup(child_hash, remaining - 1).unwrap_or(*leaf)
+                })
+                .collect();
+            let (pn, dn) = if is_or {
+                (min_pn(&entries), sum_dn(&entries))
+            } else {
+                (sum_pn(&entries), min_dn(&entries))
+            };
+            if pn == 0 || dn == 0 || pn >= th_pn || dn >= th_dn || self.is_aborted() {
+                let mut entry = TtEntry::new(pn, dn, remaining);
+                if pn == 0 {
+                    // shortest mate for the attacker, longest defence for the defender
+                    let proven = entries.iter().filter(|entry| entry.pn == 0);
+                    entry.mate_plies = if is_or {
+                        1 + proven.map(|entry| entry.mate_plies).min().unwrap()
+                    } else {
+                        1 + proven.map(|entry| entry.mate_plies).max().unwrap()
+                    };
+                }
+                self.tt.insert(hash, entry);
+                return entry;
+            }
+            // the most proving child and the thresholds until the second best one
+            let key = |entry: &TtEntry| if is_or { entry.pn } else { entry.dn };
+            let mut best = 0;
+            let mut second = INFINITY;
+            for (i, entry) in entries.iter().enumerate().skip(1) {
+                if key(entry) < key(&entries[best]) {
+                    second = key(&entries[best]);
+                    best = i;
+                } else if key(entry) < second {
+                    second = key(entry);
+                }
+            }
+            let best_entry = entries[best];
+            let (child_th_pn, child_th_dn) = if is_or {
+                (
+                    th_pn.min(second.saturating_add(1)),
+                    (th_dn - dn).saturating_add(best_entry.dn).min(INFINITY),
+                )
+            } else {
+                (
+                    (th_pn - pn).saturating_add(best_entry.pn).min(INFINITY),
+                    th_dn.min(second.saturating_add(1)),
+                )
+            };
+            self.play(game, children[best].0);
+            self.mid(game, !is_or, remaining - 1, child_th_pn, child_th_dn);
+            game.unmake_move();
+        }
+    }
+    // the mating moves of the attacker and the longest defence
+    fn mating_line(
+        &self,
+        game: &mut game_state::GameState,
+        remaining: u8,
+    ) -> Vec<bitboard::BitBoardMove> {
+        let mut line = vec![];
+        let mut is_or = true;
+        let mut remaining = remaining;
+        while game.end_game() == game_state::EndGame::None && remaining > 0 {
+            let mut best_opt: Option<(bitboard::BitBoardMove, u8)> = None;
+            for b_move in game.gen_moves() {
+                self.play(game, b_move);
+                let entry_opt = self
+                    .lookup(&game.last_hash(), remaining - 1)
+                    .filter(|entry| entry.pn == 0);
+                game.unmake_move();
+                if let Some(entry) = entry_opt {
+                    match best_opt {
+                        Some((_, mate_plies)) if is_or && entry.mate_plies >= mate_plies => {}
+                        Some((_, mate_plies)) if !is_or && entry.mate_plies <= mate_plies => {}
+                        _ => best_opt = Some((b_move, entry.mate_plies)),
+                    }
+                }
+            }
+            match best_opt {
+                Some((b_move, _)) => {
+                    self.play(game, b_move);
+                    line.push(b_move);
+                }
+                None => break,
+            }
+            is_or = !is_or;
+            remaining -= 1;
+        }
+        for _ in 0..line.len() {
+            game.unmake_move();
+        }
+        line
+    }
+}
+
+fn min_pn(entries: &[TtEntry]) -> u64 {
+    entries
+        .iter()
+        .map(|entry| entry.pn)
+        .min()
+        .unwrap_or(INFINITY)
+}
+fn min_dn(entries: &[TtEntry]) -> u64 {
+    entries
+        .iter()
+        .map(|entry| entry.dn)
+        .min()
+        .unwrap_or(INFINITY)
+}
+fn sum_pn(entries: &[TtEntry]) -> u64 {
+    entries
+        .iter()
+        .fold(0u64, |sum, entry| sum.saturating_add(entry.pn))
+        .min(INFINITY)
+}
+fn sum_dn(entries: &[TtEntry]) -> u64 {
+    entries
+        .iter()
+        .fold(0u64, |sum, entry| sum.saturating_add(entry.dn))
+        .min(INFINITY)
+}
+
+#[derive(Debug, Clone)]
+pub struct EnginePns {
+    id_number: String,
+    debug_actor_opt: Option<debug::DebugActor>,
+    zobrist_table: zobrist::Zobrist,
+    conf: config::PnsConfig,
+}
+impl EnginePns {
+    pub fn new(
+        debug_actor_opt: Option<debug::DebugActor>,
+        zobrist_table: zobrist::Zobrist,
+        conf: &config::PnsConfig,
+    ) -> Self {
+        assert!(conf.max_mate_moves >= 1);
+        Self {
+            id_number: "".to_string(),
+            debug_actor_opt,
+            zobrist_table,
+            conf: conf.clone(),
+        }
+    }
+    pub fn set_id_number(&mut self, id_number: &str) {
+        self.id_number = id_number.to_string();
+    }
+
+    // shortest mate in at most max_mate_moves moves for the player to move, with its mating line.
+    // The search is aborted by the stop flag or at the deadline.
+    pub fn solve(
+        &self,
+        game: &game_state::GameState,
+        max_mate_moves: u8,
+        is_stop: &Arc<AtomicBool>,
+        deadline_opt: Option<Instant>,
+    ) -> Option<score::BitboardMoveScoreMat> {
+        let span = span_debug();
+        let _enter = span.enter();
+
+        let mut game_clone = game.clone();
+        if game_clone.gen_moves().is_empty() {
+            return None;
+        }
+        let mut solver = Solver::new(
+            &self.zobrist_table,
+            self.conf.max_nodes,
+            is_stop,
+            deadline_opt,
+        );
+        // the table is kept between iterations: the proofs and disproofs stay valid
+        for mate_moves in 1..=max_mate_moves.min(config::MAX_PNS_MATE_MOVES) {
+            let remaining = 2 * mate_moves - 1;
+            let entry = solver.mid(&mut game_clone, true, remaining, INFINITY, INFINITY);
+            tracing::debug!(
+                "mate in {}: pn {} dn {} ({} nodes)",
+                mate_moves,
+                entry.pn,
+                entry.dn,
+                solver.n_nodes
+            );
+            if entry.pn == 0 {
+                let line = solver.mating_line(&mut game_clone, remaining);
+                let variant = line
+                    .iter()
+                    .map(|b_move| {
+                        long_notation::LongAlgebricNotationMove::build_from_b_move(*b_move).cast()
+                    })
+                    .collect::<Vec<String>>()
+                    .join(" ");
+                return Some(score::BitboardMoveScoreMat::new(
+                    line[0],
+                    entry.mate_plies.div_ceil(2),
+                    &variant,
+                ));
+            }
+            if solver.is_aborted() {
+                tracing::debug!("mate search aborted after {} nodes", solver.n_nodes);
+                break;
+            }
+        }
+        None
+    }
+}
+
+const PNS_ENGINE_ID_NAME: &str = "Pns engine";
+const PNS_ENGINE_ID_AUTHOR: &str = "Christophe le cam";
+
+impl logic::Engine for EnginePns {
+    fn id(&self) -> logic::EngineId {
+        let name = format!(
+            "{} max_mate_moves {} - {}",
+            PNS_ENGINE_ID_NAME.to_owned(),
+            self.conf.max_mate_moves,
+            self.id_number
+        )
+        .trim()
+        .to_string();
+        let author = PNS_ENGINE_ID_AUTHOR.to_owned();
+        logic::EngineId::new(name, author)
+    }
+    fn find_best_move(
+        &self,
+        self_actor: Addr<dispatcher::EngineDispatcher>,
+        stat_actor_opt: Option<stat_entity::StatActor>,
+        game: game_state::GameState,
+        time_manager_opt: Option<time_allocation::TimeManager>,
+        is_stop: &Arc<AtomicBool>,
+    ) {
+        let moves = logic::gen_moves(game.bit_position());
+        if !moves.is_empty() {
+            let deadline_opt = time_manager_opt
+                .as_ref()
+                .map(|time_manager| time_manager.hard_deadline());
+            let mat_move_opt = self.solve(&game, self.conf.max_mate_moves, is_stop, deadline_opt);
+            let best_move = match mat_move_opt {
+                Some(mat_move) => {
+                    tracing::info!("mate in {}: {}", mat_move.mat_in(), mat_move.variant());
+                    *mat_move.bitboard_move()
+                }
+                None => {
+                    tracing::warn!(
+                        "No mate found in {} moves: playing a fallback move",
+                        self.conf.max_mate_moves
+                    );
+                    logic::fallback_move(&game).expect("No legal move in the position")
+                }
+            };
+            self_actor.do_send(dispatcher::handler_engine::EngineStopThinking::new(
+                stat_actor_opt,
+            ));
+            let reply =
+                dispatcher::handler_engine::EngineEndOfAnalysis(best_move, game.last_hash());
+            if let Some(debug_actor) = &self.debug_actor_opt {
+                debug_actor.do_send(debug::AddMessage(format!(
+                    "Engine of id {:?} reply is: '{:?}'",
+                    self.id(),
+                    reply
+                )));
+            }
+            self_actor.do_send(reply);
+        } else {
+            // FIXME: Do nothing. The engine should be put asleep
+            panic!("To be implemented. When EndGame detected in game_manager, stop the engines")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::notation::fen::{self, EncodeUserInput};
+
+    fn solve(fen: &str, max_mate_moves: u8) -> Option<score::BitboardMoveScoreMat> {
+        let zobrist_table = zobrist::Zobrist::new();
+        let position = fen::Fen::decode(fen).expect("Failed to decode FEN");
+        let game = game_state::GameState::new(position, &zobrist_table);
+        let engine = EnginePns::new(None, zobrist_table, &config::PnsConfig::new(max_mate_moves));
+        engine.solve(
+            &game,
+            max_mate_moves,
+            &Arc::new(AtomicBool::new(false)),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_mate_in_one() {
+        let mat_move = solve("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 3).unwrap();
+        assert_eq!(mat_move.mat_in(), 1);
+        assert_eq!(mat_move.variant(), "a1a8");
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let zobrist_table = zobrist::Zobrist::new();
+        let position = fen::Fen::decode("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let game = game_state::GameState::new(position, &zobrist_table);
+        let engine = EnginePns::new(None, zobrist_table, &config::PnsConfig::new(1));
+        // the depth is capped: no overflow of the number of half moves
+        let mat_move = engine.solve(&game, u8::MAX, &Arc::new(AtomicBool::new(false)), None);
+        assert_eq!(mat_move.unwrap().mat_in(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_depth_beyond_limit() {
+        config::PnsConfig::new(config::MAX_PNS_MATE_MOVES + 1);
+    }
+
+    // the mating line is legal and ends with the mate
+    fn assert_mating_line(fen: &str, mat_move: &score::BitboardMoveScoreMat) {
+        let zobrist_table = zobrist::Zobrist::new();
+        let position = fen::Fen::decode(fen).unwrap();
+        let mut game = game_state::GameState::new(position, &zobrist_table);
+        let line: Vec<long_notation::LongAlgebricNotationMove> = mat_move
+            .variant()
+            .split(' ')
+            .map(|m| long_notation::LongAlgebricNotationMove::build_from_str(m).unwrap())
+            .collect();
+        assert_eq!(line.len(), 2 * mat_move.mat_in() as usize - 1);
+        game.play_moves(&line, &zobrist_table, None, true).unwrap();
+        game.update_endgame_status();
+        assert!(game.end_game().is_mat());
+    }
+
+    #[test]
+    fn test_quiet_mate_in_two() {
+        // Kf7 does not check: the mat solver only tries checks
+        let fen = "7k/8/5K2/8/8/8/8/6R1 w - - 0 1";
+        let mat_move = solve(fen, 3).unwrap();
+        assert_eq!(mat_move.mat_in(), 2);
+        assert_mating_line(fen, &mat_move);
+    }
+
+    #[test]
+    fn test_mate_in_three() {
+        let fen = "8/R7/4kPP1/3ppp2/3B1P2/1K1P1P2/8/8 w - - 0 1";
+        let mat_move = solve(fen, 4).unwrap();
+        assert_eq!(mat_move.mat_in(), 3);
+        assert_mating_line(fen, &mat_move);
+    }
+
+    #[test]
+    fn test_no_mate() {
+        // the white king is too far to mate in two
+        assert!(solve("4k3/8/8/8/8/8/8/4K2R w - - 0 1", 2).is_none());
+        // the starting position
+        assert!(solve(fen::FEN_START_POSITION, 1).is_none());
+    }
+
+    #[test]
+    fn test_node_budget() {
+        let zobrist_table = zobrist::Zobrist::new();
+        let position = fen::Fen::decode("7k/8/5K2/8/8/8/8/6R1 w - - 0 1").unwrap();
+        let game = game_state::GameState::new(position, &zobrist_table);
+        let mut conf = config::PnsConfig::new(2);
+        conf.max_nodes = 1;
+        let engine = EnginePns::new(None, zobrist_table, &conf);
+        assert!(engine
+            .solve(&game, 2, &Arc::new(AtomicBool::new(false)), None)
+            .is_none());
+    }
+
+    #[test]
+    fn test_stop_and_deadline() {
+        let zobrist_table = zobrist::Zobrist::new();
+        // mate in 2: not found when the search is stopped after the mate in 1 iteration
+        let position = fen::Fen::decode("7k/8/5K2/8/8/8/8/6R1 w - - 0 1").unwrap();
+        let game = game_state::GameState::new(position, &zobrist_table);
+        let engine = EnginePns::new(None, zobrist_table, &config::PnsConfig::new(2));
+        assert!(engine
+            .solve(&game, 2, &Arc::new(AtomicBool::new(true)), None)
+            .is_none());
+        assert!(engine
+            .solve(
+                &game,
+                2,
+                &Arc::new(AtomicBool::new(false)),
+                Some(Instant::now())
+            )
+            .is_none());
+        assert!(engine
+            .solve(&game, 2, &Arc::new(AtomicBool::new(false)), None)
+            .is_some());
+    }
+}
@@ -1,14 +1,19 @@
-use super::{engine_logic as logic, feature};
+use super::config::config;
+use super::{bitbase, engine_logic as logic, feature};
 use crate::entity::game::component::bitboard::piece_move::{self, table};
 use crate::entity::game::component::square::Switch;
 use crate::entity::game::component::{bitboard, game_state, square};
 use crate::entity::stat::actor::stat_entity;
 use crate::entity::stat::component::stat_data;
 
+pub mod endgame;
+pub mod eval_terms;
+pub mod evaluator;
+pub mod nn_evaluator;
 pub mod score;
 pub mod stat_eval;
 
-const FACTOR_PAWN_BASE: i32 = 1000;
+pub(crate) const FACTOR_PAWN_BASE: i32 = 1000;
 pub const HALF_PAWN: i32 = FACTOR_PAWN_BASE / 2;
 const FACTOR_CONTROL_SQUARES: i32 = 10;
 
@@ -46,12 +51,12 @@ pub fn handle_end_game_scenario(
     }
 }
 
-pub fn evaluate_position(
-    game: &game_state::GameState,
+// count evaluated positions and send statistics from time to time
+pub fn update_stat_eval(
     stat_eval: &mut stat_eval::StatEval,
     stat_actor_opt: &Option<stat_entity::StatActor>,
     engine_id: logic::EngineId,
-) -> i32 {
+) {
     if stat_eval.inc_n_positions_evaluated() % stat_data::SEND_STAT_EVERY_N_POSITION_EVALUATED == 0
     {
         if let Some(stat_actor) = stat_actor_opt {
@@ -63,6 +68,44 @@ pub fn evaluate_position(
         }
         stat_eval.reset_n_positions_evaluated();
     }
+}
+
+pub fn evaluate_position(
+    game: &game_state::GameState,
+    stat_eval: &mut stat_eval::StatEval,
+    stat_actor_opt: &Option<stat_entity::StatActor>,
+    engine_id: logic::EngineId,
+) -> i32 {
+    evaluate_position_with_conf(
+        game,
+        stat_eval,
+        stat_actor_opt,
+        engine_id,
+        &config::EvalConf::default(),
+    )
+}
+
+pub fn evaluate_position_with_conf(
+    game: &game_state::GameState,
+    stat_eval: &mut stat_eval::StatEval,
+    stat_actor_opt: &Option<stat_entity::StatActor>,
+    engine_id: logic::EngineId,
+    conf: &config::EvalConf,
+) -> i32 {
+    update_stat_eval(stat_eval, stat_actor_opt, engine_id);
+    // specific endgame knowledge is consulted before the general evaluation
+    let endgame_scale = if conf.f_endgame {
+        if let Some(value) = evaluate_bitbase(game.bit_position()) {
+            return value;
+        }
+        match endgame::registry().probe(game.bit_position()) {
+            Some(endgame::EndgameResult::Value(value)) => return value,
+            Some(endgame::EndgameResult::Scale(scale)) => scale,
+            None => endgame::SCALE_NORMAL,
+        }
+    } else {
+        endgame::SCALE_NORMAL
+    };
     // check if can win or insufficient material
     let player_turn = game.bit_position().bit_position_status().player_turn();
     let (player_can_win, player_opponent_can_win) = if feature::FEATURE_CANNOT_WIN_FORCE_NULL {
@@ -81,8 +124,19 @@ pub fn evaluate_position(
         (true, true)
     };
     let is_start_game = game.bit_position().bit_position_status().n_half_moves() <= 20;
-    let default_score = evaluate_static_position(game.bit_position())
-        + evaluate_dynamic_position(game.gen_control_square(), is_start_game);
+    let control_squares = game.gen_control_square();
+    let score_terms = eval_terms::evaluate_terms(game.bit_position(), &control_squares, conf);
+    // mobility replaces the uniform count of controlled squares
+    let score_control_squares = if conf.f_mobility {
+        0
+    } else {
+        evaluate_dynamic_position(control_squares, is_start_game)
+    };
+    let default_score = (evaluate_static_position(game.bit_position())
+        + score_control_squares
+        + score_terms)
+        * endgame_scale
+        / endgame::SCALE_NORMAL;
     let bonus = if player_turn == square::Color::White {
         100000
     } else {
@@ -100,6 +154,24 @@ pub fn evaluate_position(
     }
 }
 
+// exact result from the bitbases. A won position keeps the endgame heuristic (if any)
+// so that the search makes progress.
+fn evaluate_bitbase(bit_position: &bitboard::BitPosition) -> Option<i32> {
+    let white_to_move = bit_position.bit_position_status().player_turn() == square::Color::White;
+    let white_wins = match bitbase::probe(bit_position)? {
+        bitbase::Wdl::Draw => return Some(0),
+        bitbase::Wdl::Win => white_to_move,
+        bitbase::Wdl::Loss => !white_to_move,
+    };
+    let value = match endgame::registry().probe(bit_position) {
+        Some(endgame::EndgameResult::Value(value)) if value.abs() >= endgame::KNOWN_WIN => {
+            value.abs()
+        }
+        _ => endgame::KNOWN_WIN + evaluate_static_position(bit_position).abs(),
+    };
+    Some(if white_wins { value } else { -value })
+}
+
 pub fn is_final(game: &game_state::GameState) -> bool {
     let b_white_black = game.bit_position().bit_boards_white_and_black();
     let (n_rooks_w, n_knights_w, n_bishops_w, n_queens_w, n_pawns_w) =
@@ -0,0 +1,397 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use super::FACTOR_PAWN_BASE;
+use crate::entity::game::component::bitboard;
+use crate::entity::game::component::bitboard::piece_move::table;
+use crate::entity::game::component::square::{self, Switch};
+
+// value of a position known to be won (below mat scores)
+pub const KNOWN_WIN: i32 = 100_000;
+// scale factors are in percent
+pub const SCALE_NORMAL: i32 = 100;
+const SCALE_OPPOSITE_BISHOPS: i32 = 50;
+const SCALE_OPPOSITE_BISHOPS_FEW_PAWNS: i32 = 25;
+const LIGHT_SQUARES: u64 = 0x55AA_55AA_55AA_55AA;
+const MAX_PAWNS: usize = 8;
+
+// Material of one side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Material {
+    pub queens: u32,
+    pub rooks: u32,
+    pub bishops: u32,
+    pub knights: u32,
+    pub pawns: u32,
+}
+impl Material {
+    pub fn from(bit_board: &bitboard::BitBoards) -> Self {
+        Self {
+            queens: bit_board.queens().bitboard().count_ones(),
+            rooks: bit_board.rooks().bitboard().count_ones(),
+            bishops: bit_board.bishops().bitboard().count_ones(),
+            knights: bit_board.knights().bitboard().count_ones(),
+            pawns: bit_board.pawns().bitboard().count_ones(),
+        }
+    }
+    // "KQRBNP" order, e.g. "KBN"
+    pub fn code(&self) -> String {
+        let mut code = "K".to_string();
+        for (piece, n) in [
+            ('Q', self.queens),
+            ('R', self.rooks),
+            ('B', self.bishops),
+            ('N', self.knights),
+            ('P', self.pawns),
+        ] {
+            (0..n).for_each(|_| code.push(piece));
+        }
+        code
+    }
+}
+
+// Material signature: the strong side is written first, e.g. "KBNK"
+pub fn signature(strong: &Material, weak: &Material) -> String {
+    format!("{}{}", strong.code(), weak.code())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EndgameResult {
+    // score from white perspective, replaces the general evaluation
+    Value(i32),
+    // the general evaluation has to be scaled (percent)
+    Scale(i32),
+}
+
+// Context given to an endgame evaluator
+pub struct EndgameInfo<'a> {
+    pub bit_boards: &'a bitboard::BitBoardsWhiteAndBlack,
+    pub strong: square::Color,
+    pub player_turn: square::Color,
+}
+impl EndgameInfo<'_> {
+    fn strong(&self) -> &bitboard::BitBoards {
+        self.bit_boards.bit_board(&self.strong)
+    }
+    fn weak(&self) -> &bitboard::BitBoards {
+        self.bit_boards.bit_board(&self.strong.switch())
+    }
+}
+
+// An evaluator returns a score from the strong side perspective or a scale factor.
+// None means no knowledge: the general evaluation is used.
+type EndgameFn = fn(&EndgameInfo) -> Option<EndgameResultStrong>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EndgameResultStrong {
+    Value(i32),
+    Scale(i32),
+}
+
+pub struct EndgameRegistry {
+    evaluators: HashMap<String, EndgameFn>,
+}
+impl EndgameRegistry {
+    pub fn new() -> Self {
+        Self {
+            evaluators: HashMap::new(),
+        }
+    }
+    pub fn register(&mut self, signature: &str, evaluator: EndgameFn) {
+        self.evaluators.insert(signature.to_string(), evaluator);
+    }
+    pub fn contains(&self, signature: &str) -> bool {
+        self.evaluators.contains_key(signature)
+    }
+    // default knowledge
+    pub fn build_default() -> Self {
+        let mut registry = Self::new();
+        registry.register("KPK", eval_kpk);
+        registry.register("KBNK", eval_kbnk);
+        registry.register("KRK", eval_mop_up);
+        registry.register("KQK", eval_mop_up);
+        for n_pawns in 1..=MAX_PAWNS {
+            registry.register(&format!("KB{}K", "P".repeat(n_pawns)), eval_wrong_bishop);
+        }
+        for n_pawns_strong in 0..=MAX_PAWNS {
+            for n_pawns_weak in 0..=MAX_PAWNS {
+                registry.register(
+                    &format!(
+                        "KB{}KB{}",
+                        "P".repeat(n_pawns_strong),
+                        "P".repeat(n_pawns_weak)
+                    ),
+                    eval_opposite_bishops,
+                );
+            }
+        }
+        registry
+    }
+
+    pub fn probe(
+        &self,
+        bit_position: &bitboard::BitPosition,
+    ) -> Option<EndgameResult> {
+        let bit_boards = bit_position.bit_boards_white_and_black();
+        let player_turn = bit_position.bit_position_status().player_turn();
+        let material_white = Material::from(bit_boards.bit_board_white());
+        let material_black = Material::from(bit_boards.bit_board_black());
+        for (strong, signature) in [
+            (
+                square::Color::White,
+                signature(&material_white, &material_black),
+            ),
+            (
+                square::Color::Black,
+                signature(&material_black, &material_white),
+            ),
+        ] {
+            if let Some(evaluator) = self.evaluators.get(&signature) {
+                let info = EndgameInfo {
+                    bit_boards,
+                    strong,
+                    player_turn,
+                };
+                let sign = if strong == square::Color::White { 1 } else { -1 };
+                return evaluator(&info).map(|result| match result {
+                    EndgameResultStrong::Value(value) => EndgameResult::Value(sign * value),
+                    EndgameResultStrong::Scale(scale) => EndgameResult::Scale(scale),
+                });
+            }
+        }
+        None
+    }
+}
+impl Default for EndgameRegistry {
+    fn default() -> Self {
+        Self::build_default()
+    }
+}
+
+pub fn registry() -> &'static EndgameRegistry {
+    static REGISTRY: OnceLock<EndgameRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(EndgameRegistry::build_default)
+}
+
+fn distance(a: bitboard::BitIndex, b: bitboard::BitIndex) -> i32 {
+    let d_row = (a.row() as i32 - b.row() as i32).abs();
+    let d_col = (a.col() as i32 - b.col() as i32).abs();
+    d_row.max(d_col)
+}
+// 0 in the center, 6 in a corner
+fn distance_to_center(a: bitboard::BitIndex) -> i32 {
+    let row = a.row() as i32;
+    let col = a.col() as i32;
+    (3 - row).max(row - 4) + (3 - col).max(col - 4)
+}
+fn is_light_square(index: bitboard::BitIndex) -> bool {
+    LIGHT_SQUARES & index.bitboard().value() != 0
+}
+fn king_index(bit_board: &bitboard::BitBoards) -> bitboard::BitIndex {
+    bit_board.king().bitboard().index()
+}
+
+// KRK, KQK: push the weak king to the edge and bring the strong king closer
+fn eval_mop_up(info: &EndgameInfo) -> Option<EndgameResultStrong> {
+    let king_strong = king_index(info.strong());
+    let king_weak = king_index(info.weak());
+    let material = Material::from(info.strong());
+    let value = KNOWN_WIN
+        + (material.queens as i32 * 10 + material.rooks as i32 * 5) * FACTOR_PAWN_BASE
+        + distance_to_center(king_weak) * 100
+        + (7 - distance(king_strong, king_weak)) * 50;
+    Some(EndgameResultStrong::Value(value))
+}
+
+// KBNK: drive the weak king to a corner of the bishop color
+fn eval_kbnk(info: &EndgameInfo) -> Option<EndgameResultStrong> {
+    let king_strong = king_index(info.strong());
+    let king_weak = king_index(info.weak());
+    let bishop = info.strong().bishops().bitboard().index();
+    let corners: [u8; 2] = if is_light_square(bishop) {
+        // h1, a8
+        [7, 56]
+    } else {
+        // a1, h8
+        [0, 63]
+    };
+    let distance_corner = corners
+        .iter()
+        .map(|corner| distance(king_weak, bitboard::BitIndex::new(*corner)))
+        .min()
+        .unwrap();
+    let value = KNOWN_WIN + 6 * FACTOR_PAWN_BASE + (7 - distance_corner) * 200
+        + (7 - distance(king_strong, king_weak)) * 50;
+    Some(EndgameResultStrong::Value(value))
+}
+
+// KPK without bitbase: rule of the square and key squares.
+// Return None if no conclusion.
+fn eval_kpk(info: &EndgameInfo) -> Option<EndgameResultStrong> {
+    let king_strong = king_index(info.strong());
+    let king_weak = king_index(info.weak());
+    let pawn = info.strong().pawns().bitboard().index();
+    let (promotion_row, pawn_row_relative) = match info.strong {
+        square::Color::White => (7u8, pawn.row()),
+        square::Color::Black => (0u8, 7 - pawn.row()),
+    };
+    let promotion = bitboard::BitIndex::new(promotion_row * 8 + pawn.col());
+    let value_win =
+        |bonus: i32| Some(EndgameResultStrong::Value(KNOWN_WIN + FACTOR_PAWN_BASE + bonus));
+    let is_rook_pawn = pawn.col() == 0 || pawn.col() == 7;
+    // the weak king controls the promotion corner of a rook pawn
+    if is_rook_pawn && distance(king_weak, promotion) <= 1 {
+        return Some(EndgameResultStrong::Value(0));
+    }
+    // rule of the square (the strong king does not block its pawn)
+    let n_moves_to_promote = (7 - pawn_row_relative as i32).min(5);
+    let weak_to_play = if info.player_turn == info.strong { 0 } else { 1 };
+    let is_king_in_front = king_strong.col() == pawn.col()
+        && distance(king_strong, promotion) < distance(pawn, promotion);
+    if !is_king_in_front && distance(king_weak, promotion) - weak_to_play > n_moves_to_promote {
+        return value_win(pawn_row_relative as i32 * 100);
+    }
+    // key squares: two rows in front of the pawn (from row 5 one row is enough)
+    if !is_rook_pawn {
+        let rows_ahead: Vec<i32> = if pawn_row_relative >= 4 {
+            vec![1, 2]
+        } else {
+            vec![2]
+        };
+        let sign = if info.strong == square::Color::White { 1 } else { -1 };
+        let is_on_key_square = rows_ahead.iter().any(|d| {
+            let row = pawn.row() as i32 + sign * d;
+            (0..8).contains(&row)
+                && king_strong.row() as i32 == row
+                && (king_strong.col() as i32 - pawn.col() as i32).abs() <= 1
+        });
+        let weak_is_far = distance(king_weak, pawn) > 1 || info.player_turn == info.strong;
+        if is_on_key_square && weak_is_far {
+            return value_win(pawn_row_relative as i32 * 100);
+        }
+    }
+    None
+}
+
+// Bishop and rook pawns: draw if the bishop does not control the promotion square
+// and the weak king is in the corner
+fn eval_wrong_bishop(info: &EndgameInfo) -> Option<EndgameResultStrong> {
+    let pawns = info.strong().pawns().bitboard().value();
+    let col = if pawns & table::MASK_COL_A == pawns {
+        0
+    } else if pawns & table::MASK_COL_H == pawns {
+        7
+    } else {
+        return None;
+    };
+    let promotion_row = match info.strong {
+        square::Color::White => 7,
+        square::Color::Black => 0,
+    };
+    let promotion = bitboard::BitIndex::new(promotion_row * 8 + col);
+    let bishop = info.strong().bishops().bitboard().index();
+    if is_light_square(bishop) == is_light_square(promotion) {
+        return None;
+    }
+    if distance(king_index(info.weak()), promotion) <= 1 {
+        Some(EndgameResultStrong::Value(0))
+    } else {
+        None
+    }
+}
+
+// Opposite colored bishops (bishops and pawns only): drawish
+fn eval_opposite_bishops(info: &EndgameInfo) -> Option<EndgameResultStrong> {
+    let bishop_strong = info.strong().bishops().bitboard().index();
+    let bishop_weak = info.weak().bishops().bitboard().index();
+    if is_light_square(bishop_strong) == is_light_square(bishop_weak) {
+        return None;
+    }
+    let pawns_strong = info.strong().pawns().bitboard().count_ones() as i32;
+    let pawns_weak = info.weak().pawns().bitboard().count_ones() as i32;
+    if (pawns_strong - pawns_weak).abs() <= 1 {
+        Some(EndgameResultStrong::Scale(SCALE_OPPOSITE_BISHOPS_FEW_PAWNS))
+    } else {
+        Some(EndgameResultStrong::Scale(SCALE_OPPOSITE_BISHOPS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::game::component::bitboard::zobrist;
+    use crate::entity::game::component::game_state;
+    use crate::ui::notation::fen::{self, EncodeUserInput};
+
+    fn probe(fen_str: &str) -> Option<EndgameResult> {
+        let position = fen::Fen::decode(fen_str).expect("Failed to decode FEN");
+        let game = game_state::GameState::new(position, &zobrist::Zobrist::new());
+        registry().probe(game.bit_position())
+    }
+    fn value(fen_str: &str) -> i32 {
+        match probe(fen_str) {
+            Some(EndgameResult::Value(v)) => v,
+            other => panic!("value expected for {}, found {:?}", fen_str, other),
+        }
+    }
+
+    #[test]
+    fn test_signature() {
+        let strong = Material {
+            bishops: 1,
+            knights: 1,
+            ..Default::default()
+        };
+        assert_eq!(signature(&strong, &Material::default()), "KBNK");
+        assert!(registry().contains("KBPPKBP"));
+        assert!(probe(fen::FEN_START_POSITION).is_none());
+    }
+
+    #[test]
+    fn test_mop_up_edge_is_better() {
+        let center = value("8/8/8/3k4/8/8/8/R3K3 w - - 0 1");
+        let edge = value("3k4/8/8/8/8/8/8/R3K3 w - - 0 1");
+        assert!(center > KNOWN_WIN);
+        assert!(edge > center);
+        // black is the strong side
+        assert!(value("r3k3/8/8/8/8/8/8/3K4 w - - 0 1") < -KNOWN_WIN);
+    }
+
+    #[test]
+    fn test_kbnk_corner_of_bishop_color() {
+        // light squared bishop (f1): a8 and h1 are the mating corners
+        let good_corner = value("k7/8/1K6/8/8/8/8/4NB2 w - - 0 1");
+        let bad_corner = value("7k/8/6K1/8/8/8/8/4NB2 w - - 0 1");
+        assert!(good_corner > bad_corner);
+    }
+
+    #[test]
+    fn test_kpk() {
+        // pawn outside the square of the black king
+        assert!(value("8/8/8/8/P7/8/8/4K2k w - - 0 1") > KNOWN_WIN);
+        // rook pawn with the defending king in the corner
+        assert_eq!(value("k7/8/8/P7/8/8/8/4K3 w - - 0 1"), 0);
+        // white king on a key square
+        assert!(value("8/3k4/8/3K4/8/3P4/8/8 w - - 0 1") > KNOWN_WIN);
+        // black king catches the pawn: no conclusion
+        assert_eq!(probe("4k3/8/8/8/8/3P4/8/4K3 w - - 0 1"), None);
+    }
+
+    #[test]
+    fn test_wrong_bishop() {
+        // light squared bishop (f1) cannot control h8
+        assert_eq!(value("7k/8/8/7P/8/8/8/4KB2 w - - 0 1"), 0);
+        // dark squared bishop (c1) controls h8
+        assert_eq!(probe("7k/8/8/7P/8/8/8/2B1K3 w - - 0 1"), None);
+    }
+
+    #[test]
+    fn test_opposite_bishops() {
+        assert_eq!(
+            probe("4k3/5p2/8/8/2b5/8/3PP3/2B1K3 w - - 0 1"),
+            Some(EndgameResult::Scale(SCALE_OPPOSITE_BISHOPS_FEW_PAWNS))
+        );
+        // same colored bishops
+        assert_eq!(probe("4k3/5p2/8/8/3b4/8/3PP3/2B1K3 w - - 0 1"), None);
+    }
+}
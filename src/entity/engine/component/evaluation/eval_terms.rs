@@ -0,0 +1,293 @@
+use crate::entity::engine::component::config::config;
+use crate::entity::game::component::bitboard::piece_move::{self, table};
+use crate::entity::game::component::bitboard;
+use crate::entity::game::component::square::{self, Switch, TypePiece};
+
+// mobility bonus (centipawns) indexed by the number of safe squares
+const MOBILITY_KNIGHT: [i32; 9] = [-62, -53, -12, -4, 3, 13, 22, 28, 33];
+const MOBILITY_BISHOP: [i32; 14] = [-48, -20, 16, 26, 38, 51, 55, 63, 63, 68, 81, 81, 91, 98];
+const MOBILITY_ROOK: [i32; 15] = [-60, -20, 2, 3, 3, 11, 22, 31, 40, 40, 41, 48, 57, 57, 62];
+const MOBILITY_QUEEN: [i32; 28] = [
+    -30, -12, -8, -9, 20, 23, 23, 35, 38, 53, 64, 65, 65, 66, 67, 67, 72, 72, 77, 79, 93, 108,
+    108, 108, 110, 114, 114, 116,
+];
+// centipawn to evaluation unit
+const FACTOR_CENTIPAWN: i32 = 10;
+const LIGHT_SQUARES: u64 = 0x55AA_55AA_55AA_55AA;
+
+fn mask_row(row: u8) -> u64 {
+    table::MASK_ROW_1 << (8 * row)
+}
+fn mask_col(col: u8) -> u64 {
+    table::MASK_COL_A << col
+}
+// row seen from the side of color (0 is the first row of the player)
+fn relative_row(color: square::Color, row: u8) -> u8 {
+    match color {
+        square::Color::White => row,
+        square::Color::Black => 7 - row,
+    }
+}
+
+// evaluate all the switched on terms from white perspective
+pub fn evaluate_terms(
+    bit_position: &bitboard::BitPosition,
+    control_squares: &(piece_move::ControlSquares, piece_move::ControlSquares),
+    conf: &config::EvalConf,
+) -> i32 {
+    let (control_white, control_black) = control_squares;
+    let bit_boards = bit_position.bit_boards_white_and_black();
+    let score_white = evaluate_terms_one_side(
+        square::Color::White,
+        bit_boards,
+        control_white,
+        control_black,
+        conf,
+    );
+    let score_black = evaluate_terms_one_side(
+        square::Color::Black,
+        bit_boards,
+        control_black,
+        control_white,
+        conf,
+    );
+    score_white - score_black
+}
+
+fn evaluate_terms_one_side(
+    color: square::Color,
+    bit_boards: &bitboard::BitBoardsWhiteAndBlack,
+    control_squares: &piece_move::ControlSquares,
+    control_squares_opponent: &piece_move::ControlSquares,
+    conf: &config::EvalConf,
+) -> i32 {
+    let bit_board = bit_boards.bit_board(&color);
+    let bit_board_opponent = bit_boards.bit_board(&color.switch());
+    let mut score = 0;
+    if conf.f_mobility {
+        score += mobility(bit_board, control_squares, control_squares_opponent) * conf.w_mobility
+            / 100;
+    }
+    if conf.f_bishop_pair && bit_board.bishops().bitboard().count_ones() >= 2 {
+        score += conf.w_bishop_pair;
+    }
+    if conf.f_rook_open_file {
+        let (n_open, n_semi_open) = rooks_on_open_files(bit_board, bit_board_opponent);
+        score += n_open * conf.w_rook_open_file + n_semi_open * conf.w_rook_semi_open_file;
+    }
+    if conf.f_rook_7th_rank {
+        score += rooks_on_7th_rank(color, bit_board, bit_board_opponent) * conf.w_rook_7th_rank;
+    }
+    if conf.f_knight_outpost {
+        score += knight_outposts(color, bit_board, bit_board_opponent, control_squares)
+            * conf.w_knight_outpost;
+    }
+    if conf.f_bad_bishop {
+        score -= bad_bishop_pawns(bit_board) * conf.w_bad_bishop;
+    }
+    score
+}
+
+// Mobility: squares reachable by each piece except own pieces and squares attacked by enemy pawns
+fn mobility(
+    bit_board: &bitboard::BitBoards,
+    control_squares: &piece_move::ControlSquares,
+    control_squares_opponent: &piece_move::ControlSquares,
+) -> i32 {
+    let unsafe_squares = control_squares_opponent.panws_control() | bit_board.concat_bit_boards();
+    let mut score = 0;
+    for (type_piece, mobility_table) in [
+        (TypePiece::Knight, &MOBILITY_KNIGHT[..]),
+        (TypePiece::Bishop, &MOBILITY_BISHOP[..]),
+        (TypePiece::Rook, &MOBILITY_ROOK[..]),
+        (TypePiece::Queen, &MOBILITY_QUEEN[..]),
+    ] {
+        let mut n_pieces_with_moves = 0;
+        for piece_moves in control_squares
+            .moves()
+            .iter()
+            .filter(|piece_moves| piece_moves.type_piece() == type_piece)
+        {
+            let n_squares = (*piece_moves.moves() & !unsafe_squares).count_ones() as usize;
+            score += mobility_table[n_squares.min(mobility_table.len() - 1)];
+            n_pieces_with_moves += 1;
+        }
+        // pieces without any move are not listed in control squares
+        let n_pieces = bit_board.get_bitboard(type_piece).count_ones() as i32;
+        score += (n_pieces - n_pieces_with_moves) * mobility_table[0];
+    }
+    score * FACTOR_CENTIPAWN
+}
+
+// return (number of rooks on open files, number of rooks on semi-open files)
+fn rooks_on_open_files(
+    bit_board: &bitboard::BitBoards,
+    bit_board_opponent: &bitboard::BitBoards,
+) -> (i32, i32) {
+    let pawns = bit_board.pawns().bitboard().value();
+    let pawns_opponent = bit_board_opponent.pawns().bitboard().value();
+    let mut n_open = 0;
+    let mut n_semi_open = 0;
+    for rook in bit_board.rooks().bitboard().iter() {
+        let col = mask_col(rook.col());
+        match (pawns & col == 0, pawns_opponent & col == 0) {
+            (true, true) => n_open += 1,
+            (true, false) => n_semi_open += 1,
+            _ => {}
+        }
+    }
+    (n_open, n_semi_open)
+}
+
+// rooks on the 7th rank when the enemy king is on the last rank or enemy pawns are on the 7th rank
+fn rooks_on_7th_rank(
+    color: square::Color,
+    bit_board: &bitboard::BitBoards,
+    bit_board_opponent: &bitboard::BitBoards,
+) -> i32 {
+    let row_7th = mask_row(relative_row(color, 6));
+    let row_8th = mask_row(relative_row(color, 7));
+    let is_target = bit_board_opponent.pawns().bitboard().value() & row_7th != 0
+        || bit_board_opponent.king().bitboard().value() & row_8th != 0;
+    if is_target {
+        (bit_board.rooks().bitboard().value() & row_7th).count_ones() as i32
+    } else {
+        0
+    }
+}
+
+// knights on rows 4-6 (relative), protected by a pawn and that cannot be attacked by an enemy pawn
+fn knight_outposts(
+    color: square::Color,
+    bit_board: &bitboard::BitBoards,
+    bit_board_opponent: &bitboard::BitBoards,
+    control_squares: &piece_move::ControlSquares,
+) -> i32 {
+    let pawns_control = control_squares.panws_control().value();
+    let pawns_opponent = bit_board_opponent.pawns().bitboard().value();
+    let mut n_outposts = 0;
+    for knight in bit_board.knights().bitboard().iter() {
+        let row = relative_row(color, knight.row());
+        if !(3..=5).contains(&row) || pawns_control & knight.bitboard().value() == 0 {
+            continue;
+        }
+        let col = knight.col();
+        let mut cols_adjacent = 0u64;
+        if col > 0 {
+            cols_adjacent |= mask_col(col - 1);
+        }
+        if col < 7 {
+            cols_adjacent |= mask_col(col + 1);
+        }
+        let rows_ahead: u64 = ((row + 1)..8)
+            .map(|r| mask_row(relative_row(color, r)))
+            .fold(0, |acc, mask| acc | mask);
+        if pawns_opponent & cols_adjacent & rows_ahead == 0 {
+            n_outposts += 1;
+        }
+    }
+    n_outposts
+}
+
+// number of own pawns on the squares of the same color as each bishop
+fn bad_bishop_pawns(bit_board: &bitboard::BitBoards) -> i32 {
+    let pawns = bit_board.pawns().bitboard().value();
+    bit_board
+        .bishops()
+        .bitboard()
+        .iter()
+        .map(|bishop| {
+            let squares = if bishop.bitboard().value() & LIGHT_SQUARES != 0 {
+                LIGHT_SQUARES
+            } else {
+                !LIGHT_SQUARES
+            };
+            (pawns & squares).count_ones() as i32
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::game::component::bitboard::zobrist;
+    use crate::entity::game::component::game_state;
+    use crate::ui::notation::fen::{self, EncodeUserInput};
+
+    fn terms(fen_str: &str, conf: &config::EvalConf) -> i32 {
+        let position = fen::Fen::decode(fen_str).expect("Failed to decode FEN");
+        let game = game_state::GameState::new(position, &zobrist::Zobrist::new());
+        evaluate_terms(game.bit_position(), &game.gen_control_square(), conf)
+    }
+    fn only(term: &str) -> config::EvalConf {
+        let mut conf = config::EvalConf::none();
+        conf.set_term(term, true).unwrap();
+        conf
+    }
+
+    #[test]
+    fn test_terms_symmetric_start_position() {
+        assert_eq!(terms(fen::FEN_START_POSITION, &config::EvalConf::default()), 0);
+        assert_eq!(terms(fen::FEN_START_POSITION, &config::EvalConf::none()), 0);
+    }
+
+    #[test]
+    fn test_bishop_pair() {
+        let conf = only("bishop_pair");
+        assert_eq!(terms("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1", &conf), conf.w_bishop_pair);
+        assert_eq!(terms("2b1k3/8/8/8/8/8/8/2B1KB2 w - - 0 1", &conf), conf.w_bishop_pair);
+        assert_eq!(terms("2b1kb2/8/8/8/8/8/8/2B1KB2 w - - 0 1", &conf), 0);
+    }
+
+    #[test]
+    fn test_rook_files() {
+        let conf = only("rook_open_file");
+        // open file
+        assert_eq!(terms("4k3/p7/8/8/8/8/P7/3RK3 w - - 0 1", &conf), conf.w_rook_open_file);
+        // semi-open file
+        assert_eq!(
+            terms("4k3/3p4/8/8/8/8/8/3RK3 w - - 0 1", &conf),
+            conf.w_rook_semi_open_file
+        );
+        // closed file
+        assert_eq!(terms("4k3/3p4/8/8/8/8/3P4/3RK3 w - - 0 1", &conf), 0);
+    }
+
+    #[test]
+    fn test_rook_7th_rank() {
+        let conf = only("rook_7th_rank");
+        assert_eq!(terms("4k3/R7/8/8/8/8/8/4K3 w - - 0 1", &conf), conf.w_rook_7th_rank);
+        // enemy king not on the last rank and no pawn on the 7th rank
+        assert_eq!(terms("8/R7/4k3/8/8/8/8/4K3 w - - 0 1", &conf), 0);
+        // black rook on the 2nd rank
+        assert_eq!(terms("4k3/8/8/8/8/8/r7/4K3 w - - 0 1", &conf), -conf.w_rook_7th_rank);
+    }
+
+    #[test]
+    fn test_knight_outpost() {
+        let conf = only("knight_outpost");
+        // knight in d5 protected by e4, no black pawn on c/e files ahead
+        assert_eq!(terms("4k3/8/8/3N4/4P3/8/8/4K3 w - - 0 1", &conf), conf.w_knight_outpost);
+        // black pawn in c7 can attack d6 then d5
+        assert_eq!(terms("4k3/2p5/8/3N4/4P3/8/8/4K3 w - - 0 1", &conf), 0);
+        // not protected
+        assert_eq!(terms("4k3/8/8/3N4/8/8/8/4K3 w - - 0 1", &conf), 0);
+    }
+
+    #[test]
+    fn test_bad_bishop() {
+        let conf = only("bad_bishop");
+        // light squared bishop f1 with pawns on light squares d3 and e4 (b2 is dark)
+        assert_eq!(terms("4k3/8/8/8/4P3/3P4/1P6/4KB2 w - - 0 1", &conf), -2 * conf.w_bad_bishop);
+    }
+
+    #[test]
+    fn test_mobility_excludes_enemy_pawn_attacks() {
+        let conf = only("mobility");
+        let free = terms("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1", &conf);
+        assert_eq!(free, MOBILITY_KNIGHT[8] * FACTOR_CENTIPAWN);
+        // black pawn in d7 attacks c6 and e6
+        let restricted = terms("4k3/3p4/8/8/3N4/8/8/4K3 w - - 0 1", &conf);
+        assert_eq!(restricted, MOBILITY_KNIGHT[6] * FACTOR_CENTIPAWN);
+    }
+}
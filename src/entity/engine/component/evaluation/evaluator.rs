@@ -0,0 +1,70 @@
+use std::fmt;
+
+use super::stat_eval;
+use crate::entity::engine::component::config::config;
+use crate::entity::engine::component::engine_logic as logic;
+use crate::entity::engine::component::evaluation;
+use crate::entity::game::component::{bitboard, game_state};
+use crate::entity::stat::actor::stat_entity;
+
+// An evaluator scores a position from white perspective.
+// Engines receive an evaluator instead of calling evaluate_position directly.
+// The search notifies the evaluator of each move played / played back so that
+// an evaluator can maintain an incremental state (e.g. an accumulator). The state lives in
+// StatEval since it is specific to one search.
+pub trait Evaluator: fmt::Debug + Send + Sync {
+    fn name(&self) -> String;
+    fn evaluate(
+        &self,
+        game: &game_state::GameState,
+        stat_eval: &mut stat_eval::StatEval,
+        stat_actor_opt: &Option<stat_entity::StatActor>,
+        engine_id: logic::EngineId,
+    ) -> i32;
+    // called once with the root position before the search starts
+    fn init(&self, _game: &game_state::GameState, _stat_eval: &mut stat_eval::StatEval) {}
+    // called after b_move has been played
+    fn make_move(&self, _b_move: &bitboard::BitBoardMove, _stat_eval: &mut stat_eval::StatEval) {}
+    // called after the last move has been played back
+    fn unmake_move(&self, _stat_eval: &mut stat_eval::StatEval) {}
+}
+
+// Hand written evaluation (material, mobility and piece specific terms)
+#[derive(Debug, Clone)]
+pub struct ClassicEvaluator {
+    conf: config::EvalConf,
+}
+impl ClassicEvaluator {
+    pub fn new(conf: config::EvalConf) -> Self {
+        Self { conf }
+    }
+    pub fn conf(&self) -> &config::EvalConf {
+        &self.conf
+    }
+}
+impl Default for ClassicEvaluator {
+    fn default() -> Self {
+        Self::new(config::EvalConf::default())
+    }
+}
+
+impl Evaluator for ClassicEvaluator {
+    fn name(&self) -> String {
+        "classic".to_string()
+    }
+    fn evaluate(
+        &self,
+        game: &game_state::GameState,
+        stat_eval: &mut stat_eval::StatEval,
+        stat_actor_opt: &Option<stat_entity::StatActor>,
+        engine_id: logic::EngineId,
+    ) -> i32 {
+        evaluation::evaluate_position_with_conf(
+            game,
+            stat_eval,
+            stat_actor_opt,
+            engine_id,
+            &self.conf,
+        )
+    }
+}
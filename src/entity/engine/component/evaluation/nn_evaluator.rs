@@ -0,0 +1,403 @@
+use std::fs;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use super::evaluator::Evaluator;
+use super::stat_eval;
+use crate::entity::engine::component::engine_logic as logic;
+use crate::entity::engine::component::evaluation;
+use crate::entity::game::component::bitboard;
+use crate::entity::game::component::game_state;
+use crate::entity::game::component::square::{self, Switch, TypePiece};
+use crate::entity::stat::actor::stat_entity;
+
+// 2 colors x 6 pieces x 64 squares
+pub const N_INPUTS: usize = 768;
+const NN_HEADER: &str = "chess_rust-nn-v1";
+// default scale: network output 1.0 is one pawn
+const DEFAULT_SCALE: f32 = 1000.0;
+
+// Weights file (text):
+//   chess_rust-nn-v1
+//   layers 768 <hidden1> [<hidden2>] 1
+//   scale <f32>
+//   <all the weights separated by spaces or new lines>
+// Weights order:
+//   input layer: weights indexed by [input][hidden1] then hidden1 biases
+//   next layers: weights indexed by [output][input] then biases
+// Lines starting with '#' are ignored.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Accumulator {
+    values: Vec<f32>,
+}
+impl Accumulator {
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Layer {
+    n_inputs: usize,
+    n_outputs: usize,
+    weights: Vec<f32>,
+    biases: Vec<f32>,
+}
+impl Layer {
+    fn forward(&self, inputs: &[f32], is_last: bool) -> Vec<f32> {
+        (0..self.n_outputs)
+            .map(|o| {
+                let row = &self.weights[o * self.n_inputs..(o + 1) * self.n_inputs];
+                let sum: f32 = row.iter().zip(inputs).map(|(w, x)| w * x).sum();
+                let v = sum + self.biases[o];
+                if is_last {
+                    v
+                } else {
+                    clipped_relu(v)
+                }
+            })
+            .collect()
+    }
+}
+
+fn clipped_relu(v: f32) -> f32 {
+    v.clamp(0.0, 1.0)
+}
+
+pub fn feature_index(color: square::Color, type_piece: TypePiece, index: u8) -> usize {
+    let color_offset = match color {
+        square::Color::White => 0,
+        square::Color::Black => 6,
+    };
+    (color_offset + type_piece as usize) * 64 + index as usize
+}
+
+// Small fully connected network evaluated on CPU.
+// The first layer is computed incrementally (accumulator) while moves are played.
+#[derive(Debug, Clone)]
+pub struct NnEvaluator {
+    n_hidden: usize,
+    input_weights: Vec<f32>,
+    input_biases: Vec<f32>,
+    layers: Vec<Layer>,
+    scale: f32,
+}
+impl NnEvaluator {
+    // random weights: useful as a starting point for training
+    pub fn new_random(hidden_layers: &[usize], seed: u64) -> Result<Self, String> {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let sizes = Self::check_sizes(hidden_layers)?;
+        let mut rand_vec = |n: usize, range: f32| -> Vec<f32> {
+            (0..n).map(|_| rng.gen_range(-range..range)).collect()
+        };
+        let n_hidden = sizes[1];
+        let input_weights = rand_vec(N_INPUTS * n_hidden, 0.1);
+        let input_biases = rand_vec(n_hidden, 0.1);
+        let layers = sizes[1..]
+            .windows(2)
+            .map(|w| Layer {
+                n_inputs: w[0],
+                n_outputs: w[1],
+                weights: rand_vec(w[0] * w[1], 0.5),
+                biases: rand_vec(w[1], 0.1),
+            })
+            .collect();
+        Ok(Self {
+            n_hidden,
+            input_weights,
+            input_biases,
+            layers,
+            scale: DEFAULT_SCALE,
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("Cannot read weights file '{}': {}", path, err))?;
+        Self::parse(&content)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.serialize())
+            .map_err(|err| format!("Cannot write weights file '{}': {}", path, err))
+    }
+
+    // sizes of all layers including input and output: 768, h1, [h2], 1
+    fn check_sizes(hidden_layers: &[usize]) -> Result<Vec<usize>, String> {
+        if hidden_layers.is_empty() || hidden_layers.len() > 2 {
+            return Err(format!(
+                "1 or 2 hidden layers expected, found {}",
+                hidden_layers.len()
+            ));
+        }
+        if hidden_layers.contains(&0) {
+            return Err("Hidden layer size must be > 0".to_string());
+        }
+        let mut sizes = vec![N_INPUTS];
+        sizes.extend_from_slice(hidden_layers);
+        sizes.push(1);
+        Ok(sizes)
+    }
+
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut lines = content
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        match lines.next() {
+            Some(header) if header == NN_HEADER => {}
+            other => {
+                return Err(format!(
+                    "Invalid header: expected '{}', found {:?}",
+                    NN_HEADER, other
+                ))
+            }
+        }
+        let layers_line = lines.next().ok_or("Missing 'layers' line")?;
+        let sizes: Vec<usize> = match layers_line.strip_prefix("layers ") {
+            Some(values) => values
+                .split_whitespace()
+                .map(|v| v.parse::<usize>().map_err(|err| err.to_string()))
+                .collect::<Result<_, _>>()?,
+            None => return Err(format!("Invalid layers line: '{}'", layers_line)),
+        };
+        if sizes.len() < 3 || sizes[0] != N_INPUTS || sizes[sizes.len() - 1] != 1 {
+            return Err(format!("Invalid layers: {:?}", sizes));
+        }
+        Self::check_sizes(&sizes[1..sizes.len() - 1])?;
+        let scale_line = lines.next().ok_or("Missing 'scale' line")?;
+        let scale: f32 = match scale_line.strip_prefix("scale ") {
+            Some(value) => value.trim().parse().map_err(|err| format!("{}", err))?,
+            None => return Err(format!("Invalid scale line: '{}'", scale_line)),
+        };
+        let mut values = lines.flat_map(|line| line.split_whitespace()).map(|v| {
+            v.parse::<f32>()
+                .map_err(|err| format!("Invalid weight '{}': {}", v, err))
+        });
+        let mut take = |n: usize| -> Result<Vec<f32>, String> {
+            let v: Vec<f32> = values.by_ref().take(n).collect::<Result<_, _>>()?;
+            if v.len() != n {
+                return Err(format!("Not enough weights: expected {} found {}", n, v.len()));
+            }
+            Ok(v)
+        };
+        let n_hidden = sizes[1];
+        let input_weights = take(N_INPUTS * n_hidden)?;
+        let input_biases = take(n_hidden)?;
+        let mut layers = vec![];
+        for w in sizes[1..].windows(2) {
+            layers.push(Layer {
+                n_inputs: w[0],
+                n_outputs: w[1],
+                weights: take(w[0] * w[1])?,
+                biases: take(w[1])?,
+            });
+        }
+        if values.next().is_some() {
+            return Err("Too many weights in file".to_string());
+        }
+        Ok(Self {
+            n_hidden,
+            input_weights,
+            input_biases,
+            layers,
+            scale,
+        })
+    }
+
+    pub fn serialize(&self) -> String {
+        let sizes: Vec<String> = std::iter::once(N_INPUTS)
+            .chain(self.layers.iter().map(|l| l.n_inputs))
+            .chain(std::iter::once(1))
+            .map(|s| s.to_string())
+            .collect();
+        let mut out = format!("{}\nlayers {}\nscale {}\n", NN_HEADER, sizes.join(" "), self.scale);
+        let mut push = |values: &[f32]| {
+            for chunk in values.chunks(16) {
+                let line: Vec<String> = chunk.iter().map(|v| v.to_string()).collect();
+                out.push_str(&line.join(" "));
+                out.push('\n');
+            }
+        };
+        push(&self.input_weights);
+        push(&self.input_biases);
+        for layer in &self.layers {
+            push(&layer.weights);
+            push(&layer.biases);
+        }
+        out
+    }
+
+    fn add_feature(&self, acc: &mut Accumulator, feature: usize) {
+        let row = &self.input_weights[feature * self.n_hidden..(feature + 1) * self.n_hidden];
+        acc.values.iter_mut().zip(row).for_each(|(a, w)| *a += w);
+    }
+    fn sub_feature(&self, acc: &mut Accumulator, feature: usize) {
+        let row = &self.input_weights[feature * self.n_hidden..(feature + 1) * self.n_hidden];
+        acc.values.iter_mut().zip(row).for_each(|(a, w)| *a -= w);
+    }
+
+    // full computation of the accumulator
+    pub fn refresh(&self, bit_position: &bitboard::BitPosition) -> Accumulator {
+        let mut acc = Accumulator {
+            values: self.input_biases.clone(),
+        };
+        let bit_boards = bit_position.bit_boards_white_and_black();
+        for color in [square::Color::White, square::Color::Black] {
+            for type_piece in TypePiece::ALL {
+                for index in bit_boards.bit_board(&color).get_bitboard(type_piece).iter() {
+                    self.add_feature(&mut acc, feature_index(color, type_piece, index.value()));
+                }
+            }
+        }
+        acc
+    }
+
+    // incremental update of the accumulator for a move
+    pub fn update(&self, acc: &Accumulator, b_move: &bitboard::BitBoardMove) -> Accumulator {
+        let mut acc = acc.clone();
+        let color = b_move.color();
+        let start = b_move.start().value();
+        let end = b_move.end().value();
+        self.sub_feature(&mut acc, feature_index(color, b_move.type_piece(), start));
+        let type_piece_end = b_move
+            .promotion()
+            .map(|p| p.as_type_piece())
+            .unwrap_or(b_move.type_piece());
+        self.add_feature(&mut acc, feature_index(color, type_piece_end, end));
+        if let Some(capture) = b_move.capture() {
+            self.sub_feature(&mut acc, feature_index(color.switch(), capture, end));
+        } else if b_move.is_capture_en_passant() {
+            let index_pawn = b_move.start().row() * 8 + b_move.end().col();
+            self.sub_feature(
+                &mut acc,
+                feature_index(color.switch(), TypePiece::Pawn, index_pawn),
+            );
+        }
+        if let Some(castle) = b_move.check_castle() {
+            let (rook_start, rook_end) = match castle {
+                bitboard::Castle::Short => (start + 3, start + 1),
+                bitboard::Castle::Long => (start - 4, start - 1),
+            };
+            self.sub_feature(&mut acc, feature_index(color, TypePiece::Rook, rook_start));
+            self.add_feature(&mut acc, feature_index(color, TypePiece::Rook, rook_end));
+        }
+        acc
+    }
+
+    // score from white perspective
+    pub fn forward(&self, acc: &Accumulator) -> i32 {
+        let mut values: Vec<f32> = acc.values.iter().map(|v| clipped_relu(*v)).collect();
+        let n_layers = self.layers.len();
+        for (idx, layer) in self.layers.iter().enumerate() {
+            values = layer.forward(&values, idx == n_layers - 1);
+        }
+        (values[0] * self.scale) as i32
+    }
+}
+
+impl Evaluator for NnEvaluator {
+    fn name(&self) -> String {
+        let sizes: Vec<String> = self.layers.iter().map(|l| l.n_inputs.to_string()).collect();
+        format!("nn {}", sizes.join("x"))
+    }
+    fn evaluate(
+        &self,
+        game: &game_state::GameState,
+        stat_eval: &mut stat_eval::StatEval,
+        stat_actor_opt: &Option<stat_entity::StatActor>,
+        engine_id: logic::EngineId,
+    ) -> i32 {
+        evaluation::update_stat_eval(stat_eval, stat_actor_opt, engine_id);
+        match stat_eval.accumulators().last() {
+            Some(acc) => self.forward(acc),
+            None => self.forward(&self.refresh(game.bit_position())),
+        }
+    }
+    fn init(&self, game: &game_state::GameState, stat_eval: &mut stat_eval::StatEval) {
+        let acc = self.refresh(game.bit_position());
+        let accumulators = stat_eval.accumulators_mut();
+        accumulators.clear();
+        accumulators.push(acc);
+    }
+    fn make_move(&self, b_move: &bitboard::BitBoardMove, stat_eval: &mut stat_eval::StatEval) {
+        let accumulators = stat_eval.accumulators_mut();
+        if let Some(acc) = accumulators.last() {
+            let acc = self.update(acc, b_move);
+            accumulators.push(acc);
+        }
+    }
+    fn unmake_move(&self, stat_eval: &mut stat_eval::StatEval) {
+        let accumulators = stat_eval.accumulators_mut();
+        // keep the root accumulator
+        if accumulators.len() > 1 {
+            accumulators.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::game::component::bitboard::zobrist;
+    use crate::ui::notation::fen::{self, EncodeUserInput};
+    use crate::ui::notation::long_notation;
+
+    fn play_and_compare(fen_str: &str, moves: &[&str]) {
+        let nn = NnEvaluator::new_random(&[16, 8], 42).unwrap();
+        let zobrist_table = zobrist::Zobrist::new();
+        let position = fen::Fen::decode(fen_str).expect("Failed to decode FEN");
+        let mut game = game_state::GameState::new(position, &zobrist_table);
+        let mut stat_eval = stat_eval::StatEval::default();
+        nn.init(&game, &mut stat_eval);
+        for m in moves {
+            let long_move = long_notation::LongAlgebricNotationMove::build_from_str(m).unwrap();
+            let b_moves = game
+                .play_moves(&[long_move], &zobrist_table, None, false)
+                .unwrap();
+            nn.make_move(&b_moves[0], &mut stat_eval);
+            let expected = nn.refresh(game.bit_position());
+            let acc = stat_eval.accumulators().last().unwrap();
+            for (a, b) in acc.values().iter().zip(expected.values()) {
+                assert!((a - b).abs() < 1e-4, "accumulator mismatch after {}", m);
+            }
+        }
+        for _ in moves {
+            game.play_back();
+            nn.unmake_move(&mut stat_eval);
+        }
+        assert_eq!(stat_eval.accumulators().len(), 1);
+    }
+
+    #[test]
+    fn test_nn_accumulator_incremental() {
+        play_and_compare(
+            fen::FEN_START_POSITION,
+            &["e2e4", "d7d5", "e4d5", "g8f6", "g1f3", "f6d5", "f1c4", "b8c6", "e1g1"],
+        );
+    }
+
+    #[test]
+    fn test_nn_accumulator_en_passant_promotion_castle_long() {
+        play_and_compare(
+            "r3k3/1P6/8/8/3p4/8/4P3/4K3 w q - 0 1",
+            &["e2e4", "d4e3", "b7b8q", "e8c8"],
+        );
+    }
+
+    #[test]
+    fn test_nn_save_and_load() {
+        let nn = NnEvaluator::new_random(&[8], 1).unwrap();
+        let nn2 = NnEvaluator::parse(&nn.serialize()).unwrap();
+        let zobrist_table = zobrist::Zobrist::new();
+        let position = fen::Fen::decode(fen::FEN_START_POSITION).unwrap();
+        let game = game_state::GameState::new(position, &zobrist_table);
+        let score = nn.forward(&nn.refresh(game.bit_position()));
+        let score2 = nn2.forward(&nn2.refresh(game.bit_position()));
+        assert!((score - score2).abs() <= 1);
+        assert!(NnEvaluator::parse("bad header").is_err());
+        assert!(NnEvaluator::new_random(&[8, 8, 8], 1).is_err());
+    }
+}
@@ -1,8 +1,16 @@
+use super::nn_evaluator;
+
 #[derive(Debug, Default)]
 pub struct StatEval {
     n_positions_evaluated: u64,
     n_transposition_hit: u64,
     n_check: u64,
+    // total number of positions evaluated (never reset)
+    n_nodes: u64,
+    // the search is stopped once this number of positions is evaluated
+    max_nodes_opt: Option<u64>,
+    // incremental state for the nn evaluator (one accumulator per ply)
+    accumulators: Vec<nn_evaluator::Accumulator>,
 }
 impl StatEval {
     pub fn n_positions_evaluated(&self) -> u64 {
@@ -11,7 +19,18 @@ impl StatEval {
     pub fn n_transposition_hit(&self) -> u64 {
         self.n_transposition_hit
     }
+    pub fn n_nodes(&self) -> u64 {
+        self.n_nodes
+    }
+    pub fn set_max_nodes(&mut self, max_nodes_opt: Option<u64>) {
+        self.max_nodes_opt = max_nodes_opt;
+    }
+    pub fn is_node_limit_reached(&self) -> bool {
+        self.max_nodes_opt
+            .is_some_and(|max_nodes| self.n_nodes >= max_nodes)
+    }
     pub fn inc_n_positions_evaluated(&mut self) -> u64 {
+        self.n_nodes += 1;
         self.n_positions_evaluated += 1;
         self.n_positions_evaluated
     }
@@ -26,4 +45,10 @@ impl StatEval {
         self.n_check += n_check;
         self.n_check
     }
+    pub fn accumulators(&self) -> &Vec<nn_evaluator::Accumulator> {
+        &self.accumulators
+    }
+    pub fn accumulators_mut(&mut self) -> &mut Vec<nn_evaluator::Accumulator> {
+        &mut self.accumulators
+    }
 }
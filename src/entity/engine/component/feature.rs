@@ -9,3 +9,12 @@ pub const FEATURE_CHECK_HORIZON: bool = true;
 pub const FEATURE_MAT_SOLVER: bool = false;
 pub const FEATURE_CANNOT_WIN_FORCE_NULL: bool = true;
 pub const FEATURE_ASPIRATION_WINDOW: bool = true;
+pub const FEATURE_BITBASE: bool = true;
+// Evaluation terms
+pub const FEATURE_EVAL_MOBILITY: bool = true;
+pub const FEATURE_EVAL_BISHOP_PAIR: bool = true;
+pub const FEATURE_EVAL_ROOK_OPEN_FILE: bool = true;
+pub const FEATURE_EVAL_ROOK_7TH_RANK: bool = true;
+pub const FEATURE_EVAL_KNIGHT_OUTPOST: bool = true;
+pub const FEATURE_EVAL_BAD_BISHOP: bool = true;
+pub const FEATURE_EVAL_ENDGAME: bool = true;
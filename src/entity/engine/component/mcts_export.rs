@@ -0,0 +1,303 @@
+use std::fmt::Write;
+
+use crate::entity::engine::component::config::config;
+use crate::entity::engine::component::mcts_tree;
+use crate::entity::game::component::bitboard::zobrist;
+use crate::ui::notation::san;
+
+// a node of the tree as it is exported
+struct ExportNode {
+    id: usize,
+    parent_opt: Option<usize>,
+    depth: usize,
+    // move leading to the node (none for the root)
+    san: String,
+    visits: u64,
+    // average playout result for the player who moved into the node
+    win_rate: f64,
+    // UCB1 or PUCT value seen from the parent (none for the root)
+    selection_value_opt: Option<f64>,
+    proof: mcts_tree::Proof,
+}
+
+// write the tree below root to export_conf.path, in DOT or JSON
+pub fn export_tree(
+    graph: &mcts_tree::Graph,
+    root: mcts_tree::NodeIdx,
+    mcts_conf: &config::MctsConfig,
+    export_conf: &config::TreeExportConf,
+    zobrist_table: &zobrist::Zobrist,
+) -> Result<(), String> {
+    let content = match export_conf.format {
+        config::TreeExportFormat::Dot => {
+            tree_to_dot(graph, root, mcts_conf, export_conf, zobrist_table)
+        }
+        config::TreeExportFormat::Json => {
+            tree_to_json(graph, root, mcts_conf, export_conf, zobrist_table)
+        }
+    };
+    std::fs::write(&export_conf.path, content)
+        .map_err(|err| format!("Cannot write the tree to '{}': {}", export_conf.path, err))
+}
+
+pub fn tree_to_dot(
+    graph: &mcts_tree::Graph,
+    root: mcts_tree::NodeIdx,
+    mcts_conf: &config::MctsConfig,
+    export_conf: &config::TreeExportConf,
+    zobrist_table: &zobrist::Zobrist,
+) -> String {
+    let nodes = export_nodes(graph, root, mcts_conf, export_conf, zobrist_table);
+    let mut dot = String::new();
+    dot.push_str("digraph mcts {\n");
+    dot.push_str("  node [shape=box, fontname=\"monospace\"];\n");
+    for node in &nodes {
+        let mut label = if node.parent_opt.is_none() {
+            "root".to_string()
+        } else {
+            node.san.clone()
+        };
+        let _ = write!(
+            label,
+            "\\nvisits: {}\\nwin rate: {:.3}",
+            node.visits, node.win_rate
+        );
+        if let Some(selection_value) = node.selection_value_opt {
+            let _ = write!(label, "\\n{}: {:.3}", mcts_conf.selection, selection_value);
+        }
+        let color = match node.proof {
+            mcts_tree::Proof::Unknown => "",
+            mcts_tree::Proof::Win => ", color=green",
+            mcts_tree::Proof::Loss => ", color=red",
+        };
+        let _ = writeln!(
+            dot,
+            "  n{} [label=\"{}\"{}];",
+            node.id,
+            dot_escape(&label),
+            color
+        );
+    }
+    for node in &nodes {
+        if let Some(parent) = node.parent_opt {
+            let _ = writeln!(
+                dot,
+                "  n{} -> n{} [label=\"{}\"];",
+                parent,
+                node.id,
+                dot_escape(&node.san)
+            );
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+// flat list of nodes: each node refers to its parent id
+pub fn tree_to_json(
+    graph: &mcts_tree::Graph,
+    root: mcts_tree::NodeIdx,
+    mcts_conf: &config::MctsConfig,
+    export_conf: &config::TreeExportConf,
+    zobrist_table: &zobrist::Zobrist,
+) -> String {
+    let nodes = export_nodes(graph, root, mcts_conf, export_conf, zobrist_table);
+    let mut json = String::new();
+    let _ = writeln!(
+        json,
+        "{{\n  \"selection\": \"{}\",\n  \"nodes\": [",
+        mcts_conf.selection
+    );
+    for (i, node) in nodes.iter().enumerate() {
+        let parent = node
+            .parent_opt
+            .map_or("null".to_string(), |parent| parent.to_string());
+        let san = if node.parent_opt.is_none() {
+            "null".to_string()
+        } else {
+            format!("\"{}\"", json_escape(&node.san))
+        };
+        let selection_value = node
+            .selection_value_opt
+            .map_or("null".to_string(), json_number);
+        let proof = match node.proof {
+            mcts_tree::Proof::Unknown => "null",
+            mcts_tree::Proof::Win => "\"win\"",
+            mcts_tree::Proof::Loss => "\"loss\"",
+        };
+        let separator = if i + 1 < nodes.len() { "," } else { "" };
+        let _ = writeln!(
+            json,
+            "    {{\"id\": {}, \"parent\": {}, \"depth\": {}, \"move\": {}, \"visits\": {}, \"win_rate\": {}, \"selection_value\": {}, \"proof\": {}}}{}",
+            node.id,
+            parent,
+            node.depth,
+            san,
+            node.visits,
+            json_number(node.win_rate),
+            selection_value,
+            proof,
+            separator
+        );
+    }
+    json.push_str("  ]\n}\n");
+    json
+}
+
+// depth first walk: the root is always exported, the other nodes within max_depth and min_visits
+fn export_nodes(
+    graph: &mcts_tree::Graph,
+    root: mcts_tree::NodeIdx,
+    mcts_conf: &config::MctsConfig,
+    export_conf: &config::TreeExportConf,
+    zobrist_table: &zobrist::Zobrist,
+) -> Vec<ExportNode> {
+    let mut nodes: Vec<ExportNode> = vec![];
+    let mut stack = vec![(root, None, 0, String::new())];
+    while let Some((node_idx, parent_opt, depth, san)) = stack.pop() {
+        let node = &graph[node_idx];
+        let visits = node.visits();
+        let win_rate = if visits == 0 {
+            0.0
+        } else {
+            node.value() / visits as f64
+        };
+        let selection_value_opt = parent_opt.map(|_| node.selection_value(graph, mcts_conf));
+        let id = nodes.len();
+        nodes.push(ExportNode {
+            id,
+            parent_opt,
+            depth,
+            san,
+            visits,
+            win_rate,
+            selection_value_opt,
+            proof: node.proof(),
+        });
+        if depth >= export_conf.max_depth {
+            continue;
+        }
+        let game = node.game();
+        let moves = game.gen_moves();
+        // reversed so that the children are exported in the order of expansion
+        for child_idx in node.children().iter().rev() {
+            let child = &graph[*child_idx];
+            if child.visits() < export_conf.min_visits {
+                continue;
+            }
+            let edge_index = graph.find_edge(node_idx, *child_idx).unwrap();
+            let b_move = graph[edge_index].0;
+            let mut child_san = san::san_to_str(
+                &b_move,
+                &moves,
+                &san::Lang::LangEn,
+                game,
+                zobrist_table,
+                false,
+            )
+            .info()
+            .clone();
+            if child.game().end_game().is_mat() {
+                child_san = format!("{}#", child_san.trim_end_matches('+'));
+            }
+            stack.push((*child_idx, Some(id), depth + 1, child_san));
+        }
+    }
+    nodes
+}
+
+// unexplored nodes have an infinite UCB1 value, which JSON cannot represent
+fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        format!("{:.4}", value)
+    } else {
+        "null".to_string()
+    }
+}
+
+fn json_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// line breaks '\n' of the labels are kept
+fn dot_escape(s: &str) -> String {
+    s.replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::game::component::game_state;
+    use crate::ui::notation::fen::{self, EncodeUserInput};
+
+    // root with all its moves expanded, each child visited twice
+    fn expanded_tree(zobrist_table: &zobrist::Zobrist) -> (mcts_tree::Graph, mcts_tree::NodeIdx) {
+        let position = fen::Fen::decode("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let game = game_state::GameState::new(position, zobrist_table);
+        let mut graph = mcts_tree::Graph::new();
+        let moves = game.gen_moves();
+        let root = mcts_tree::Node::build_root(game, &moves);
+        let root_id = mcts_tree::add_node_to_graph(&mut graph, root);
+        for _ in 0..moves.len() {
+            let child_id = mcts_tree::Node::exploration(&mut graph, root_id, 0, zobrist_table);
+            graph[child_id].inc_stat(1.0, 2);
+            graph[root_id].inc_stat(1.0, 2);
+        }
+        (graph, root_id)
+    }
+
+    #[test]
+    fn test_export_dot() {
+        let zobrist_table = zobrist::Zobrist::new();
+        let (graph, root_id) = expanded_tree(&zobrist_table);
+        let conf = config::MctsConfig::new(1);
+        let export_conf = config::TreeExportConf::new("tree.dot");
+        assert_eq!(export_conf.format, config::TreeExportFormat::Dot);
+        let dot = tree_to_dot(&graph, root_id, &conf, &export_conf, &zobrist_table);
+        assert!(dot.starts_with("digraph mcts {"));
+        assert!(dot.contains("n0 [label=\"root\\nvisits: "));
+        // the mate is labelled with its SAN move and proven
+        assert!(dot.contains("[label=\"Ra8#\"]"));
+        assert!(dot.contains("win rate: 0.500\\nucb1: "));
+        assert!(dot.contains("color=green"));
+    }
+
+    #[test]
+    fn test_export_json_filters() {
+        let zobrist_table = zobrist::Zobrist::new();
+        let (graph, root_id) = expanded_tree(&zobrist_table);
+        let conf = config::MctsConfig::new(1);
+        let mut export_conf = config::TreeExportConf::new("tree.json");
+        assert_eq!(export_conf.format, config::TreeExportFormat::Json);
+        let nodes = export_nodes(&graph, root_id, &conf, &export_conf, &zobrist_table);
+        assert_eq!(nodes.len(), 1 + graph[root_id].children().len());
+        assert!(nodes.iter().skip(1).all(|node| node.depth == 1));
+        assert!(nodes.iter().any(|node| node.san == "Ra8#"));
+        // depth 0: only the root
+        export_conf.max_depth = 0;
+        let json = tree_to_json(&graph, root_id, &conf, &export_conf, &zobrist_table);
+        assert!(json.contains("\"selection\": \"ucb1\""));
+        assert!(json.contains("{\"id\": 0, \"parent\": null, \"depth\": 0, \"move\": null"));
+        assert!(!json.contains("\"id\": 1"));
+        // the children have not been visited enough
+        export_conf.max_depth = 3;
+        export_conf.min_visits = 3;
+        let nodes = export_nodes(&graph, root_id, &conf, &export_conf, &zobrist_table);
+        assert_eq!(nodes.len(), 1);
+    }
+
+    #[test]
+    fn test_export_tree_to_file() {
+        let zobrist_table = zobrist::Zobrist::new();
+        let (graph, root_id) = expanded_tree(&zobrist_table);
+        let conf = config::MctsConfig::new(1);
+        let path = std::env::temp_dir().join(format!("mcts_tree_{}.json", std::process::id()));
+        let export_conf = config::TreeExportConf::new(path.to_str().unwrap());
+        export_tree(&graph, root_id, &conf, &export_conf, &zobrist_table).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(content.contains("\"move\": \"Ra8#\""));
+        let export_conf = config::TreeExportConf::new("/nonexistent/tree.dot");
+        assert!(export_tree(&graph, root_id, &conf, &export_conf, &zobrist_table).is_err());
+    }
+}
@@ -1,13 +1,11 @@
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::{env, fmt};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
 
+use crate::entity::engine::component::config::config;
 use crate::entity::game::component::bitboard::zobrist;
 use crate::entity::game::component::{bitboard, game_state};
 use crate::ui::notation::long_notation;
 
-const TREE_FILE_PATH: &str = "tree.txt";
-
 pub type NodeIdx = petgraph::graph::NodeIndex;
 pub type Graph = petgraph::graph::Graph<Node, EdgeMove>;
 pub struct EdgeMove(pub bitboard::BitBoardMove);
@@ -18,19 +16,93 @@ fn span_debug() -> tracing::Span {
     span_debug!("mcts_tree")
 }
 
-#[derive(Debug, Clone)]
+// f64 updated by several threads
+#[derive(Debug, Default)]
+pub struct AtomicF64(AtomicU64);
+impl AtomicF64 {
+    pub fn new(value: f64) -> Self {
+        Self(AtomicU64::new(value.to_bits()))
+    }
+    pub fn load(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+    pub fn fetch_add(&self, value: f64) {
+        let _ = self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some((f64::from_bits(bits) + value).to_bits())
+            });
+    }
+}
+
+// Game theoretic value of a node for the player who moved into it (MCTS-Solver)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Proof {
+    Unknown,
+    Win,
+    Loss,
+}
+impl Proof {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Win,
+            2 => Self::Loss,
+            _ => Self::Unknown,
+        }
+    }
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::Unknown => 0,
+            Self::Win => 1,
+            Self::Loss => 2,
+        }
+    }
+}
+
+// the statistics are atomic: they are updated by the search threads without locking the tree
+#[derive(Debug)]
 pub struct Node {
     index: Option<NodeIdx>,
     parent: Option<NodeIdx>,
     children: Vec<NodeIdx>,
     untried_moves: Vec<bitboard::BitBoardMove>,
+    // prior probabilities of the untried moves
+    untried_priors: Vec<f64>,
+    // prior probability of the move leading to the node
+    prior: f64,
     game: game_state::GameState,
-    n_visits: u64,
-    n_wins: u64,
+    n_visits: AtomicU64,
+    // sum of the playout results seen by the player who moved into the node
+    value: AtomicF64,
+    // searches going through the node, counted as losses until their backpropagation
+    n_virtual_losses: AtomicU64,
+    proof: AtomicU8,
+}
+impl Clone for Node {
+    fn clone(&self) -> Self {
+        Self {
+            index: self.index,
+            parent: self.parent,
+            children: self.children.clone(),
+            untried_moves: self.untried_moves.clone(),
+            untried_priors: self.untried_priors.clone(),
+            prior: self.prior,
+            game: self.game.clone(),
+            n_visits: AtomicU64::new(self.visits()),
+            value: AtomicF64::new(self.value()),
+            n_virtual_losses: AtomicU64::new(self.n_virtual_losses.load(Ordering::Relaxed)),
+            proof: AtomicU8::new(self.proof().to_u8()),
+        }
+    }
 }
 impl fmt::Display for Node {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}/{}", self.n_wins, self.n_visits)
+        write!(f, "{:.1}/{}", self.value(), self.visits())?;
+        match self.proof() {
+            Proof::Unknown => Ok(()),
+            Proof::Win => write!(f, " (win)"),
+            Proof::Loss => write!(f, " (loss)"),
+        }
     }
 }
 impl Node {
@@ -49,22 +121,49 @@ impl Node {
     pub fn untried_moves(&self) -> &Vec<bitboard::BitBoardMove> {
         &self.untried_moves
     }
-    pub fn n_wins(&self) -> u64 {
-        self.n_wins
+    pub fn value(&self) -> f64 {
+        self.value.load()
+    }
+    pub fn prior(&self) -> f64 {
+        self.prior
+    }
+    pub fn untried_priors(&self) -> &Vec<f64> {
+        &self.untried_priors
     }
     pub fn visits(&self) -> u64 {
-        self.n_visits
+        self.n_visits.load(Ordering::Relaxed)
+    }
+    pub fn proof(&self) -> Proof {
+        Proof::from_u8(self.proof.load(Ordering::Relaxed))
+    }
+    pub fn is_proven(&self) -> bool {
+        self.proof() != Proof::Unknown
+    }
+    pub fn virtual_losses(&self) -> u64 {
+        self.n_virtual_losses.load(Ordering::Relaxed)
+    }
+    // visits including the virtual losses
+    fn effective_visits(&self, virtual_loss: u64) -> u64 {
+        self.visits() + self.virtual_losses() * virtual_loss
     }
 
-    pub fn add_child(parent_idx: NodeIdx, game: game_state::GameState) -> Self {
+    pub fn add_child(parent_idx: NodeIdx, game: game_state::GameState, prior: f64) -> Self {
         Self {
             index: None,
             parent: Some(parent_idx),
             children: vec![],
             untried_moves: vec![],
+            untried_priors: vec![],
+            prior,
+            // the player who mates wins
+            proof: AtomicU8::new(match game.end_game() {
+                game_state::EndGame::Mat(_) => Proof::Win.to_u8(),
+                _ => Proof::Unknown.to_u8(),
+            }),
             game,
-            n_visits: 0,
-            n_wins: 0,
+            n_visits: AtomicU64::new(0),
+            value: AtomicF64::new(0.0),
+            n_virtual_losses: AtomicU64::new(0),
         }
     }
     pub fn build_root(game: game_state::GameState, moves: &[bitboard::BitBoardMove]) -> Self {
@@ -73,9 +172,13 @@ impl Node {
             parent: None,
             children: vec![],
             untried_moves: moves.to_vec(),
+            untried_priors: vec![1.0 / moves.len().max(1) as f64; moves.len()],
+            prior: 1.0,
             game,
-            n_visits: 0,
-            n_wins: 0,
+            n_visits: AtomicU64::new(0),
+            value: AtomicF64::new(0.0),
+            n_virtual_losses: AtomicU64::new(0),
+            proof: AtomicU8::new(Proof::Unknown.to_u8()),
         }
     }
     pub fn get_node_mut(graph: &mut Graph, node_idx: NodeIdx) -> &mut Node {
@@ -85,36 +188,91 @@ impl Node {
             panic!("No node {:?}", node_idx);
         }
     }
-    pub fn argmax(graph: &Graph, values: &[NodeIdx], c: f64) -> Option<usize> {
+    pub fn argmax(graph: &Graph, values: &[NodeIdx], conf: &config::MctsConfig) -> Option<usize> {
         values
             .iter()
             .enumerate()
-            .fold(None, |max_index: Option<(usize, &Node)>, (i, node_idx)| {
-                let node = &graph[*node_idx];
+            // proven nodes need no more search
+            .filter(|(_, node_idx)| !graph[**node_idx].is_proven())
+            .fold(None, |max_index: Option<(usize, f64)>, (i, node_idx)| {
+                let value = graph[*node_idx].selection_value(graph, conf);
                 match max_index {
-                    Some((max_i, max_value)) if node.ucb1(graph, c) <= max_value.ucb1(graph, c) => {
-                        Some((max_i, max_value))
-                    }
-                    _ => Some((i, node)),
+                    Some((max_i, max_value)) if value <= max_value => Some((max_i, max_value)),
+                    _ => Some((i, value)),
                 }
             })
             .map(|(i, _)| i)
     }
-    pub fn ucb1(&self, graph: &Graph, c: f64) -> f64 {
+    pub fn selection_value(&self, graph: &Graph, conf: &config::MctsConfig) -> f64 {
+        match conf.selection {
+            config::SelectionFormula::Ucb1 => self.ucb1(graph, conf.c, conf.virtual_loss),
+            config::SelectionFormula::Puct => self.puct(graph, conf.c_puct, conf.virtual_loss),
+        }
+    }
+    pub fn ucb1(&self, graph: &Graph, c: f64, virtual_loss: u64) -> f64 {
         assert!(!self.is_root());
-        if self.n_visits == 0 {
+        let n_visits = self.effective_visits(virtual_loss);
+        if n_visits == 0 {
             // ensure a node is explored at least once
             f64::INFINITY
         } else {
-            let exploitation_rate = (self.n_wins as f64) / (self.n_visits as f64);
+            let exploitation_rate = self.value() / (n_visits as f64);
             let mut exploration_rate = 0f64;
             if let Some(node_parent) = graph.node_weight(self.parent.unwrap()) {
-                exploration_rate =
-                    c * ((node_parent.visits() as f64).ln() / (self.n_visits as f64));
+                let parent_visits = node_parent.effective_visits(virtual_loss);
+                exploration_rate = c * ((parent_visits as f64).ln() / (n_visits as f64));
             }
             exploitation_rate + exploration_rate
         }
     }
+    pub fn puct(&self, graph: &Graph, c_puct: f64, virtual_loss: u64) -> f64 {
+        assert!(!self.is_root());
+        let n_visits = self.effective_visits(virtual_loss);
+        let exploitation_rate = if n_visits == 0 {
+            0.0
+        } else {
+            self.value() / (n_visits as f64)
+        };
+        let parent_visits = graph[self.parent.unwrap()].effective_visits(virtual_loss) as f64;
+        let exploration_rate = c_puct * self.prior * parent_visits.sqrt() / (1 + n_visits) as f64;
+        exploitation_rate + exploration_rate
+    }
+    // maximum number of children with progressive widening
+    pub fn max_children(&self, conf: &config::MctsConfig) -> usize {
+        if conf.widening_k <= 0.0 {
+            usize::MAX
+        } else {
+            let width = conf.widening_k * (self.visits() as f64).powf(conf.widening_alpha);
+            (width as usize).max(1)
+        }
+    }
+    // untried moves are expanded within the progressive widening limit,
+    // or when all the children are proven
+    pub fn is_expandable(&self, graph: &Graph, conf: &config::MctsConfig) -> bool {
+        !self.untried_moves.is_empty()
+            && (self.children.len() < self.max_children(conf)
+                || self.children.iter().all(|child| graph[*child].is_proven()))
+    }
+    // prove the node from its children: the node is lost if a child is won by the opponent,
+    // and won if all the moves have been expanded and are lost by the opponent.
+    // Return true if the node has just been proven.
+    pub fn update_proof(graph: &Graph, node_idx: NodeIdx) -> bool {
+        let node = &graph[node_idx];
+        if node.is_proven() || node.children.is_empty() {
+            return false;
+        }
+        let children_proofs: Vec<Proof> = node.children.iter().map(|c| graph[*c].proof()).collect();
+        let proof = if children_proofs.contains(&Proof::Win) {
+            Proof::Loss
+        } else if node.untried_moves.is_empty() && children_proofs.iter().all(|p| *p == Proof::Loss)
+        {
+            Proof::Win
+        } else {
+            return false;
+        };
+        node.proof.store(proof.to_u8(), Ordering::Relaxed);
+        true
+    }
     pub fn is_root(&self) -> bool {
         self.parent.is_none()
     }
@@ -123,12 +281,24 @@ impl Node {
             && self.untried_moves.is_empty()
             && self.game.end_game() != game_state::EndGame::None
     }
-    pub fn set_untried_moves(&mut self, untried_moves: Vec<bitboard::BitBoardMove>) {
+    pub fn set_untried_moves(
+        &mut self,
+        untried_moves: Vec<bitboard::BitBoardMove>,
+        priors: Vec<f64>,
+    ) {
+        assert_eq!(untried_moves.len(), priors.len());
         self.untried_moves = untried_moves;
+        self.untried_priors = priors;
+    }
+    pub fn inc_stat(&self, new_value: f64, n_new_visits: u64) {
+        self.value.fetch_add(new_value);
+        self.n_visits.fetch_add(n_new_visits, Ordering::Relaxed);
     }
-    pub fn inc_stat(&mut self, n_new_wins: u64, n_new_visits: u64) {
-        self.n_wins += n_new_wins;
-        self.n_visits += n_new_visits;
+    pub fn add_virtual_loss(&self) {
+        self.n_virtual_losses.fetch_add(1, Ordering::Relaxed);
+    }
+    pub fn remove_virtual_loss(&self) {
+        self.n_virtual_losses.fetch_sub(1, Ordering::Relaxed);
     }
     // add a new child based on the untried_moves at index idx
     pub fn exploration(
@@ -142,15 +312,12 @@ impl Node {
 
         assert!(idx < graph[node_idx].untried_moves.len());
         let selected_move = graph[node_idx].untried_moves.swap_remove(idx);
-        let long_algebraic_move =
-            long_notation::LongAlgebricNotationMove::build_from_b_move(selected_move);
+        let prior = graph[node_idx].untried_priors.swap_remove(idx);
         let mut game_clone = graph[node_idx].game.clone();
-        game_clone
-            .play_moves(&[long_algebraic_move], zobrist_table, None, false)
-            .unwrap();
+        game_clone.make_move(&selected_move, zobrist_table);
         game_clone.update_endgame_status();
         // create child node
-        let new_node = Node::add_child(graph[node_idx].index.unwrap(), game_clone);
+        let new_node = Node::add_child(graph[node_idx].index.unwrap(), game_clone, prior);
         let child_id = add_node_to_graph(graph, new_node);
         let edge_move = EdgeMove(selected_move);
         graph.add_edge(graph[node_idx].index.unwrap(), child_id, edge_move);
@@ -165,52 +332,6 @@ impl Node {
     }
 }
 
-#[allow(dead_code)]
-fn write_tree(graph: &Graph, node: NodeIdx) {
-    let exe_path = env::current_exe().expect("Failed to find executable path");
-    let folder_exe_path = exe_path
-        .parent()
-        .expect("Failed to get folder executable path");
-    let path = format!("{}/{}", folder_exe_path.display(), TREE_FILE_PATH);
-    let mut file = OpenOptions::new()
-        .write(true) // Set append mode
-        .create(true) // Create file if it doesn't exist
-        .truncate(true)
-        .open(path)
-        .expect("Failed to open or create file");
-    let indent = 0;
-    display_tree_to_file(graph, node, indent, 0, &mut file)
-}
-
-#[allow(dead_code)]
-fn display_tree_to_file(
-    graph: &Graph,
-    node: NodeIdx,
-    indent: usize,
-    level: i8,
-    file: &mut std::fs::File,
-) {
-    // Leaf ?
-    if graph
-        .neighbors_directed(node, petgraph::Direction::Outgoing)
-        .next()
-        .is_none()
-    {
-        let output = format!("{:indent$}{} {}\n", "", level, graph[node], indent = indent);
-        let _ = file.write_all(output.as_bytes());
-    } else {
-        let output = format!("{:indent$}{} {}\n", "", level, graph[node], indent = indent);
-        let _ = file.write_all(output.as_bytes());
-        if level < 5 {
-            // Iterate over children
-            for neighbor in graph.neighbors_directed(node, petgraph::Direction::Outgoing) {
-                display_tree(graph, neighbor, indent + 3, level + 1);
-                // Augmenter l'indentation pour les enfants
-            }
-        }
-    }
-}
-
 pub fn display_tree(graph: &Graph, node: NodeIdx, indent: usize, level: i8) {
     let span = span_debug();
     let _enter = span.enter();
@@ -257,6 +378,30 @@ pub fn display_tree(graph: &Graph, node: NodeIdx, indent: usize, level: i8) {
     }
 }
 
+// copy of the subtree below node: node is the root of the new graph
+pub fn subtree(graph: &Graph, node: NodeIdx) -> (Graph, NodeIdx) {
+    let mut new_graph = Graph::new();
+    let mut new_root = graph[node].clone();
+    new_root.parent = None;
+    new_root.children = vec![];
+    let new_root_idx = add_node_to_graph(&mut new_graph, new_root);
+    let mut stack = vec![(node, new_root_idx)];
+    while let Some((node_idx, new_node_idx)) = stack.pop() {
+        for child_idx in graph[node_idx].children() {
+            let mut child = graph[*child_idx].clone();
+            child.parent = Some(new_node_idx);
+            child.children = vec![];
+            let new_child_idx = add_node_to_graph(&mut new_graph, child);
+            let edge_index = graph.find_edge(node_idx, *child_idx).unwrap();
+            let edge_move = EdgeMove(graph[edge_index].0);
+            new_graph.add_edge(new_node_idx, new_child_idx, edge_move);
+            new_graph[new_node_idx].children.push(new_child_idx);
+            stack.push((*child_idx, new_child_idx));
+        }
+    }
+    (new_graph, new_root_idx)
+}
+
 pub fn add_node_to_graph(graph: &mut Graph, node: Node) -> NodeIdx {
     let node_idx = graph.add_node(node);
     // Update the `index` field after the node is added
@@ -5,7 +5,7 @@ pub const MAX_DEPTH: usize = 64;
 #[derive(Debug)]
 pub struct SearchState {
     // Stocke les killer moves pour chaque profondeur.
-    killer_moves: [[Option<bitboard::BitBoardMove>; 2]; MAX_DEPTH],
+    killer_moves: [[Option<bitboard::PackedMove>; 2]; MAX_DEPTH],
 }
 
 impl SearchState {
@@ -17,6 +17,7 @@ impl SearchState {
 
     // Ajoute un killer move pour une profondeur donnée
     pub fn add_killer_move(&mut self, depth: usize, mv: bitboard::BitBoardMove) {
+        let mv = mv.pack();
         // Vérifie si le coup est déjà stocké
         if self.killer_moves[depth][0] != Some(mv) {
             // Déplace le premier coup dans la deuxième position et ajoute le nouveau
@@ -27,6 +28,6 @@ impl SearchState {
 
     // Vérifie si un coup est un killer move
     pub fn is_killer_move(&self, depth: usize, mv: bitboard::BitBoardMove) -> bool {
-        self.killer_moves[depth].contains(&Some(mv))
+        self.killer_moves[depth].contains(&Some(mv.pack()))
     }
 }
@@ -1,31 +1,283 @@
-use std::time;
+use std::time::{Duration, Instant};
 
-use crate::entity::game::component::game_state;
+use crate::entity::game::component::bitboard::{BitBoardMove, BitPositionStatus};
+use crate::entity::game::component::square;
 
 const AVG_LENGTH_GAME: u64 = 220;
+// bounds of the estimated number of moves to play in sudden death
+const MIN_MOVES_TO_GO: u64 = 20;
+const MAX_MOVES_TO_GO: u64 = 50;
+// the hard limit is at most this factor of the soft limit
+const HARD_FACTOR: u64 = 5;
+// maximum share (%) of the available time for one move
+const MAX_USAGE: u64 = 50;
+const MAX_USAGE_LAST_MOVE: u64 = 90;
+const MIN_TIME_MS: u64 = 10;
+// below this target the engine plays the move of a shallow search
+const EMERGENCY_TIME_MS: u64 = 100;
+// latency between the engine and the GUI, subtracted from every allocation
+pub const DEFAULT_MOVE_OVERHEAD_MS: u64 = 50;
+pub const MAX_MOVE_OVERHEAD_MS: u64 = 5000;
+// a score drop of this value (0.3 pawn) between two iterations extends the search
+const SCORE_DROP: i32 = 300;
 
-fn compute_allocate_time_in_sec(time_remaining_ms: u64, n_half_moves: u64) -> u64 {
-    // very basic allocation
-    let allocation_in_ms = if n_half_moves < 10 {
-        5000
-    } else if n_half_moves > AVG_LENGTH_GAME {
-        time_remaining_ms / 20
-    } else {
-        2 * time_remaining_ms / (AVG_LENGTH_GAME - n_half_moves)
+// What the engine knows about its clock when it starts thinking (ms)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimeInfo {
+    pub remaining_ms: u64,
+    pub inc_ms: u64,
+    pub moves_to_go: Option<u32>,
+    pub move_overhead_ms: u64,
+    // clock of the opponent, when the game has one
+    pub opponent_remaining_ms_opt: Option<u64>,
+    pub opponent_inc_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeLimits {
+    // target time: no new iteration is started after it
+    pub soft: Duration,
+    // the search is stopped at this time
+    pub hard: Duration,
+    // almost no time left: no real search
+    pub is_emergency: bool,
+}
+
+impl TimeLimits {
+    // search of a fixed duration: no new iteration after half of it
+    pub fn move_time(time_ms: u64) -> Self {
+        Self {
+            soft: Duration::from_millis(time_ms / 2),
+            hard: Duration::from_millis(time_ms),
+            is_emergency: false,
+        }
+    }
+}
+
+// plies played since the start of the game, from the fullmove number
+// (the halfmove clock is reset by every capture or pawn move)
+pub fn n_plies_played(bit_position_status: &BitPositionStatus) -> u64 {
+    let n_plies = bit_position_status.n_moves().saturating_sub(1) as u64 * 2;
+    match bit_position_status.player_turn() {
+        square::Color::White => n_plies,
+        square::Color::Black => n_plies + 1,
+    }
+}
+
+pub fn compute_time_limits(time_info: &TimeInfo, n_plies_played: u64) -> TimeLimits {
+    let available = time_info
+        .remaining_ms
+        .saturating_sub(time_info.move_overhead_ms);
+    let moves_to_go = match time_info.moves_to_go {
+        Some(moves_to_go) => (moves_to_go as u64).clamp(1, MAX_MOVES_TO_GO),
+        None => (AVG_LENGTH_GAME.saturating_sub(n_plies_played) / 2)
+            .clamp(MIN_MOVES_TO_GO, MAX_MOVES_TO_GO),
     };
-    if allocation_in_ms > 1000 {
-        allocation_in_ms / 1000
+    let max_usage = if moves_to_go == 1 {
+        MAX_USAGE_LAST_MOVE
     } else {
-        1
+        MAX_USAGE
+    };
+    // the increment of the next moves is spent now
+    let soft_ms = available / moves_to_go + time_info.inc_ms * 3 / 4;
+    let hard_ms = (soft_ms * HARD_FACTOR)
+        .min(available * max_usage / 100)
+        .max(MIN_TIME_MS);
+    let soft_ms = soft_ms.clamp(MIN_TIME_MS, hard_ms);
+    TimeLimits {
+        soft: Duration::from_millis(soft_ms),
+        hard: Duration::from_millis(hard_ms),
+        is_emergency: soft_ms < EMERGENCY_TIME_MS,
     }
 }
 
-pub fn estimate_time_allocation(
-    remaining_time_ms_opt: Option<u64>,
-    game: &game_state::GameState,
-) -> Option<time::Duration> {
-    let n_half_moves = game.bit_position().bit_position_status().n_half_moves() as u64;
-    let max_time_opt = remaining_time_ms_opt
-        .map(|clock| time::Duration::from_secs(compute_allocate_time_in_sec(clock, n_half_moves)));
-    max_time_opt
+// Decide between two iterations of the iterative deepening if the search goes on
+#[derive(Debug, Clone)]
+pub struct TimeManager {
+    start: Instant,
+    limits: TimeLimits,
+    best_move_opt: Option<BitBoardMove>,
+    score_opt: Option<i32>,
+    // number of iterations in a row with the same best move
+    n_stable: u32,
+    // clock the limits come from (external engines manage their own time)
+    time_info_opt: Option<TimeInfo>,
+}
+impl TimeManager {
+    pub fn new(limits: TimeLimits, start: Instant) -> Self {
+        Self {
+            start,
+            limits,
+            best_move_opt: None,
+            score_opt: None,
+            n_stable: 0,
+            time_info_opt: None,
+        }
+    }
+    pub fn with_time_info(mut self, time_info: TimeInfo) -> Self {
+        self.time_info_opt = Some(time_info);
+        self
+    }
+    pub fn limits(&self) -> &TimeLimits {
+        &self.limits
+    }
+    pub fn time_info(&self) -> Option<&TimeInfo> {
+        self.time_info_opt.as_ref()
+    }
+    pub fn is_emergency(&self) -> bool {
+        self.limits.is_emergency
+    }
+    pub fn soft_deadline(&self) -> Instant {
+        self.start + self.limits.soft
+    }
+    pub fn hard_deadline(&self) -> Instant {
+        self.start + self.limits.hard
+    }
+    // score is from the point of view of the player to move.
+    // Return true if no new iteration should be started.
+    pub fn end_of_iteration(&mut self, best_move: BitBoardMove, score: i32, now: Instant) -> bool {
+        let is_flip = self.best_move_opt.is_some_and(|m| m != best_move);
+        let is_drop = self.score_opt.is_some_and(|s| score < s.saturating_sub(SCORE_DROP));
+        if self.best_move_opt == Some(best_move) {
+            self.n_stable += 1;
+        } else {
+            self.n_stable = 0;
+        }
+        self.best_move_opt = Some(best_move);
+        self.score_opt = Some(score);
+        // percentage of the soft limit
+        let mut scale: u32 = match self.n_stable {
+            0 | 1 => 100,
+            2 => 80,
+            3 => 65,
+            _ => 50,
+        };
+        if is_flip {
+            scale += 50;
+        }
+        if is_drop {
+            scale += 50;
+        }
+        let soft = (self.limits.soft * scale / 100).min(self.limits.hard);
+        let elapsed = now.saturating_duration_since(self.start);
+        tracing::debug!(
+            "time manager: elapsed {:?}, soft {:?} ({}%), hard {:?}",
+            elapsed,
+            soft,
+            scale,
+            self.limits.hard
+        );
+        elapsed >= soft
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::game::component::bitboard::{BitIndex, BitPosition};
+    use crate::entity::game::component::square::{Color, TypePiece};
+    use crate::ui::notation::fen::{self, EncodeUserInput};
+
+    fn ms(value: u64) -> Duration {
+        Duration::from_millis(value)
+    }
+
+    fn b_move(start: u8, end: u8) -> BitBoardMove {
+        BitBoardMove::new(
+            Color::White,
+            TypePiece::Pawn,
+            BitIndex::new(start),
+            BitIndex::new(end),
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_time_limits() {
+        let mut time_info = TimeInfo {
+            remaining_ms: 60_050,
+            inc_ms: 0,
+            moves_to_go: None,
+            move_overhead_ms: 50,
+            ..Default::default()
+        };
+        let limits = compute_time_limits(&time_info, 200);
+        assert_eq!(limits.soft, ms(3_000));
+        assert_eq!(limits.hard, ms(15_000));
+        // increment
+        time_info.inc_ms = 1_000;
+        let limits = compute_time_limits(&time_info, 200);
+        assert_eq!(limits.soft, ms(3_750));
+        // last move before the time control
+        time_info.inc_ms = 0;
+        time_info.moves_to_go = Some(1);
+        let limits = compute_time_limits(&time_info, 200);
+        assert_eq!(limits.hard, ms(54_000));
+        assert_eq!(limits.soft, ms(54_000));
+        assert!(!limits.is_emergency);
+        // the overhead is taken from the remaining time
+        time_info.move_overhead_ms = 10_050;
+        let limits = compute_time_limits(&time_info, 200);
+        assert_eq!(limits.hard, ms(45_000));
+        // no time left
+        time_info.remaining_ms = 20;
+        let limits = compute_time_limits(&time_info, 200);
+        assert_eq!(limits.hard, ms(MIN_TIME_MS));
+        assert!(limits.is_emergency);
+    }
+
+    #[test]
+    fn test_time_limits_late_game() {
+        let time_info = TimeInfo {
+            remaining_ms: 60_050,
+            move_overhead_ms: 50,
+            ..Default::default()
+        };
+        // move 90 just after a capture: the halfmove clock is 0
+        let position = fen::Fen::decode("8/5k2/8/8/3K4/8/8/8 w - - 0 90").unwrap();
+        let bit_position = BitPosition::from(position);
+        let n_plies = n_plies_played(bit_position.bit_position_status());
+        assert_eq!(n_plies, 178);
+        let limits = compute_time_limits(&time_info, n_plies);
+        // (220 - 178) / 2 = 21 moves to go
+        assert_eq!(limits.soft, ms(60_000 / 21));
+        // more time per move than in the opening
+        let limits_opening = compute_time_limits(&time_info, 0);
+        assert_eq!(limits_opening.soft, ms(60_000 / MAX_MOVES_TO_GO));
+        assert!(limits.soft > limits_opening.soft);
+    }
+
+    #[test]
+    fn test_stable_best_move() {
+        let limits = TimeLimits {
+            soft: ms(1_000),
+            hard: ms(5_000),
+            is_emergency: false,
+        };
+        let t0 = Instant::now();
+        let mut time_manager = TimeManager::new(limits, t0);
+        assert!(!time_manager.end_of_iteration(b_move(12, 28), 0, t0 + ms(100)));
+        assert!(!time_manager.end_of_iteration(b_move(12, 28), 0, t0 + ms(200)));
+        assert!(!time_manager.end_of_iteration(b_move(12, 28), 0, t0 + ms(700)));
+        // the best move is stable: the soft limit is shortened
+        assert!(time_manager.end_of_iteration(b_move(12, 28), 0, t0 + ms(700)));
+    }
+
+    #[test]
+    fn test_unstable_best_move() {
+        let limits = TimeLimits {
+            soft: ms(1_000),
+            hard: ms(5_000),
+            is_emergency: false,
+        };
+        let t0 = Instant::now();
+        let mut time_manager = TimeManager::new(limits, t0);
+        assert!(!time_manager.end_of_iteration(b_move(12, 28), 0, t0 + ms(100)));
+        // best move flips
+        assert!(!time_manager.end_of_iteration(b_move(11, 27), 0, t0 + ms(1_200)));
+        // score drops
+        assert!(!time_manager.end_of_iteration(b_move(11, 27), -500, t0 + ms(1_400)));
+        assert!(time_manager.end_of_iteration(b_move(11, 27), -500, t0 + ms(1_400)));
+    }
 }
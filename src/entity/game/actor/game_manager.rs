@@ -3,20 +3,25 @@ pub mod handler_engine;
 pub mod handler_game;
 pub mod handler_uci_command;
 
-use actix::{Actor, Addr, Context};
+use actix::{Actor, Addr, AsyncContext, Context};
 
 use crate::entity::{
-    engine::component::ts_best_move,
+    engine::{actor::engine_dispatcher as dispatcher, component::ts_best_move},
     game::component::{
         bitboard::{self, zobrist},
-        square,
+        square::{self, Switch},
     },
 };
 use crate::ui::notation::fen;
 use crate::ui::notation::long_notation::LongAlgebricNotationMove;
 
 use crate::entity::clock::actor::chessclock;
-use crate::entity::game::component::{game_state::GameState, parameters, player};
+use crate::entity::stat::actor::stat_entity;
+use crate::entity::uci::actor::uci_entity;
+use crate::entity::game::component::{
+    game_state::{EndGame, GameState},
+    parameters, player,
+};
 use crate::monitoring::debug;
 
 pub type GameManagerActor = Addr<GameManager>;
@@ -31,6 +36,8 @@ fn span_debug() -> tracing::Span {
 pub struct History {
     fen: String,
     moves: Vec<bitboard::BitBoardMove>,
+    // moves taken back, the last one is the next move to redo
+    undone_moves: Vec<bitboard::BitBoardMove>,
 }
 impl History {
     pub fn init(&mut self) {
@@ -39,9 +46,28 @@ impl History {
     pub fn set_fen(&mut self, fen: &str) {
         self.fen = fen.to_string();
         self.moves = vec![];
+        self.undone_moves = vec![];
     }
+    // a new move discards the moves taken back
     pub fn add_moves(&mut self, m: bitboard::BitBoardMove) {
         self.moves.push(m);
+        self.undone_moves = vec![];
+    }
+    pub fn moves(&self) -> &Vec<bitboard::BitBoardMove> {
+        &self.moves
+    }
+    pub fn n_undone_moves(&self) -> usize {
+        self.undone_moves.len()
+    }
+    fn undo_move(&mut self) -> Option<bitboard::BitBoardMove> {
+        let m = self.moves.pop()?;
+        self.undone_moves.push(m);
+        Some(m)
+    }
+    fn redo_move(&mut self) -> Option<bitboard::BitBoardMove> {
+        let m = self.undone_moves.pop()?;
+        self.moves.push(m);
+        Some(m)
     }
 }
 
@@ -55,6 +81,10 @@ pub struct GameManager {
     white_clock_actor_opt: Option<chessclock::ClockActor>,
     black_clock_actor_opt: Option<chessclock::ClockActor>,
     zobrist_table: zobrist::Zobrist,
+    // color of the player with a pending draw offer
+    draw_offer_opt: Option<square::Color>,
+    // the moves of the engines are played by the game manager
+    is_auto_play: bool,
 }
 
 impl GameManager {
@@ -69,6 +99,8 @@ impl GameManager {
             white_clock_actor_opt: None,
             black_clock_actor_opt: None,
             zobrist_table: zobrist::Zobrist::new(),
+            draw_offer_opt: None,
+            is_auto_play: false,
         }
     }
     pub fn game_state(&self) -> Option<&GameState> {
@@ -103,41 +135,276 @@ impl GameManager {
             });
         match result {
             Some(Ok(b_moves)) => {
-                let mut n_moves_white = 0u64;
-                let mut n_moves_black = 0u64;
-                for b_move in b_moves {
-                    self.history.add_moves(b_move);
-                    if b_move.color() == square::Color::White {
-                        n_moves_white += 1;
-                    } else {
-                        n_moves_black += 1;
+                for b_move in &b_moves {
+                    self.history.add_moves(*b_move);
+                    // a move of the opponent declines the draw offer
+                    if self
+                        .draw_offer_opt
+                        .is_some_and(|color| color != b_move.color())
+                    {
+                        self.draw_offer_opt = None;
                     }
                 }
-                if let Some(white_clock_actor) = &self.white_clock_actor_opt {
-                    async_clock_inc(
-                        "white".to_string(),
-                        n_moves_white,
-                        white_clock_actor.clone(),
-                    );
-                }
-                if let Some(black_clock_actor) = &self.black_clock_actor_opt {
-                    async_clock_inc(
-                        "black".to_string(),
-                        n_moves_black,
-                        black_clock_actor.clone(),
-                    );
-                }
+                self.inc_clocks(&b_moves);
                 Ok(())
             }
             Some(Err(err)) => Err(err), // illegal move
             None => Err("moves ignored since no position has been defined".to_string()),
         }
     }
+    fn inc_clocks(&self, b_moves: &[bitboard::BitBoardMove]) {
+        let (n_moves_white, n_moves_black) = count_moves_by_color(b_moves);
+        if let Some(white_clock_actor) = &self.white_clock_actor_opt {
+            async_clock_inc(
+                "white".to_string(),
+                n_moves_white,
+                white_clock_actor.clone(),
+            );
+        }
+        if let Some(black_clock_actor) = &self.black_clock_actor_opt {
+            async_clock_inc(
+                "black".to_string(),
+                n_moves_black,
+                black_clock_actor.clone(),
+            );
+        }
+    }
+    // the clocks are paused while navigating in the game:
+    // the next StartOrSwitchClocks restarts the clock of the player to move
+    fn pause_clocks(&self) {
+        for clock_actor in [&self.white_clock_actor_opt, &self.black_clock_actor_opt]
+            .into_iter()
+            .flatten()
+        {
+            clock_actor.do_send(chessclock::handler_clock::PauseClock);
+        }
+    }
+    // the searches in progress are aborted: their best move would be out of date
+    fn stop_engines(&mut self) {
+        self.is_auto_play = false;
+        for color in [square::Color::White, square::Color::Black] {
+            if let Ok(engine_actor) = self.players.get_engine(color) {
+                engine_actor.do_send(dispatcher::handler_engine::EngineCleanResources);
+            }
+        }
+    }
+    // take back the n last moves
+    fn undo_moves(&mut self, n_moves: usize) -> Result<(), String> {
+        let game_state = self
+            .game_state_opt
+            .as_mut()
+            .ok_or("undo ignored since no position has been defined".to_string())?;
+        if n_moves > self.history.moves().len() {
+            return Err(format!(
+                "cannot undo {} moves: only {} moves played",
+                n_moves,
+                self.history.moves().len()
+            ));
+        }
+        let mut b_moves = vec![];
+        for _ in 0..n_moves {
+            let b_move = self.history.undo_move().unwrap();
+            game_state.play_back();
+            b_moves.push(b_move);
+        }
+        game_state.update_endgame_status();
+        self.ts_best_move_opt = None;
+        self.draw_offer_opt = None;
+        self.stop_engines();
+        self.pause_clocks();
+        let (n_moves_white, n_moves_black) = count_moves_by_color(&b_moves);
+        if let Some(white_clock_actor) = &self.white_clock_actor_opt {
+            white_clock_actor.do_send(chessclock::handler_clock::UndoMoves(n_moves_white));
+        }
+        if let Some(black_clock_actor) = &self.black_clock_actor_opt {
+            black_clock_actor.do_send(chessclock::handler_clock::UndoMoves(n_moves_black));
+        }
+        Ok(())
+    }
+    // play again the n last moves taken back
+    fn redo_moves(&mut self, n_moves: usize) -> Result<(), String> {
+        let game_state = self
+            .game_state_opt
+            .as_mut()
+            .ok_or("redo ignored since no position has been defined".to_string())?;
+        if n_moves > self.history.n_undone_moves() {
+            return Err(format!(
+                "cannot redo {} moves: only {} moves taken back",
+                n_moves,
+                self.history.n_undone_moves()
+            ));
+        }
+        let mut b_moves = vec![];
+        for _ in 0..n_moves {
+            let b_move = self.history.redo_move().unwrap();
+            game_state.play_moves(
+                &[LongAlgebricNotationMove::build_from_b_move(b_move)],
+                &self.zobrist_table,
+                self.debug_actor_opt.clone(),
+                true,
+            )?;
+            b_moves.push(b_move);
+        }
+        game_state.update_endgame_status();
+        self.ts_best_move_opt = None;
+        self.draw_offer_opt = None;
+        self.stop_engines();
+        self.pause_clocks();
+        self.inc_clocks(&b_moves);
+        Ok(())
+    }
+    // go to the position after ply k of the history (0: initial position)
+    fn goto_ply(&mut self, ply: usize) -> Result<(), String> {
+        let n_moves = self.history.moves().len();
+        if ply <= n_moves {
+            self.undo_moves(n_moves - ply)
+        } else {
+            self.redo_moves(ply - n_moves)
+        }
+    }
+    fn game_in_progress(&mut self) -> Result<&mut GameState, String> {
+        let game_state = self
+            .game_state_opt
+            .as_mut()
+            .ok_or("no position has been defined".to_string())?;
+        match game_state.end_game() {
+            EndGame::None => Ok(game_state),
+            end_game => Err(format!("the game is over: {:?}", end_game)),
+        }
+    }
+    fn resign(&mut self, color: square::Color) -> Result<(), String> {
+        self.game_in_progress()?
+            .set_end_game(EndGame::Resign(color));
+        self.draw_offer_opt = None;
+        Ok(())
+    }
+    // return true if the offer ends the game: both players have offered a draw
+    fn offer_draw(&mut self, color: square::Color) -> Result<bool, String> {
+        self.game_in_progress()?;
+        if self.draw_offer_opt == Some(color.switch()) {
+            self.accept_draw(color)?;
+            return Ok(true);
+        }
+        self.draw_offer_opt = Some(color);
+        Ok(false)
+    }
+    fn accept_draw(&mut self, color: square::Color) -> Result<(), String> {
+        if self.draw_offer_opt != Some(color.switch()) {
+            return Err(format!("no draw offer to {:?}", color));
+        }
+        self.game_in_progress()?
+            .set_end_game(EndGame::NullAgreement);
+        self.draw_offer_opt = None;
+        Ok(())
+    }
+    fn decline_draw(&mut self, color: square::Color) -> Result<(), String> {
+        if self.draw_offer_opt != Some(color.switch()) {
+            return Err(format!("no draw offer to {:?}", color));
+        }
+        self.draw_offer_opt = None;
+        Ok(())
+    }
+    // threefold repetition or fifty-move rule
+    fn claim_draw(&mut self) -> Result<EndGame, String> {
+        let game_state = self
+            .game_state_opt
+            .as_mut()
+            .ok_or("no position has been defined".to_string())?;
+        let end_game = game_state
+            .claimable_draw()
+            .ok_or("no draw can be claimed in this position".to_string())?;
+        match game_state.end_game() {
+            EndGame::None => game_state.set_end_game(end_game.clone()),
+            current if current == end_game => {}
+            current => return Err(format!("the game is over: {:?}", current)),
+        }
+        self.draw_offer_opt = None;
+        Ok(end_game)
+    }
+    // the engine of the player to move starts thinking and its clock is started
+    fn start_engine_thinking(
+        &self,
+        ctx: &mut Context<Self>,
+        uci_actor_opt: Option<uci_entity::UciActor>,
+        stat_actor_opt: Option<stat_entity::StatActor>,
+    ) -> Result<(), String> {
+        let game_state = self
+            .game_state_opt
+            .as_ref()
+            .ok_or("no position has been defined".to_string())?;
+        let color = game_state
+            .bit_position()
+            .bit_position_status()
+            .player_turn();
+        let engine_actor = self.players.get_engine(color)?;
+        let msg = dispatcher::handler_engine::EngineStartThinking::new(
+            game_state.clone(),
+            ctx.address(),
+            uci_actor_opt,
+            stat_actor_opt,
+        );
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "game_manager_actor forward message to engine_actor for color {:?}: {:?}",
+                color, msg
+            )));
+        }
+        engine_actor.do_send(msg);
+        if let (Some(white_clock_actor), Some(black_clock_actor)) =
+            (&self.white_clock_actor_opt, &self.black_clock_actor_opt)
+        {
+            start_or_switch_clock(
+                color,
+                white_clock_actor,
+                black_clock_actor,
+                self.debug_actor_opt.clone(),
+            );
+        }
+        Ok(())
+    }
     pub fn set_players(&mut self, players: player::Players) {
         self.players = players;
     }
 }
 
+fn start_or_switch_clock(
+    color: square::Color,
+    white_clock_actor: &chessclock::ClockActor,
+    black_clock_actor: &chessclock::ClockActor,
+    debug_actor_opt: Option<debug::DebugActor>,
+) {
+    match color {
+        square::Color::White => {
+            if let Some(debug_actor) = debug_actor_opt {
+                let msg = debug::AddMessage(
+                    "Send to black clock 'Pause' and to white clock 'Resume'".to_string(),
+                );
+                debug_actor.do_send(msg);
+            }
+            black_clock_actor.do_send(chessclock::handler_clock::PauseClock);
+            white_clock_actor.do_send(chessclock::handler_clock::ResumeClock);
+        }
+        square::Color::Black => {
+            if let Some(debug_actor) = debug_actor_opt {
+                let msg = debug::AddMessage(
+                    "Send to white clock 'Pause' and to black clock 'Resume'".to_string(),
+                );
+                debug_actor.do_send(msg);
+            }
+            black_clock_actor.do_send(chessclock::handler_clock::ResumeClock);
+            white_clock_actor.do_send(chessclock::handler_clock::PauseClock);
+        }
+    }
+}
+
+fn count_moves_by_color(b_moves: &[bitboard::BitBoardMove]) -> (u64, u64) {
+    let n_moves_white = b_moves
+        .iter()
+        .filter(|b_move| b_move.color() == square::Color::White)
+        .count() as u64;
+    (n_moves_white, b_moves.len() as u64 - n_moves_white)
+}
+
 fn async_clock_inc(debug_info: String, n_moves: u64, clock_actor: Addr<chessclock::Clock>) {
     use tokio::task;
 
@@ -203,15 +470,15 @@ pub async fn build_game_manager_actor(inputs: Vec<&str>) -> GameManagerActor {
     actix::clock::sleep(std::time::Duration::from_millis(100)).await;
     // define clocks
     let white_clock_actor =
-        chessclock::Clock::new("white", 3, 0, game_manager_actor.clone()).start();
+        chessclock::Clock::new("white", 3000, 0, game_manager_actor.clone()).start();
     let black_clock_actor =
-        chessclock::Clock::new("black", 3, 0, game_manager_actor.clone()).start();
+        chessclock::Clock::new("black", 3000, 0, game_manager_actor.clone()).start();
     game_manager_actor.do_send(handler_clock::SetClocks::new(
         Some(white_clock_actor),
         Some(black_clock_actor),
     ));
     // send clock to game
-    let set_clock_msg = handler_clock::SetClockRemainingTime::new(&square::Color::White, 2);
+    let set_clock_msg = handler_clock::SetClockRemainingTime::new(&square::Color::White, 2000);
     game_manager_actor.do_send(set_clock_msg);
     actix::clock::sleep(std::time::Duration::from_millis(100)).await;
     game_manager_actor
@@ -1056,9 +1323,9 @@ mod tests {
         let game_manager_actor = game_manager::GameManager::start(game_manager);
         // set the position from uci command
         let white_clock_actor =
-            chessclock::Clock::new("white", 3, 1, game_manager_actor.clone()).start();
+            chessclock::Clock::new("white", 3000, 1000, game_manager_actor.clone()).start();
         let black_clock_actor =
-            chessclock::Clock::new("black", 4, 2, game_manager_actor.clone()).start();
+            chessclock::Clock::new("black", 4000, 2000, game_manager_actor.clone()).start();
         game_manager_actor.do_send(game_manager::handler_clock::SetClocks::new(
             Some(white_clock_actor),
             Some(black_clock_actor),
@@ -1091,8 +1358,8 @@ mod tests {
             .await
             .unwrap()
             .unwrap();
-        assert_eq!(clock_white, 6);
-        assert_eq!(clock_black, 8);
+        assert_eq!(clock_white, 6000);
+        assert_eq!(clock_black, 8000);
     }
     #[actix::test]
     async fn test_game_timeout_no_material_gameover() {
@@ -1127,4 +1394,321 @@ mod tests {
             .unwrap();
         assert_eq!(end_game, game_state::EndGame::TimeOutDraw)
     }
+
+    #[actix::test]
+    async fn test_game_undo_redo() {
+        let game_manager_actor = GameManager::start(GameManager::new(None));
+        game_manager_actor
+            .send(game_manager::handler_uci_command::UciCommand::InitPosition)
+            .await
+            .expect("actix error")
+            .unwrap();
+        let moves: Vec<String> = "g1f3 g8f6 f3g1 f6g8 g1f3 g8f6 f3g1 f6g8"
+            .split_whitespace()
+            .map(|m| m.to_string())
+            .collect();
+        let moves = uci_entity::handler_event::moves_validation(&moves).unwrap();
+        game_manager_actor
+            .send(game_manager::handler_game::PlayMoves::new(moves))
+            .await
+            .expect("actix error")
+            .unwrap();
+        let game = get_game_state(&game_manager_actor).await.unwrap();
+        assert_eq!(game.end_game(), game_state::EndGame::Repetition3x);
+        // take back the last move: no more repetition
+        game_manager_actor
+            .send(game_manager::handler_game::UndoMove(1))
+            .await
+            .expect("actix error")
+            .unwrap();
+        let game = get_game_state(&game_manager_actor).await.unwrap();
+        assert_eq!(game.end_game(), game_state::EndGame::None);
+        game_manager_actor
+            .send(game_manager::handler_game::RedoMove)
+            .await
+            .expect("actix error")
+            .unwrap();
+        let game = get_game_state(&game_manager_actor).await.unwrap();
+        assert_eq!(game.end_game(), game_state::EndGame::Repetition3x);
+        // navigation in the history
+        game_manager_actor
+            .send(game_manager::handler_game::GotoPly(1))
+            .await
+            .expect("actix error")
+            .unwrap();
+        let game = get_game_state(&game_manager_actor).await.unwrap();
+        let fen = fen::Fen::encode(&game.bit_position().to()).unwrap();
+        assert_eq!(
+            fen,
+            "rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1"
+        );
+        let history = game_manager_actor
+            .send(game_manager::handler_game::GetHistory)
+            .await
+            .expect("actix error")
+            .unwrap();
+        assert_eq!(history.moves().len(), 1);
+        assert_eq!(history.n_undone_moves(), 7);
+        assert!(game_manager_actor
+            .send(game_manager::handler_game::GotoPly(9))
+            .await
+            .expect("actix error")
+            .is_err());
+        game_manager_actor
+            .send(game_manager::handler_game::GotoPly(8))
+            .await
+            .expect("actix error")
+            .unwrap();
+        let game = get_game_state(&game_manager_actor).await.unwrap();
+        assert_eq!(game.end_game(), game_state::EndGame::Repetition3x);
+    }
+
+    #[actix::test]
+    async fn test_game_stale_engine_move() {
+        let mut game_manager = GameManager::new(None);
+        game_manager.is_auto_play = true;
+        let game_manager_actor = game_manager.start();
+        game_manager_actor
+            .send(game_manager::handler_uci_command::UciCommand::InitPosition)
+            .await
+            .expect("actix error")
+            .unwrap();
+        let initial_hash = get_game_state(&game_manager_actor)
+            .await
+            .unwrap()
+            .last_hash();
+        let moves = uci_entity::handler_event::moves_validation(&vec!["e2e4".to_string()]).unwrap();
+        game_manager_actor
+            .send(game_manager::handler_game::PlayMoves::new(moves))
+            .await
+            .expect("actix error")
+            .unwrap();
+        let game = get_game_state(&game_manager_actor).await.unwrap();
+        let b_move = game.gen_moves()[0];
+        // the search started before e2e4: its move is dropped
+        game_manager_actor
+            .send(game_manager::handler_engine::EngineMoveFound(
+                b_move,
+                initial_hash,
+            ))
+            .await
+            .expect("actix error");
+        let history = game_manager_actor
+            .send(game_manager::handler_game::GetHistory)
+            .await
+            .expect("actix error")
+            .unwrap();
+        assert_eq!(history.moves().len(), 1);
+        game_manager_actor
+            .send(game_manager::handler_engine::EngineMoveFound(
+                b_move,
+                game.last_hash(),
+            ))
+            .await
+            .expect("actix error");
+        let history = game_manager_actor
+            .send(game_manager::handler_game::GetHistory)
+            .await
+            .expect("actix error")
+            .unwrap();
+        assert_eq!(history.moves().len(), 2);
+    }
+
+    #[actix::test]
+    async fn test_game_hint() {
+        use crate::entity::engine::component::config::config;
+        use crate::entity::engine::component::engine_iddfs;
+
+        let mut game_manager = GameManager::new(None);
+        let conf = config::IDDFSConfig::new(
+            3,
+            config::IddfsFeatureConf::default(),
+            config::AlphabetaFeatureConf::default(),
+        );
+        let engine = engine_iddfs::EngineIddfs::new(None, game_manager.zobrist_table(), &conf);
+        let engine_dispatcher = dispatcher::EngineDispatcher::new(Arc::new(engine), None, None);
+        let player1 = player::Player::Human {
+            engine_opt: Some(engine_dispatcher.start()),
+        };
+        let player2 = player::Player::Human { engine_opt: None };
+        game_manager.set_players(player::Players::new(player1, player2));
+        let game_manager_actor = game_manager.start();
+        let fen_str = "4k3/8/8/3q4/8/4N3/8/R3K3 w - - 0 1";
+        let position = fen::Fen::decode(fen_str).expect("Failed to decode FEN");
+        game_manager_actor
+            .send(game_manager::handler_uci_command::UciCommand::UpdatePosition(
+                fen_str.to_string(),
+                position,
+            ))
+            .await
+            .expect("actix error")
+            .unwrap();
+        let hint = game_manager_actor
+            .send(game_manager::handler_engine::RequestHint::new(5000))
+            .await
+            .expect("actix error")
+            .unwrap();
+        assert_eq!(hint.san, "Nxd5");
+        assert_eq!(hint.pv.first(), Some(&"Nxd5".to_string()));
+        assert!(hint.score > 0);
+        // the hint does not play the move
+        let game = get_game_state(&game_manager_actor).await.unwrap();
+        assert_eq!(
+            fen::Fen::encode(&game.bit_position().to()).unwrap(),
+            fen_str
+        );
+    }
+
+    #[actix::test]
+    async fn test_game_resign_and_draw() {
+        let game_manager_actor = GameManager::start(GameManager::new(None));
+        game_manager_actor
+            .send(game_manager::handler_uci_command::UciCommand::InitPosition)
+            .await
+            .expect("actix error")
+            .unwrap();
+        // no pending offer
+        assert!(game_manager_actor
+            .send(game_manager::handler_game::AcceptDraw(square::Color::Black))
+            .await
+            .expect("actix error")
+            .is_err());
+        game_manager_actor
+            .send(game_manager::handler_game::OfferDraw(square::Color::White))
+            .await
+            .expect("actix error")
+            .unwrap();
+        // a move of the opponent declines the offer
+        let moves = vec!["e2e4".to_string(), "e7e5".to_string()];
+        let moves = uci_entity::handler_event::moves_validation(&moves).unwrap();
+        game_manager_actor
+            .send(game_manager::handler_game::PlayMoves::new(moves))
+            .await
+            .expect("actix error")
+            .unwrap();
+        assert!(game_manager_actor
+            .send(game_manager::handler_game::AcceptDraw(square::Color::Black))
+            .await
+            .expect("actix error")
+            .is_err());
+        // no repetition, no fifty-move rule
+        assert!(game_manager_actor
+            .send(game_manager::handler_game::ClaimDraw)
+            .await
+            .expect("actix error")
+            .is_err());
+        game_manager_actor
+            .send(game_manager::handler_game::OfferDraw(square::Color::White))
+            .await
+            .expect("actix error")
+            .unwrap();
+        game_manager_actor
+            .send(game_manager::handler_game::AcceptDraw(square::Color::Black))
+            .await
+            .expect("actix error")
+            .unwrap();
+        let game = get_game_state(&game_manager_actor).await.unwrap();
+        assert_eq!(game.end_game(), game_state::EndGame::NullAgreement);
+        // the game is over
+        assert!(game_manager_actor
+            .send(game_manager::handler_game::Resign(square::Color::White))
+            .await
+            .expect("actix error")
+            .is_err());
+        game_manager_actor
+            .send(game_manager::handler_game::UndoMove(1))
+            .await
+            .expect("actix error")
+            .unwrap();
+        game_manager_actor
+            .send(game_manager::handler_game::Resign(square::Color::Black))
+            .await
+            .expect("actix error")
+            .unwrap();
+        let game = get_game_state(&game_manager_actor).await.unwrap();
+        assert_eq!(
+            game.end_game(),
+            game_state::EndGame::Resign(square::Color::Black)
+        );
+    }
+
+    #[actix::test]
+    async fn test_game_claim_draw() {
+        let game_manager_actor = GameManager::start(GameManager::new(None));
+        let fen_str = "4k3/8/8/8/8/8/4P3/4K3 w - - 99 80";
+        let position = fen::Fen::decode(fen_str).expect("Failed to decode FEN");
+        game_manager_actor
+            .send(game_manager::handler_uci_command::UciCommand::UpdatePosition(
+                fen_str.to_string(),
+                position,
+            ))
+            .await
+            .expect("actix error")
+            .unwrap();
+        assert!(game_manager_actor
+            .send(game_manager::handler_game::ClaimDraw)
+            .await
+            .expect("actix error")
+            .is_err());
+        let moves = vec!["e1d1".to_string()];
+        let moves = uci_entity::handler_event::moves_validation(&moves).unwrap();
+        game_manager_actor
+            .send(game_manager::handler_game::PlayMoves::new(moves))
+            .await
+            .expect("actix error")
+            .unwrap();
+        let end_game = game_manager_actor
+            .send(game_manager::handler_game::ClaimDraw)
+            .await
+            .expect("actix error")
+            .unwrap();
+        assert_eq!(end_game, game_state::EndGame::NoPawnAndCapturex50);
+    }
+
+    #[actix::test]
+    async fn test_engine_accepts_draw() {
+        use crate::entity::engine::component::adjudication;
+
+        let mut game_manager = GameManager::new(None);
+        let engine = dummy::EngineDummy::new(None);
+        let engine_actor = dispatcher::EngineDispatcher::new(Arc::new(engine), None, None).start();
+        let player1 = player::Player::Human { engine_opt: None };
+        let player2 = player::Player::Computer {
+            engine: engine_actor.clone(),
+        };
+        game_manager.set_players(player::Players::new(player1, player2));
+        let game_manager_actor = game_manager.start();
+        game_manager_actor
+            .send(game_manager::handler_uci_command::UciCommand::InitPosition)
+            .await
+            .expect("actix error")
+            .unwrap();
+        let conf = adjudication::AdjudicationConf {
+            resign_opt: None,
+            draw_opt: Some(adjudication::DrawRule {
+                score: 100,
+                min_move_number: 1,
+            }),
+        };
+        engine_actor
+            .send(dispatcher::handler_engine::EngineSetAdjudication(conf))
+            .await
+            .expect("actix error");
+        engine_actor
+            .send(dispatcher::handler_engine::EngineSearchScore::new(
+                square::Color::Black,
+                20,
+                1,
+            ))
+            .await
+            .expect("actix error");
+        game_manager_actor
+            .send(game_manager::handler_game::OfferDraw(square::Color::White))
+            .await
+            .expect("actix error")
+            .unwrap();
+        actix::clock::sleep(Duration::from_millis(50)).await;
+        let game = get_game_state(&game_manager_actor).await.unwrap();
+        assert_eq!(game.end_game(), game_state::EndGame::NullAgreement);
+    }
 }
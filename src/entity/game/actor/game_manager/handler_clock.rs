@@ -3,6 +3,7 @@ use actix::{Context, Handler, Message, ResponseFuture};
 use crate::{
     entity::{
         clock::actor::chessclock,
+        engine::component::time_allocation,
         game::component::{
             game_state,
             square::{self, Switch},
@@ -112,6 +113,66 @@ impl Handler<GetClockRemainingTime> for GameManager {
     }
 }
 
+// Clock data needed by the time manager of the engine
+#[derive(Debug, Message)]
+#[rtype(result = "Option<time_allocation::TimeInfo>")]
+pub struct GetClockTimeInfo(square::Color);
+
+impl GetClockTimeInfo {
+    pub fn new(color: square::Color) -> Self {
+        GetClockTimeInfo(color)
+    }
+}
+
+impl Handler<GetClockTimeInfo> for GameManager {
+    type Result = ResponseFuture<Option<time_allocation::TimeInfo>>;
+
+    fn handle(&mut self, msg: GetClockTimeInfo, _ctx: &mut Self::Context) -> Self::Result {
+        let span = span_debug();
+        let _enter = span.enter();
+        tracing::debug!("Clock receive msg {:?}", msg);
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "game_manager_actor receive {:?}",
+                msg
+            )));
+        }
+        let (clock_actor_opt, opponent_clock_actor_opt) = match msg.0 {
+            square::Color::White => (
+                self.white_clock_actor_opt.clone(),
+                self.black_clock_actor_opt.clone(),
+            ),
+            square::Color::Black => (
+                self.black_clock_actor_opt.clone(),
+                self.white_clock_actor_opt.clone(),
+            ),
+        };
+        // uci 'movestogo' overrides the time control of the clock
+        let moves_to_go_opt = self.parameters.moves_to_go();
+        let move_overhead_ms = self.parameters.move_overhead_ms();
+        Box::pin(async move {
+            let mut time_info = clock_actor_opt?
+                .send(chessclock::handler_clock::GetTimeInfo)
+                .await
+                .ok()?;
+            if moves_to_go_opt.is_some() {
+                time_info.moves_to_go = moves_to_go_opt;
+            }
+            time_info.move_overhead_ms = move_overhead_ms;
+            if let Some(opponent_clock_actor) = opponent_clock_actor_opt {
+                if let Ok(opponent_time_info) = opponent_clock_actor
+                    .send(chessclock::handler_clock::GetTimeInfo)
+                    .await
+                {
+                    time_info.opponent_remaining_ms_opt = Some(opponent_time_info.remaining_ms);
+                    time_info.opponent_inc_ms = opponent_time_info.inc_ms;
+                }
+            }
+            Some(time_info)
+        })
+    }
+}
+
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct SetClockRemainingTime {
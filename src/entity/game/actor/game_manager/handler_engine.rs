@@ -1,8 +1,14 @@
-use actix::{Addr, Handler, Message};
+use std::fmt;
+
+use actix::{Addr, Handler, Message, ResponseFuture};
 
 use crate::entity::engine::actor::engine_dispatcher as dispatcher;
+use crate::entity::engine::component::evaluation::score;
+use crate::entity::game::component::bitboard::{self, zobrist};
+use crate::entity::game::component::{game_state, square};
 use crate::entity::uci::actor::uci_entity;
 use crate::monitoring::debug;
+use crate::ui::notation::{long_notation, san};
 
 use super::GameManager;
 
@@ -25,16 +31,15 @@ impl Handler<GetCurrentEngineAsync> for GameManager {
                 "game_manager_actor receive GetCurrentEngineAsync".to_string(),
             ));
         }
-        if let Some(game_state) = &self.game_state_opt {
-            let bitboard_position = game_state.bit_position();
-            let bit_position_status = bitboard_position.bit_position_status();
-            let color = bit_position_status.player_turn();
-            let engine_actor_opt = self.players.get_player_into(color).get_engine().cloned();
-            if let Some(engine_actor) = engine_actor_opt {
-                let reply =
-                    dispatcher::handler_engine::EngineGetIdAsync::new(msg.uci_caller.clone());
-                engine_actor.do_send(reply);
-            }
+        // 'uci' is sent before any position: white engine by default
+        let color = match &self.game_state_opt {
+            Some(game_state) => game_state.bit_position().bit_position_status().player_turn(),
+            None => square::Color::White,
+        };
+        let engine_actor_opt = self.players.get_player_into(color).get_engine().cloned();
+        if let Some(engine_actor) = engine_actor_opt {
+            let reply = dispatcher::handler_engine::EngineGetIdAsync::new(msg.uci_caller.clone());
+            engine_actor.do_send(reply);
         }
     }
 }
@@ -65,3 +70,206 @@ impl Handler<GetCurrentEngine> for GameManager {
         }
     }
 }
+
+const HINT_PV_LENGTH: usize = 4;
+
+// Suggested move for a human player
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub san: String,
+    pub score: i32,       // white perspective (1000 = 1 pawn)
+    pub pv: Vec<String>, // short variant in SAN, starting with the suggested move
+}
+impl Hint {
+    fn build(
+        b_move_score: &score::BitboardMoveScore,
+        game: &game_state::GameState,
+        zobrist_table: &zobrist::Zobrist,
+    ) -> Self {
+        let best_move = long_notation::LongAlgebricNotationMove::build_from_b_move(
+            *b_move_score.bitboard_move(),
+        );
+        let variant = b_move_score.get_variant();
+        let mut moves: Vec<&str> = variant.split_whitespace().collect();
+        let best_move_str = best_move.cast();
+        if moves.first() != Some(&best_move_str.as_str()) {
+            moves = vec![best_move_str.as_str()];
+        }
+        let mut game_clone = game.clone();
+        let mut pv = vec![];
+        for move_str in moves.into_iter().take(HINT_PV_LENGTH) {
+            let Ok(long_move) = long_notation::LongAlgebricNotationMove::build_from_str(move_str)
+            else {
+                break;
+            };
+            let legal_moves = game_clone.gen_moves();
+            let Some(b_move) = legal_moves.iter().find(|m| {
+                long_notation::LongAlgebricNotationMove::build_from_b_move(**m) == long_move
+            }) else {
+                break;
+            };
+            let san_move = san::san_to_str(
+                b_move,
+                &legal_moves,
+                &san::Lang::LangEn,
+                &game_clone,
+                zobrist_table,
+                false,
+            );
+            pv.push(san_move.info().clone());
+            game_clone
+                .play_moves(&[long_move], zobrist_table, None, false)
+                .unwrap();
+        }
+        Hint {
+            san: pv.first().cloned().unwrap_or(best_move_str),
+            score: b_move_score.score().value(),
+            pv,
+        }
+    }
+}
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({:+.2}) {}",
+            self.san,
+            self.score as f64 / 1000.0,
+            self.pv.join(" ")
+        )
+    }
+}
+
+// Ask the engine of the human player to move for a hint.
+// The move is not played and the clocks are not touched.
+#[derive(Debug, Message)]
+#[rtype(result = "Result<Hint, String>")]
+pub struct RequestHint {
+    max_time_ms: u64,
+}
+impl RequestHint {
+    pub fn new(max_time_ms: u64) -> Self {
+        Self { max_time_ms }
+    }
+}
+
+impl Handler<RequestHint> for GameManager {
+    type Result = ResponseFuture<Result<Hint, String>>;
+
+    fn handle(&mut self, msg: RequestHint, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "game_manager_actor receive {:?}",
+                msg
+            )));
+        }
+        let Some(game_state) = self.game_state_opt.clone() else {
+            return Box::pin(async { Err("No position has been defined".to_string()) });
+        };
+        let color = game_state.bit_position().bit_position_status().player_turn();
+        let engine_actor = match self.players.get_hint_engine(color) {
+            Ok(engine_actor) => engine_actor.clone(),
+            Err(err) => return Box::pin(async { Err(err) }),
+        };
+        if game_state.end_game() != game_state::EndGame::None {
+            return Box::pin(async { Err("The game is over".to_string()) });
+        }
+        let zobrist_table = self.zobrist_table.clone();
+        Box::pin(async move {
+            let b_move_score_opt = engine_actor
+                .send(dispatcher::handler_engine::EngineHint::new(
+                    game_state.clone(),
+                    msg.max_time_ms,
+                ))
+                .await
+                .map_err(|err| format!("{:?}", err))?;
+            b_move_score_opt
+                .map(|b_move_score| Hint::build(&b_move_score, &game_state, &zobrist_table))
+                .ok_or("The engine cannot give a hint".to_string())
+        })
+    }
+}
+
+// The engines play the game on their own: the best move found at the end of an analysis
+// is played and the engine of the opponent starts thinking (engine vs engine games)
+#[derive(Debug, Message)]
+#[rtype(result = "Result<(), String>")]
+pub struct StartAutoPlay;
+
+impl Handler<StartAutoPlay> for GameManager {
+    type Result = Result<(), String>;
+
+    fn handle(&mut self, msg: StartAutoPlay, ctx: &mut Self::Context) -> Self::Result {
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "game_manager_actor receive {:?}",
+                msg
+            )));
+        }
+        for color in [square::Color::White, square::Color::Black] {
+            if self.players.get_computer_engine(color).is_none() {
+                return Err(format!("Player {:?} is not a computer", color));
+            }
+        }
+        self.game_in_progress()?;
+        self.is_auto_play = true;
+        self.start_engine_thinking(ctx, None, None)
+    }
+}
+
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct StopAutoPlay;
+
+impl Handler<StopAutoPlay> for GameManager {
+    type Result = ();
+
+    fn handle(&mut self, msg: StopAutoPlay, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "game_manager_actor receive {:?}",
+                msg
+            )));
+        }
+        self.stop_engines();
+    }
+}
+
+// Best move of an engine at the end of its analysis, with the hash of the position searched
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct EngineMoveFound(pub bitboard::BitBoardMove, pub zobrist::ZobristHash);
+
+impl Handler<EngineMoveFound> for GameManager {
+    type Result = ();
+
+    fn handle(&mut self, msg: EngineMoveFound, ctx: &mut Self::Context) -> Self::Result {
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "game_manager_actor receive {:?}",
+                msg
+            )));
+        }
+        if !self.is_auto_play {
+            return;
+        }
+        // the game may be over (resignation, timeout) or the move out of date
+        // (the position has changed since the search started)
+        match self.game_in_progress() {
+            Ok(game_state) if game_state.last_hash() == msg.1 => {}
+            _ => return,
+        }
+        let result = self
+            .play_moves(vec![
+                long_notation::LongAlgebricNotationMove::build_from_b_move(msg.0),
+            ])
+            .and_then(|_| match self.game_in_progress() {
+                Ok(_) => self.start_engine_thinking(ctx, None, None),
+                Err(_) => Ok(()),
+            });
+        if let Err(err) = result {
+            tracing::error!("auto play stopped: {}", err);
+            self.is_auto_play = false;
+        }
+    }
+}
@@ -1,9 +1,15 @@
-use actix::{ActorContext, Handler, Message};
+use actix::{ActorContext, AsyncContext, Handler, Message};
 
 use crate::{
     entity::{
-        engine::component::{ts_best_move, ts_bitboard_move},
-        game::component::{bitboard, game_state, parameters},
+        engine::{
+            actor::engine_dispatcher as dispatcher,
+            component::{ts_best_move, ts_bitboard_move},
+        },
+        game::component::{
+            bitboard, game_state, parameters,
+            square::{self, Switch},
+        },
     },
     monitoring::debug,
     ui::notation::long_notation,
@@ -164,6 +170,174 @@ impl Handler<PlayMoves> for GameManager {
     }
 }
 
+// Take back the n last moves
+#[derive(Debug, Message)]
+#[rtype(result = "Result<(), String>")]
+pub struct UndoMove(pub usize);
+
+impl Handler<UndoMove> for GameManager {
+    type Result = Result<(), String>;
+
+    fn handle(&mut self, msg: UndoMove, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "game_manager_actor receive {:?}",
+                msg
+            )));
+        }
+        self.undo_moves(msg.0)
+    }
+}
+
+// Play again the last move taken back
+#[derive(Debug, Message)]
+#[rtype(result = "Result<(), String>")]
+pub struct RedoMove;
+
+impl Handler<RedoMove> for GameManager {
+    type Result = Result<(), String>;
+
+    fn handle(&mut self, msg: RedoMove, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "game_manager_actor receive {:?}",
+                msg
+            )));
+        }
+        self.redo_moves(1)
+    }
+}
+
+// Go to the position after k plies (moves played or taken back)
+#[derive(Debug, Message)]
+#[rtype(result = "Result<(), String>")]
+pub struct GotoPly(pub usize);
+
+impl Handler<GotoPly> for GameManager {
+    type Result = Result<(), String>;
+
+    fn handle(&mut self, msg: GotoPly, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "game_manager_actor receive {:?}",
+                msg
+            )));
+        }
+        self.goto_ply(msg.0)
+    }
+}
+
+// The player of this color resigns
+#[derive(Debug, Message)]
+#[rtype(result = "Result<(), String>")]
+pub struct Resign(pub square::Color);
+
+impl Handler<Resign> for GameManager {
+    type Result = Result<(), String>;
+
+    fn handle(&mut self, msg: Resign, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "game_manager_actor receive {:?}",
+                msg
+            )));
+        }
+        self.resign(msg.0)
+    }
+}
+
+// The player of this color offers a draw. An engine opponent answers
+// with AcceptDraw or DeclineDraw, a human opponent by a move or a message.
+#[derive(Debug, Message)]
+#[rtype(result = "Result<(), String>")]
+pub struct OfferDraw(pub square::Color);
+
+impl Handler<OfferDraw> for GameManager {
+    type Result = Result<(), String>;
+
+    fn handle(&mut self, msg: OfferDraw, ctx: &mut Self::Context) -> Self::Result {
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "game_manager_actor receive {:?}",
+                msg
+            )));
+        }
+        let is_agreement = self.offer_draw(msg.0)?;
+        let opponent = msg.0.switch();
+        if !is_agreement {
+            if let Some(engine) = self.players.get_computer_engine(opponent) {
+                let move_number = self
+                    .game_state()
+                    .map(|game_state| game_state.bit_position().bit_position_status().n_moves())
+                    .unwrap_or_default() as u32;
+                engine.do_send(dispatcher::handler_engine::EngineDrawOffer::new(
+                    opponent,
+                    move_number,
+                    ctx.address(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+// The player of this color accepts the draw offer of the opponent
+#[derive(Debug, Message)]
+#[rtype(result = "Result<(), String>")]
+pub struct AcceptDraw(pub square::Color);
+
+impl Handler<AcceptDraw> for GameManager {
+    type Result = Result<(), String>;
+
+    fn handle(&mut self, msg: AcceptDraw, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "game_manager_actor receive {:?}",
+                msg
+            )));
+        }
+        self.accept_draw(msg.0)
+    }
+}
+
+// The player of this color declines the draw offer of the opponent
+#[derive(Debug, Message)]
+#[rtype(result = "Result<(), String>")]
+pub struct DeclineDraw(pub square::Color);
+
+impl Handler<DeclineDraw> for GameManager {
+    type Result = Result<(), String>;
+
+    fn handle(&mut self, msg: DeclineDraw, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "game_manager_actor receive {:?}",
+                msg
+            )));
+        }
+        self.decline_draw(msg.0)
+    }
+}
+
+// Claim a draw by threefold repetition or by the fifty-move rule
+#[derive(Debug, Message)]
+#[rtype(result = "Result<game_state::EndGame, String>")]
+pub struct ClaimDraw;
+
+impl Handler<ClaimDraw> for GameManager {
+    type Result = Result<game_state::EndGame, String>;
+
+    fn handle(&mut self, msg: ClaimDraw, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(debug_actor) = &self.debug_actor_opt {
+            debug_actor.do_send(debug::AddMessage(format!(
+                "game_manager_actor receive {:?}",
+                msg
+            )));
+        }
+        self.claim_draw()
+    }
+}
+
 #[derive(Debug, Message)]
 #[rtype(result = "Result<super::History, ()>")]
 pub struct GetHistory;
@@ -1,12 +1,12 @@
 use actix::{
     dev::ContextFutureSpawner, ActorFutureExt, AsyncContext, Handler, Message, WrapFuture,
 };
-use actix::{Actor, Addr};
+use actix::Actor;
 
 use crate::entity::engine::actor::engine_dispatcher as dispatcher;
 use crate::entity::engine::component::ts_bitboard_move;
 use crate::entity::game::component::bitboard::piece_move::GenMoves;
-use crate::entity::game::component::square::{self, Switch};
+use crate::entity::game::component::square::Switch;
 use crate::entity::stat::actor::stat_entity;
 use crate::entity::uci::actor::uci_entity;
 use crate::{
@@ -46,6 +46,8 @@ pub enum UciCommand {
     },
     InitPosition,                                              // Set starting position
     MaxTimePerMoveInMs(u32),                                   // Set maximum time per move
+    MoveOverhead(u64),                                         // Latency budget of the GUI (ms)
+    MovesToGo(u32),                                            // Moves before the next time control
     SearchMoves(Vec<long_notation::LongAlgebricNotationMove>), // Focus on a list of moves for analysis
     SearchInfinite,                                            // Set infinite search
     UpdatePosition(String, fen::Position),                     // Set a new position
@@ -76,7 +78,7 @@ impl Handler<UciCommand> for GameManager {
                 match &self.black_clock_actor_opt {
                     None => {
                         let black_clock_actor =
-                            chessclock::Clock::new("white", time, 0, ctx.address()).start();
+                            chessclock::Clock::new("black", time, 0, ctx.address()).start();
                         ctx.address()
                             .do_send(handler_clock::SetClocks::new(None, Some(black_clock_actor)));
                     }
@@ -144,6 +146,12 @@ impl Handler<UciCommand> for GameManager {
             UciCommand::MaxTimePerMoveInMs(time) => {
                 self.parameters.set_time_per_move_in_ms(time);
             }
+            UciCommand::MoveOverhead(move_overhead) => {
+                self.parameters.set_move_overhead_ms(move_overhead);
+            }
+            UciCommand::MovesToGo(moves_to_go) => {
+                self.parameters.set_moves_to_go(moves_to_go);
+            }
             UciCommand::UpdatePosition(fen, position) => {
                 let mut game_state = game_state::GameState::new(position, &self.zobrist_table);
                 let color = &game_state
@@ -195,40 +203,8 @@ impl Handler<UciCommand> for GameManager {
                 uci_actor,
                 stat_actor_opt,
             } => {
-                if let Some(ref game_state) = &self.game_state_opt {
-                    let color = game_state
-                        .bit_position()
-                        .bit_position_status()
-                        .player_turn();
-                    let engine_actor_or_error = self.players.get_engine(color);
-                    match engine_actor_or_error {
-                        Ok(engine_actor) => {
-                            let msg = dispatcher::handler_engine::EngineStartThinking::new(
-                                game_state.clone(),
-                                ctx.address().clone(),
-                                uci_actor,
-                                stat_actor_opt,
-                            );
-                            if let Some(debug_actor) = &self.debug_actor_opt {
-                                debug_actor.do_send(debug::AddMessage(format!(
-                                    "game_manager_actor forward message to engine_actor for color {:?}: {:?}", color,
-                                    msg
-                                )));
-                            }
-                            engine_actor.do_send(msg);
-                            if let (Some(white_clock_actor), Some(black_clock_actor)) =
-                                (&self.white_clock_actor_opt, &self.black_clock_actor_opt)
-                            {
-                                start_or_switch_clock(
-                                    color,
-                                    white_clock_actor,
-                                    black_clock_actor,
-                                    self.debug_actor_opt.clone(),
-                                );
-                            }
-                        }
-                        Err(err) => result = Err(err),
-                    }
+                if self.game_state_opt.is_some() {
+                    result = self.start_engine_thinking(ctx, Some(uci_actor), stat_actor_opt);
                 }
             }
             UciCommand::CleanResources => {
@@ -337,33 +313,3 @@ impl Handler<UciCommand> for GameManager {
         result
     }
 }
-
-fn start_or_switch_clock(
-    color: square::Color,
-    white_clock_actor: &chessclock::ClockActor,
-    black_clock_actor: &chessclock::ClockActor,
-    debug_actor_opt: Option<Addr<debug::DebugEntity>>,
-) {
-    match color {
-        square::Color::White => {
-            if let Some(debug_actor) = debug_actor_opt {
-                let msg = debug::AddMessage(
-                    "Send to black clock 'Pause' and to white clock 'Resume'".to_string(),
-                );
-                debug_actor.do_send(msg);
-            }
-            black_clock_actor.do_send(chessclock::handler_clock::PauseClock);
-            white_clock_actor.do_send(chessclock::handler_clock::ResumeClock);
-        }
-        square::Color::Black => {
-            if let Some(debug_actor) = debug_actor_opt {
-                let msg = debug::AddMessage(
-                    "Send to white clock 'Pause' and to black clock 'Resume'".to_string(),
-                );
-                debug_actor.do_send(msg);
-            }
-            black_clock_actor.do_send(chessclock::handler_clock::ResumeClock);
-            white_clock_actor.do_send(chessclock::handler_clock::PauseClock);
-        }
-    }
-}
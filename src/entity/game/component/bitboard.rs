@@ -63,6 +63,10 @@ impl BitBoardMove {
     pub fn promotion(&self) -> Option<TypePiecePromotion> {
         self.promotion
     }
+    pub fn pack(&self) -> PackedMove {
+        let promotion = self.promotion.map_or(0, |promotion| promotion as u16 + 1);
+        PackedMove(self.start.0 as u16 | (self.end.0 as u16) << 6 | promotion << 12)
+    }
     pub fn is_capture_en_passant(&self) -> bool {
         self.type_piece == TypePiece::Pawn
             && self.capture.is_none()
@@ -151,6 +155,51 @@ impl BitBoardMove {
     }
 }
 
+// start square (6 bits), end square (6 bits) and promotion (3 bits, 0 if none)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PackedMove(u16);
+impl PackedMove {
+    pub fn start(&self) -> BitIndex {
+        BitIndex((self.0 & 0x3f) as u8)
+    }
+    pub fn end(&self) -> BitIndex {
+        BitIndex((self.0 >> 6 & 0x3f) as u8)
+    }
+    pub fn promotion(&self) -> Option<TypePiecePromotion> {
+        match self.0 >> 12 {
+            0 => None,
+            1 => Some(TypePiecePromotion::Rook),
+            2 => Some(TypePiecePromotion::Knight),
+            3 => Some(TypePiecePromotion::Bishop),
+            _ => Some(TypePiecePromotion::Queen),
+        }
+    }
+    pub fn value(&self) -> u16 {
+        self.0
+    }
+}
+
+// what is needed to take back a move: the moved and captured pieces and the status before the move
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UndoMove {
+    packed_move: PackedMove,
+    type_piece: TypePiece,
+    capture: Option<TypePiece>,
+    bit_position_status: BitPositionStatus,
+}
+impl UndoMove {
+    pub fn b_move(&self) -> BitBoardMove {
+        BitBoardMove::new(
+            self.bit_position_status.player_turn(),
+            self.type_piece,
+            self.packed_move.start(),
+            self.packed_move.end(),
+            self.capture,
+            self.packed_move.promotion(),
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BitPosition {
     bit_boards_white_and_black: BitBoardsWhiteAndBlack,
@@ -158,21 +207,6 @@ pub struct BitPosition {
     hash_positions: zobrist::ZobristHistory,
 }
 impl BitPosition {
-    pub fn play_back(
-        &mut self,
-        bit_position_status: BitPositionStatus,
-        bitboards_white_and_black_mask: BitBoardsWhiteAndBlack,
-    ) {
-        self.bit_position_status = bit_position_status;
-        self.bit_boards_white_and_black
-            .xor_mut(bitboards_white_and_black_mask);
-    }
-    pub fn tuple(&self) -> (BitBoardsWhiteAndBlack, BitPositionStatus) {
-        (
-            self.bit_boards_white_and_black.clone(),
-            self.bit_position_status,
-        )
-    }
     pub fn change_side(&mut self) {
         self.bit_position_status.change_side();
     }
@@ -208,11 +242,42 @@ impl BitPosition {
                 .pawns
                 .bitboard(),
         };
-        self.bit_position_status =
+        let bit_position_status =
             update_status(b_move, bit_board_pawn_opponent, self.bit_position_status);
+        *hash = hash.xor_status(
+            &self.bit_position_status,
+            &bit_position_status,
+            zobrist_table,
+        );
+        self.bit_position_status = bit_position_status;
         // update hash with change of color
         *hash = hash.xor_player_turn(zobrist_table);
     }
+    // play the move and return what is needed to take it back
+    pub fn make_move(
+        &mut self,
+        b_move: &BitBoardMove,
+        hash: &mut zobrist::ZobristHash,
+        zobrist_table: &zobrist::Zobrist,
+    ) -> UndoMove {
+        let undo_move = UndoMove {
+            packed_move: b_move.pack(),
+            type_piece: b_move.type_piece,
+            capture: b_move.capture,
+            bit_position_status: self.bit_position_status,
+        };
+        self.move_piece(b_move, hash, zobrist_table);
+        undo_move
+    }
+    // the pieces are moved with xor: playing the same move again takes it back
+    pub fn unmake_move(&mut self, undo_move: &UndoMove) {
+        self.bit_boards_white_and_black.move_piece(
+            &undo_move.b_move(),
+            &mut zobrist::ZobristHash::default(),
+            None,
+        );
+        self.bit_position_status = undo_move.bit_position_status;
+    }
     pub fn bit_boards_white_and_black(&self) -> &BitBoardsWhiteAndBlack {
         &self.bit_boards_white_and_black
     }
@@ -427,17 +492,13 @@ impl BitBoardsWhiteAndBlack {
         };
         match b_move.check_castle() {
             Some(Castle::Short) => {
+                // the hash of the rook is updated by the recursive call
                 let b_move = BitBoardMove {
                     type_piece: TypePiece::Rook,
                     start: b_move.end().right(),
                     end: b_move.end().left(),
                     ..*b_move
                 };
-                if let Some(zobrist_table) = zobrist_table_opt {
-                    let piece = square::Piece::new(b_move.type_piece, b_move.color);
-                    *hash = hash.xor_piece(zobrist_table, piece, b_move.start.value() as usize);
-                    *hash = hash.xor_piece(zobrist_table, piece, b_move.end.value() as usize);
-                }
                 self.move_piece(&b_move, hash, zobrist_table_opt)
             }
             Some(Castle::Long) => {
@@ -447,11 +508,6 @@ impl BitBoardsWhiteAndBlack {
                     end: b_move.end().right(),
                     ..*b_move
                 };
-                if let Some(zobrist_table) = zobrist_table_opt {
-                    let piece = square::Piece::new(b_move.type_piece, b_move.color);
-                    *hash = hash.xor_piece(zobrist_table, piece, b_move.start.value() as usize);
-                    *hash = hash.xor_piece(zobrist_table, piece, b_move.end.value() as usize);
-                }
                 self.move_piece(&b_move, hash, zobrist_table_opt)
             }
             None => {}
@@ -536,13 +592,14 @@ impl BitIndex {
         indexes.iter().map(|idx| Self::new(*idx)).collect()
     }
     pub fn direction(&self, to: BitIndex) -> Direction {
-        let index = (to.0 as i8 - self.0 as i8).abs();
-        match index {
-            _ if index % 8 == 0 => Direction::RookVertical,
-            _ if index % 9 == 0 => Direction::BishopBottomLeftTopRight,
-            // must be tested at the end because 56 = 8 x 7, 63 = 9 x 7
-            _ if index % 7 == 0 => Direction::BishopTopLeftBottomRight,
-            _ if to.row() == self.row() => Direction::RookHorizontal,
+        // compare rows and cols: an index difference of 7 or 9 can wrap around the board
+        let delta_row = to.row() as i8 - self.row() as i8;
+        let delta_col = to.col() as i8 - self.col() as i8;
+        match (delta_row, delta_col) {
+            (_, 0) => Direction::RookVertical,
+            (0, _) => Direction::RookHorizontal,
+            _ if delta_row == delta_col => Direction::BishopBottomLeftTopRight,
+            _ if delta_row == -delta_col => Direction::BishopTopLeftBottomRight,
             _ => Direction::None,
         }
     }
@@ -605,8 +662,9 @@ impl BitBoard {
     pub fn xor(&self, bitboard: &BitBoard) -> BitBoard {
         *self ^ *bitboard
     }
+    // xor only so that a move can be taken back by playing it again
     fn switch(&mut self, mask_switch: BitBoard, mask_promotion: BitBoard) {
-        *self = (*self ^ mask_switch) | mask_promotion
+        *self = *self ^ mask_switch ^ mask_promotion
     }
     pub fn iter(&self) -> BitIterator {
         BitIterator {
@@ -1391,6 +1449,32 @@ mod tests {
         assert_eq!(coords[0], coord::Coord::from('A', 8).unwrap());
     }
 
+    #[test]
+    fn test_packed_move() {
+        let b_move = BitBoardMove::new(
+            Color::White,
+            TypePiece::Pawn,
+            BitIndex(54),
+            BitIndex(63),
+            Some(TypePiece::Rook),
+            Some(TypePiecePromotion::Knight),
+        );
+        let packed_move = b_move.pack();
+        assert_eq!(packed_move.start(), BitIndex(54));
+        assert_eq!(packed_move.end(), BitIndex(63));
+        assert_eq!(packed_move.promotion(), Some(TypePiecePromotion::Knight));
+        let b_move = BitBoardMove::new(
+            Color::Black,
+            TypePiece::Queen,
+            BitIndex(0),
+            BitIndex(9),
+            None,
+            None,
+        );
+        assert_eq!(b_move.pack().value(), 9 << 6);
+        assert_eq!(b_move.pack().promotion(), None);
+    }
+
     #[test]
     fn test_bitboard_empty() {
         let bitboard = BitBoard::default();
@@ -1,6 +1,5 @@
 pub mod table;
-use table::table_bishop;
-use table::table_rook;
+use table::table_magic;
 
 use super::zobrist;
 use super::BitBoard;
@@ -554,16 +553,41 @@ fn gen_moves_for_all_simple_check(
         )
     };
     let mut moves: Vec<PieceMoves> = moves_king.into_iter().collect();
-    // capture attacker to remove check
+    // capture attacker to remove check (the king captures are king moves)
     for (type_piece, bit_board_attacker_of_opponent) in attackers_opponent_check.iter() {
-        if bit_board_attacker_of_opponent.non_empty() {
+        if type_piece == TypePiece::King {
+            continue;
+        }
+        for index in bit_board_attacker_of_opponent.iter() {
             moves.push(PieceMoves {
                 type_piece,
-                index: bit_board_attacker_of_opponent.index(),
+                index,
                 moves: attacker_index.bitboard(),
             })
         }
     }
+    // capture en passant the pawn which gives check
+    if let Some(en_passant_idx) = capture_en_passant {
+        let pawn_index = match color {
+            square::Color::White => en_passant_idx.down(),
+            square::Color::Black => en_passant_idx.up(),
+        };
+        if pawn_index == attacker_index {
+            let pawn_moves = bit_board.pawns().gen_moves_no_check(
+                color,
+                bit_board,
+                bit_board_opponent,
+                capture_en_passant,
+            );
+            let mask = en_passant_idx.bitboard();
+            moves.extend(
+                pawn_moves
+                    .into_iter()
+                    .filter(|m| (m.moves & mask).non_empty())
+                    .map(|m| m.and_moves(mask)),
+            );
+        }
+    }
     // move a piece to block attack
     match king_index.direction(attacker_index) {
         bitboard::Direction::RookHorizontal | bitboard::Direction::RookVertical => {
@@ -1013,8 +1037,8 @@ fn gen_moves_for_king_castle(
         let are_squares_empty = (occupied_squares
             & (sq1_idx.bitboard() | sq2_idx.bitboard() | sq3_idx.bitboard()))
         .empty();
+        // the king does not cross the b-file square: it can be attacked
         if are_squares_empty
-            && attackers(sq1_idx, color, bit_board, bit_board_opponent).is_empty()
             && attackers(sq2_idx, color, bit_board, bit_board_opponent).is_empty()
             && attackers(sq3_idx, color, bit_board, bit_board_opponent).is_empty()
         {
@@ -1044,24 +1068,6 @@ fn gen_moves_for_knight(
     )
 }
 
-fn gen_moves_for_rook_horizontal(index: bitboard::BitIndex, blockers_h: BitBoard) -> BitBoard {
-    let mask_h = BitBoard::new(255) << index.first_col();
-    let blockers_h = blockers_h & mask_h;
-    let col = index.col();
-    let index_col_a = index.first_col();
-    let blockers_first_row = (blockers_h >> index_col_a).0 as u8;
-    BitBoard(table_rook::table_rook_h(col, blockers_first_row) as u64) << index_col_a
-}
-
-fn gen_moves_for_rook_vertical(index: bitboard::BitIndex, blockers_v: BitBoard) -> BitBoard {
-    let mask_v = BitBoard(table::MASK_COL_A) << index.col();
-    BitBoard::new(table::table_rook::table_rook_v(
-        index.value(),
-        blockers_v.value(),
-        mask_v.value(),
-    ))
-}
-
 fn gen_moves_for_rook(
     is_queen: bool,
     index: bitboard::BitIndex,
@@ -1069,9 +1075,7 @@ fn gen_moves_for_rook(
     bit_board_opponent: &bitboard::BitBoard,
 ) -> Option<PieceMoves> {
     let blockers = *bit_board | *bit_board_opponent;
-    let moves_horizontal = gen_moves_for_rook_horizontal(index, blockers);
-    let moves_vertical = gen_moves_for_rook_vertical(index, blockers);
-    let moves_bitboard = moves_horizontal | moves_vertical;
+    let moves_bitboard = BitBoard(table_magic::rook_moves(index.value(), blockers.value()));
     let type_piece = if is_queen {
         TypePiece::Queen
     } else {
@@ -1087,7 +1091,7 @@ fn gen_moves_for_bishop(
     bit_board_opponent: &bitboard::BitBoard,
 ) -> Option<PieceMoves> {
     let blockers = *bit_board | *bit_board_opponent;
-    let moves_bitboard = BitBoard(table_bishop::bishop_moves(index.value(), blockers.value()));
+    let moves_bitboard = BitBoard(table_magic::bishop_moves(index.value(), blockers.value()));
     let type_piece = if is_queen {
         TypePiece::Queen
     } else {
@@ -1207,7 +1211,7 @@ fn gen_pawn_non_attacker_moves(
             }
             // capture en passant
             if let Some(en_passant_idx) = capture_en_passant {
-                if index.col() > 7 && index.down().right() == *en_passant_idx
+                if index.col() < 7 && index.down().right() == *en_passant_idx
                     || index.col() > 0 && index.down().left() == *en_passant_idx
                 {
                     moves |= en_passant_idx.bitboard();
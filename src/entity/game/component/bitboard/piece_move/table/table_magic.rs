@@ -0,0 +1,253 @@
+use std::sync::OnceLock;
+
+use super::{MASK_COL_A, MASK_COL_H, MASK_ROW_1, MASK_ROW_8};
+
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+// seeds of the magic number generator for each row, chosen so that the search is short
+const MAGIC_SEEDS: [u64; 8] = [728, 10316, 55013, 32803, 12281, 15100, 16645, 255];
+
+// the relevant blockers of a square are hashed into an index of the attack table
+struct Magic {
+    mask: u64,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    fn index(&self, blockers: u64) -> usize {
+        let hash = (blockers & self.mask).wrapping_mul(self.magic) >> self.shift;
+        self.offset + hash as usize
+    }
+}
+
+pub struct SlidingAttacks {
+    rook: Vec<Magic>,
+    bishop: Vec<Magic>,
+    attacks: Vec<u64>,
+}
+
+impl SlidingAttacks {
+    fn new() -> Self {
+        let mut attacks = vec![];
+        let rook = (0..64)
+            .map(|index| find_magic(index, &ROOK_DIRECTIONS, &mut attacks))
+            .collect();
+        let bishop = (0..64)
+            .map(|index| find_magic(index, &BISHOP_DIRECTIONS, &mut attacks))
+            .collect();
+        SlidingAttacks {
+            rook,
+            bishop,
+            attacks,
+        }
+    }
+}
+
+static SLIDING_ATTACKS: OnceLock<SlidingAttacks> = OnceLock::new();
+
+fn sliding_attacks() -> &'static SlidingAttacks {
+    SLIDING_ATTACKS.get_or_init(SlidingAttacks::new)
+}
+
+// build the tables now rather than during the first search
+pub fn init() {
+    sliding_attacks();
+}
+
+pub fn rook_moves(index: u8, blockers: u64) -> u64 {
+    let tables = sliding_attacks();
+    tables.attacks[tables.rook[index as usize].index(blockers)]
+}
+
+pub fn bishop_moves(index: u8, blockers: u64) -> u64 {
+    let tables = sliding_attacks();
+    tables.attacks[tables.bishop[index as usize].index(blockers)]
+}
+
+// slow reference: follow each ray until the edge of the board or the first blocker (included)
+fn ray_moves(index: u8, blockers: u64, directions: &[(i8, i8)]) -> u64 {
+    let (row, col) = ((index / 8) as i8, (index % 8) as i8);
+    let mut moves = 0u64;
+    for (d_row, d_col) in directions {
+        let (mut r, mut c) = (row + d_row, col + d_col);
+        while (0..8).contains(&r) && (0..8).contains(&c) {
+            let square = 1u64 << (r * 8 + c);
+            moves |= square;
+            if blockers & square != 0 {
+                break;
+            }
+            r += d_row;
+            c += d_col;
+        }
+    }
+    moves
+}
+
+// squares of the board edge do not matter as blockers unless the piece is on that edge
+fn relevant_mask(index: u8, directions: &[(i8, i8)]) -> u64 {
+    let row_mask = MASK_ROW_1 << (8 * (index / 8));
+    let col_mask = MASK_COL_A << (index % 8);
+    let edges = ((MASK_ROW_1 | MASK_ROW_8) & !row_mask) | ((MASK_COL_A | MASK_COL_H) & !col_mask);
+    ray_moves(index, 0, directions) & !edges
+}
+
+// xorshift64* generator: the magic numbers are the same from one run to the next
+struct Prng(u64);
+
+impl Prng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(2685821657736338717)
+    }
+    // magic numbers with few bits set are found faster
+    fn sparse(&mut self) -> u64 {
+        self.next() & self.next() & self.next()
+    }
+}
+
+// search a magic number for the square and append its attacks to the shared table
+fn find_magic(index: u8, directions: &[(i8, i8)], attacks: &mut Vec<u64>) -> Magic {
+    let mask = relevant_mask(index, directions);
+    let bits = mask.count_ones();
+    // enumerate all the subsets of the mask (carry rippler)
+    let mut occupancies = vec![];
+    let mut subset = 0u64;
+    loop {
+        occupancies.push((subset, ray_moves(index, subset, directions)));
+        subset = subset.wrapping_sub(mask) & mask;
+        if subset == 0 {
+            break;
+        }
+    }
+    let size = occupancies.len();
+    let mut table = vec![0u64; size];
+    // epoch of the last attempt which filled each entry: no need to clear the table
+    let mut epochs = vec![0u32; size];
+    let mut prng = Prng(MAGIC_SEEDS[(index / 8) as usize]);
+    let mut epoch = 0;
+    loop {
+        let magic = prng.sparse();
+        if (mask.wrapping_mul(magic) >> 56).count_ones() < 6 {
+            continue;
+        }
+        epoch += 1;
+        let candidate = Magic {
+            mask,
+            magic,
+            shift: 64 - bits,
+            offset: 0,
+        };
+        let is_magic = occupancies.iter().all(|(blockers, moves)| {
+            let i = candidate.index(*blockers);
+            if epochs[i] < epoch {
+                epochs[i] = epoch;
+                table[i] = *moves;
+                true
+            } else {
+                table[i] == *moves
+            }
+        });
+        if is_magic {
+            let offset = attacks.len();
+            attacks.extend_from_slice(&table);
+            return Magic {
+                offset,
+                ..candidate
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_magic_against_rays() {
+        let mut prng = Prng(42);
+        for index in 0..64 {
+            for _ in 0..200 {
+                let blockers = prng.sparse();
+                assert_eq!(
+                    rook_moves(index, blockers),
+                    ray_moves(index, blockers, &ROOK_DIRECTIONS)
+                );
+                assert_eq!(
+                    bishop_moves(index, blockers),
+                    ray_moves(index, blockers, &BISHOP_DIRECTIONS)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_relevant_mask() {
+        // a1 rook: the edges h1 and a8 are not relevant
+        assert_eq!(relevant_mask(0, &ROOK_DIRECTIONS).count_ones(), 12);
+        // d4 rook
+        assert_eq!(relevant_mask(27, &ROOK_DIRECTIONS).count_ones(), 10);
+        assert_eq!(relevant_mask(0, &BISHOP_DIRECTIONS).count_ones(), 6);
+        assert_eq!(relevant_mask(27, &BISHOP_DIRECTIONS).count_ones(), 9);
+    }
+
+    #[test]
+    fn test_rook_moves_blockers() {
+        // rook in d4, blockers in d6 and b4
+        let blockers = (1u64 << 43) | (1u64 << 25);
+        let expected = (1u64 << 35 | 1u64 << 43)
+            | (1u64 << 19 | 1u64 << 11 | 1u64 << 3)
+            | (1u64 << 26 | 1u64 << 25)
+            | (1u64 << 28 | 1u64 << 29 | 1u64 << 30 | 1u64 << 31);
+        assert_eq!(rook_moves(27, blockers), expected);
+    }
+
+    #[test]
+    fn test_bishop_moves_no_blockers() {
+        let expected = (1u64 << 0)
+            | (1u64 << 9)
+            | (1u64 << 18)
+            | (1u64 << 36)
+            | (1u64 << 45)
+            | (1u64 << 54)
+            | (1u64 << 63)
+            | (1u64 << 6)
+            | (1u64 << 13)
+            | (1u64 << 20)
+            | (1u64 << 34)
+            | (1u64 << 41)
+            | (1u64 << 48);
+        assert_eq!(bishop_moves(27, 0), expected);
+        let expected = (1u64 << 54)
+            | (1u64 << 45)
+            | (1u64 << 36)
+            | (1u64 << 27)
+            | (1u64 << 18)
+            | (1u64 << 9)
+            | (1u64 << 0);
+        assert_eq!(bishop_moves(63, 0), expected);
+    }
+
+    #[test]
+    fn test_bishop_moves_blockers() {
+        let expected = (1u64 << 45)
+            | (1u64 << 54)
+            | (1u64 << 63)
+            | (1u64 << 27)
+            | (1u64 << 15)
+            | (1u64 << 22)
+            | (1u64 << 29)
+            | (1u64 << 43)
+            | (1u64 << 50 | 1u64 << 57);
+        assert_eq!(bishop_moves(36, 1u64 << 27), expected);
+        let expected = (1u64 << 27)
+            | (1u64 << 45)
+            | (1u64 << 15)
+            | (1u64 << 22 | 1u64 << 29 | 1u64 << 43 | 1u64 << 50 | 1u64 << 57);
+        assert_eq!(bishop_moves(36, (1u64 << 27) | (1u64 << 45)), expected);
+    }
+}
@@ -189,6 +189,34 @@ impl ZobristHash {
         let hash = self.0;
         ZobristHash(hash ^ zobrist.en_passant[ep_square.value() as usize])
     }
+    // castling rights lost and en passant square changed by a move
+    pub fn xor_status(
+        &self,
+        before: &bitboard::BitPositionStatus,
+        after: &bitboard::BitPositionStatus,
+        zobrist: &Zobrist,
+    ) -> Self {
+        let mut hash = self.clone();
+        if before.castling_white_king_side() != after.castling_white_king_side() {
+            hash = hash.xor_castling_white_king_side(zobrist);
+        }
+        if before.castling_white_queen_side() != after.castling_white_queen_side() {
+            hash = hash.xor_castling_white_queen_side(zobrist);
+        }
+        if before.castling_black_king_side() != after.castling_black_king_side() {
+            hash = hash.xor_castling_black_king_side(zobrist);
+        }
+        if before.castling_black_queen_side() != after.castling_black_queen_side() {
+            hash = hash.xor_castling_black_queen_side(zobrist);
+        }
+        if let Some(ep_square) = before.pawn_en_passant() {
+            hash = hash.xor_en_passant(ep_square, zobrist);
+        }
+        if let Some(ep_square) = after.pawn_en_passant() {
+            hash = hash.xor_en_passant(ep_square, zobrist);
+        }
+        hash
+    }
     pub fn xor_player_turn(&self, zobrist: &Zobrist) -> Self {
         let hash = self.0;
         ZobristHash(hash ^ zobrist.side_to_move)
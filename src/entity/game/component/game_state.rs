@@ -22,6 +22,7 @@ pub enum EndGame {
     TimeOutLost(square::Color), // color of the player that has lost
     TimeOutDraw,                // Timeout but only a King, King + Bishop or Knight
     NullAgreement,              // Two players agree to end the game
+    Resign(square::Color),      // color of the player that has resigned
 }
 impl EndGame {
     pub fn is_mat(&self) -> bool {
@@ -29,27 +30,12 @@ impl EndGame {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-struct BackMove {
-    bitboard_white_and_black_mask: bitboard::BitBoardsWhiteAndBlack,
-    bit_position_status_back: bitboard::BitPositionStatus,
-}
-impl BackMove {
-    fn new(
-        bitboard_white_and_black_mask: bitboard::BitBoardsWhiteAndBlack,
-        bit_position_status_back: bitboard::BitPositionStatus,
-    ) -> Self {
-        Self {
-            bitboard_white_and_black_mask,
-            bit_position_status_back,
-        }
-    }
-}
 #[derive(Debug, Clone)]
 pub struct GameState {
     bit_position: bitboard::BitPosition,
     hash_positions: zobrist::ZobristHistory,
-    backup: Vec<BackMove>,
+    backup: Vec<bitboard::UndoMove>,
+    backup_null_moves: Vec<bitboard::BitPositionStatus>,
     end_game: EndGame,
 }
 impl PartialEq for GameState {
@@ -67,6 +53,7 @@ impl GameState {
             bit_position: bitboard::BitPosition::from(position),
             hash_positions: zobrist::ZobristHistory::default(),
             backup: vec![],
+            backup_null_moves: vec![],
             end_game: EndGame::None,
         };
         // init moves and game status
@@ -79,16 +66,6 @@ impl GameState {
     fn add_hash(&mut self, hash: zobrist::ZobristHash) {
         self.hash_positions.push(hash);
     }
-    fn store_backup(
-        &mut self,
-        bit_boards_white_and_black_masks: BitBoardsWhiteAndBlack,
-        bit_position_status_back: bitboard::BitPositionStatus,
-    ) {
-        self.backup.push(BackMove::new(
-            bit_boards_white_and_black_masks,
-            bit_position_status_back,
-        ))
-    }
     // build the hash table
     fn init_hash_table(&mut self, zobrist_table: &zobrist::Zobrist) {
         // reset hash from new position
@@ -133,6 +110,17 @@ impl GameState {
         }
     }
 
+    // draw that a player can claim in the current position
+    pub fn claimable_draw(&self) -> Option<EndGame> {
+        let n_half_moves = self.bit_position.bit_position_status().n_half_moves();
+        if n_half_moves >= 100 {
+            Some(EndGame::NoPawnAndCapturex50)
+        } else if self.hash_positions.check_3x(n_half_moves) {
+            Some(EndGame::Repetition3x)
+        } else {
+            None
+        }
+    }
     pub fn gen_control_square(&self) -> (piece_move::ControlSquares, piece_move::ControlSquares) {
         let bit_boards_white_and_black = self.bit_position.bit_boards_white_and_black();
         let control_square_white_with_pawns =
@@ -203,10 +191,15 @@ impl GameState {
     // null move is playing no move (switch side only)
     // we assume we check just after if there is a pat
     pub fn play_null_move(&mut self, zobrist_table: &zobrist::Zobrist) {
+        self.backup_null_moves
+            .push(*self.bit_position.bit_position_status());
         let mut hash = self.last_hash();
         // Change player turn
         self.bit_position.change_side();
         // Do not allow capture en passant if we play twice
+        if let Some(ep_square) = self.bit_position.bit_position_status().pawn_en_passant() {
+            hash = hash.xor_en_passant(ep_square, zobrist_table);
+        }
         self.bit_position
             .bit_position_status_into()
             .set_pawn_en_passant(None);
@@ -214,21 +207,70 @@ impl GameState {
         self.add_hash(hash);
     }
     pub fn play_back_null_move(&mut self) {
+        let bit_position_status = self
+            .backup_null_moves
+            .pop()
+            .expect("No null move to take back");
+        *self.bit_position.bit_position_status_into() = bit_position_status;
+        self.end_game = EndGame::None;
         self.hash_positions.pop();
-        self.bit_position.change_side();
     }
 
+    // moves played since the position the game was created from
+    pub fn moves(&self) -> Vec<BitBoardMove> {
+        self.backup
+            .iter()
+            .map(|undo_move| undo_move.b_move())
+            .collect()
+    }
+    // position the game was created from, before the moves
+    pub fn initial_position(&self) -> bitboard::BitPosition {
+        let mut game = self.clone();
+        while !game.backup.is_empty() {
+            game.unmake_move();
+        }
+        game.bit_position
+    }
+
+    // take back the last move played with play_moves or make_move
     pub fn play_back(&mut self) {
-        assert!(!self.backup.is_empty());
-        let back_info = self.backup.pop().unwrap();
-        self.bit_position.play_back(
-            back_info.bit_position_status_back,
-            back_info.bitboard_white_and_black_mask,
-        );
+        self.unmake_move();
+    }
+
+    // play a move generated by gen_moves: no validation
+    pub fn make_move(&mut self, b_move: &BitBoardMove, zobrist_table: &zobrist::Zobrist) {
+        let mut hash = self.last_hash();
+        let undo_move = self
+            .bit_position
+            .make_move(b_move, &mut hash, zobrist_table);
+        self.backup.push(undo_move);
+        self.add_hash(hash);
+    }
+    pub fn unmake_move(&mut self) {
+        let undo_move = self.backup.pop().expect("No move to take back");
+        self.bit_position.unmake_move(&undo_move);
         self.end_game = EndGame::None;
         self.hash_positions.pop();
     }
 
+    // number of leaf nodes of the legal move tree (move generator check and benchmark)
+    pub fn perft(&mut self, depth: u8, zobrist_table: &zobrist::Zobrist) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.gen_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        let mut n_nodes = 0;
+        for b_move in moves {
+            self.make_move(&b_move, zobrist_table);
+            n_nodes += self.perft(depth - 1, zobrist_table);
+            self.unmake_move();
+        }
+        n_nodes
+    }
+
     // play n moves from the current position
     pub fn play_moves(
         &mut self,
@@ -258,19 +300,7 @@ impl GameState {
                     if let Some(debug_actor) = &debug_actor_opt {
                         debug_actor.do_send(debug::AddMessage(format!("play: {:?}", b_move)));
                     }
-                    let mut hash = self.last_hash();
-                    // get information for back playing
-                    let (bit_position_black_and_white_before_move, bit_position_status_before_move) =
-                        self.bit_position.tuple();
-                    self.bit_position
-                        .move_piece(&b_move, &mut hash, zobrist_table);
-                    let bitboards_masks = self
-                        .bit_position
-                        .bit_boards_white_and_black()
-                        .xor(bit_position_black_and_white_before_move);
-                    self.store_backup(bitboards_masks, bit_position_status_before_move);
-                    // update hash history
-                    self.add_hash(hash);
+                    self.make_move(&b_move, zobrist_table);
                     summary.push(b_move);
                     if let Some(debug_actor) = &debug_actor_opt {
                         debug_actor.do_send(debug::AddMessage(format!(
@@ -537,4 +567,90 @@ mod tests {
         println!("{}", moves.join(","));
         assert!(moves.iter().any(|m| m == mv))
     }
+
+    // every node: the incremental hash is the hash of the position, and unmake restores it
+    fn check_make_unmake(game: &mut super::GameState, depth: u8, zobrist_table: &Zobrist) {
+        let fen_before = fen::Fen::encode(&game.bit_position().to()).unwrap();
+        let hash_before = game.last_hash();
+        for b_move in game.gen_moves() {
+            game.make_move(&b_move, zobrist_table);
+            let hash = bitboard::zobrist::ZobristHash::zobrist_hash_from_position(
+                game.bit_position(),
+                zobrist_table,
+            );
+            assert_eq!(game.last_hash(), hash);
+            if depth > 1 {
+                check_make_unmake(game, depth - 1, zobrist_table);
+            }
+            game.unmake_move();
+            assert_eq!(
+                fen::Fen::encode(&game.bit_position().to()).unwrap(),
+                fen_before
+            );
+            assert_eq!(game.last_hash(), hash_before);
+        }
+    }
+
+    #[test]
+    fn test_make_unmake_move() {
+        let zobrist_table = Zobrist::new();
+        // castles, en passant, promotions and captures of rooks
+        for fen_pos in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/1P4P1/8/2pP4/8/8/6p1/R3K2R w KQkq c6 0 1",
+        ] {
+            let position = fen::Fen::decode(fen_pos).expect("Failed to decode FEN");
+            let mut game = super::GameState::new(position, &zobrist_table);
+            check_make_unmake(&mut game, 2, &zobrist_table);
+        }
+        // null move: the en passant square is restored
+        let fen_pos = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2";
+        let position = fen::Fen::decode(fen_pos).expect("Failed to decode FEN");
+        let mut game = super::GameState::new(position, &zobrist_table);
+        let fen_before = fen::Fen::encode(&game.bit_position().to()).unwrap();
+        let hash_before = game.last_hash();
+        let n_moves = game.gen_moves().len();
+        game.play_null_move(&zobrist_table);
+        let hash = bitboard::zobrist::ZobristHash::zobrist_hash_from_position(
+            game.bit_position(),
+            &zobrist_table,
+        );
+        assert_eq!(game.last_hash(), hash);
+        game.play_back_null_move();
+        assert_eq!(
+            fen::Fen::encode(&game.bit_position().to()).unwrap(),
+            fen_before
+        );
+        assert_eq!(game.last_hash(), hash_before);
+        assert_eq!(game.gen_moves().len(), n_moves);
+    }
+
+    #[test]
+    fn test_perft() {
+        let zobrist_table = Zobrist::new();
+        // node counts from the chess programming wiki
+        for (fen_pos, depth, n_nodes) in [
+            (fen::FEN_START_POSITION, 3, 8902),
+            (
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                3,
+                97862,
+            ),
+            ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 4, 43238),
+            (
+                "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+                3,
+                9467,
+            ),
+            (
+                "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+                3,
+                62379,
+            ),
+        ] {
+            let position = fen::Fen::decode(fen_pos).expect("Failed to decode FEN");
+            let mut game = super::GameState::new(position, &zobrist_table);
+            assert_eq!(game.perft(depth, &zobrist_table), n_nodes, "{}", fen_pos);
+        }
+    }
 }
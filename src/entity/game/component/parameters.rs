@@ -1,3 +1,4 @@
+use crate::entity::engine::component::time_allocation;
 use crate::ui::notation::long_notation::LongAlgebricNotationMove;
 
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -8,7 +9,9 @@ pub struct Parameters {
     opt_btime: Option<u64>,
     opt_wtime_inc: Option<u64>,
     opt_btime_inc: Option<u64>,
+    opt_moves_to_go: Option<u32>,
     search_moves: Vec<LongAlgebricNotationMove>,
+    opt_move_overhead_ms: Option<u64>,
 }
 
 impl Parameters {
@@ -20,6 +23,7 @@ impl Parameters {
         opt_btime: Option<u64>,
         opt_wtime_inc: Option<u64>,
         opt_btime_inc: Option<u64>,
+        opt_moves_to_go: Option<u32>,
         search_moves: Vec<LongAlgebricNotationMove>,
     ) -> Self {
         Parameters {
@@ -29,7 +33,9 @@ impl Parameters {
             opt_btime,
             opt_wtime_inc,
             opt_btime_inc,
+            opt_moves_to_go,
             search_moves,
+            opt_move_overhead_ms: None,
         }
     }
     pub fn set_depth(&mut self, depth: u32) {
@@ -53,6 +59,19 @@ impl Parameters {
     pub fn set_btime_inc(&mut self, btime_inc: u64) {
         self.opt_btime_inc = Some(btime_inc);
     }
+    pub fn set_moves_to_go(&mut self, moves_to_go: u32) {
+        self.opt_moves_to_go = Some(moves_to_go);
+    }
+    pub fn moves_to_go(&self) -> Option<u32> {
+        self.opt_moves_to_go
+    }
+    pub fn set_move_overhead_ms(&mut self, move_overhead_ms: u64) {
+        self.opt_move_overhead_ms = Some(move_overhead_ms);
+    }
+    pub fn move_overhead_ms(&self) -> u64 {
+        self.opt_move_overhead_ms
+            .unwrap_or(time_allocation::DEFAULT_MOVE_OVERHEAD_MS)
+    }
     pub fn set_search_moves(&mut self, search_moves: Vec<LongAlgebricNotationMove>) {
         self.search_moves = search_moves;
     }
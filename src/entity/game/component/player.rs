@@ -52,6 +52,31 @@ impl Players {
             &self.black
         }
     }
+    // engine attached to a human player to get a hint
+    pub fn get_hint_engine(
+        &self,
+        color: square::Color,
+    ) -> Result<&dispatcher::EngineDispatcherActor, String> {
+        match self.get_player(color) {
+            Player::Human {
+                engine_opt: Some(engine),
+            } => Ok(engine),
+            Player::Human { engine_opt: None } => {
+                Err(format!("No engine to get a hint for player {:?}", color))
+            }
+            Player::Computer { .. } => Err(format!("Player {:?} is not a human", color)),
+        }
+    }
+    // engine playing for color, None for a human player
+    pub fn get_computer_engine(
+        &self,
+        color: square::Color,
+    ) -> Option<&dispatcher::EngineDispatcherActor> {
+        match self.get_player(color) {
+            Player::Computer { engine } => Some(engine),
+            Player::Human { .. } => None,
+        }
+    }
     pub fn get_engine(
         &self,
         color: square::Color,
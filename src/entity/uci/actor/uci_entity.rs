@@ -345,6 +345,30 @@ mod tests {
         assert_eq!(parameters, expected)
     }
 
+    #[actix::test]
+    async fn test_uci_setoption_move_overhead() {
+        let debug_actor_opt: Option<debug::DebugActor> = None;
+        let input = "setoption name Move Overhead value 200";
+        let inputs = vec![input];
+        let (game_manager_actor, _command) = init(&input).await;
+        let uci_reader = Box::new(UciReadVecStringWrapper::new(&inputs));
+        let uci_entity = UciEntity::new(
+            uci_reader,
+            game_manager_actor.clone(),
+            debug_actor_opt.clone(),
+            None,
+        );
+        let uci_entity_actor = uci_entity.start();
+        exec_inputs(uci_entity_actor, inputs).await;
+        actix::clock::sleep(Duration::from_millis(100)).await;
+        let parameters = game_manager_actor
+            .send(game_manager::handler_game::GetParameters)
+            .await
+            .expect("mailbox error")
+            .unwrap();
+        assert_eq!(parameters.move_overhead_ms(), 200)
+    }
+
     #[actix::test]
     async fn test_uci_input_modified_parameters() {
         let debug_actor_opt: Option<debug::DebugActor> = None;
@@ -377,9 +401,9 @@ mod tests {
         let game_manager_actor = game_manager::GameManager::start(game_manager);
         // set clocks before executing UCI commands
         let white_clock_actor =
-            chessclock::Clock::new("white", 3, 0, game_manager_actor.clone()).start();
+            chessclock::Clock::new("white", 3000, 0, game_manager_actor.clone()).start();
         let black_clock_actor =
-            chessclock::Clock::new("black", 3, 0, game_manager_actor.clone()).start();
+            chessclock::Clock::new("black", 3000, 0, game_manager_actor.clone()).start();
         game_manager_actor.do_send(game_manager::handler_clock::SetClocks::new(
             Some(white_clock_actor),
             Some(black_clock_actor),
@@ -405,6 +429,7 @@ mod tests {
             Some(3600001),
             None,
             None,
+            None,
             vec![],
         );
         assert_eq!(parameters, expected);
@@ -423,7 +448,8 @@ mod tests {
             .await
             .expect("actor error")
             .expect("Missing data");
-        assert_eq!(remaining_time_white, 3600000);
+        // the white clock is running since 'go'
+        assert!(remaining_time_white <= 3600000 && remaining_time_white > 3600000 - 1000);
         assert_eq!(remaining_time_black, 3600001);
     }
 
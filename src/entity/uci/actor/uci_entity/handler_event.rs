@@ -93,6 +93,16 @@ impl Handler<event::Event> for UciEntity {
                     game_manager::handler_uci_command::UciCommand::MaxTimePerMoveInMs(time),
                 );
             }
+            event::Event::MoveOverhead(move_overhead) => {
+                self.game_manager_actor.do_send(
+                    game_manager::handler_uci_command::UciCommand::MoveOverhead(move_overhead),
+                );
+            }
+            event::Event::MovesToGo(moves_to_go) => {
+                self.game_manager_actor.do_send(
+                    game_manager::handler_uci_command::UciCommand::MovesToGo(moves_to_go),
+                );
+            }
             ref event @ event::Event::Moves(ref moves) => match moves_validation(moves) {
                 Ok(valid_moves) => {
                     self.game_manager_actor.do_send(
@@ -1,7 +1,10 @@
 use actix::{ActorContext, Handler, Message};
 
 use crate::{
-    entity::{engine::component::ts_best_move, game::actor::game_manager},
+    entity::{
+        engine::component::{time_allocation, ts_best_move},
+        game::actor::game_manager,
+    },
     monitoring::debug,
 };
 
@@ -21,7 +24,14 @@ impl Handler<DisplayEngineId> for UciEntity {
         let engine_id = msg.0;
         writeln!(self.stdout, "id name {}", engine_id.name())?;
         writeln!(self.stdout, "id author {}", engine_id.author())?;
+        writeln!(
+            self.stdout,
+            "option name Move Overhead type spin default {} min 0 max {}",
+            time_allocation::DEFAULT_MOVE_OVERHEAD_MS,
+            time_allocation::MAX_MOVE_OVERHEAD_MS
+        )?;
         writeln!(self.stdout, "uciok")?;
+        self.stdout.flush()?;
         Ok(())
     }
 }
@@ -49,7 +59,7 @@ impl Handler<UciResult> for UciEntity {
                         format!("bestmove {}", timestamped_best_move.best_move().cast());
                     let msg_ts = format!("timestamp: {}", timestamped_best_move.timestamp());
                     let msg_origin = format!("origin: {:?}", timestamped_best_move.origin());
-                    let msg = [msg_best_move, msg_ts, msg_origin].join(", ");
+                    let msg = [msg_best_move.clone(), msg_ts, msg_origin].join(", ");
                     if let Some(debug_actor) = &self.debug_actor_opt {
                         debug_actor.do_send(debug::AddMessage(msg.to_string()));
                     }
@@ -57,7 +67,8 @@ impl Handler<UciResult> for UciEntity {
                         tracing::debug!("writing bestmove");
                         let mut handle = std::io::BufWriter::new(io::stdout());
                         //let mut handle = self.stdout.lock();
-                        writeln!(handle, "{}", msg).unwrap(); // Write message with a newline
+                        // only the move: GUIs parse the line
+                        writeln!(handle, "{}", msg_best_move).unwrap(); // Write message with a newline
                         handle.flush().unwrap();
                         tracing::debug!("done");
                     }
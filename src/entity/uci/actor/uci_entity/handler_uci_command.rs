@@ -1,4 +1,5 @@
 use crate::entity::{
+    engine::component::time_allocation,
     game::actor::game_manager,
     uci::component::{command, event},
 };
@@ -99,6 +100,12 @@ impl Handler<command::Command> for UciEntity {
                     ));
                     events.push(event::Event::BtimeInc(btime_inc));
                 }
+                if let Some(moves_to_go) = go.moves_to_go() {
+                    events.push(event::Event::WriteDebug(
+                        format!("Moves to go: {}", moves_to_go).to_string(),
+                    ));
+                    events.push(event::Event::MovesToGo(moves_to_go));
+                }
                 if !go.search_moves().is_empty() {
                     events.push(event::Event::WriteDebug(format!(
                         "Limit search to these moves: {:?}",
@@ -108,6 +115,29 @@ impl Handler<command::Command> for UciEntity {
                 }
                 events.push(event::Event::StartEngineThinking)
             }
+            command::Command::SetOption(name, value_opt) => {
+                match (name.to_lowercase().as_str(), value_opt) {
+                    ("move overhead", Some(value)) => match value.parse::<u64>() {
+                        Ok(move_overhead)
+                            if move_overhead <= time_allocation::MAX_MOVE_OVERHEAD_MS =>
+                        {
+                            events.push(event::Event::WriteDebug(format!(
+                                "Move overhead: {} ms",
+                                move_overhead
+                            )));
+                            events.push(event::Event::MoveOverhead(move_overhead));
+                        }
+                        _ => events.push(event::Event::Write(format!(
+                            "info string invalid value '{}' for option '{}'",
+                            value, name
+                        ))),
+                    },
+                    _ => events.push(event::Event::Write(format!(
+                        "info string unknown option '{}'",
+                        name
+                    ))),
+                }
+            }
             command::Command::Stop => {
                 events.push(event::Event::WriteDebug("Stopping search.".to_string()));
                 events.push(event::Event::StopEngine(self.stat_actor_opt.clone()));
@@ -30,6 +30,7 @@ pub enum Command {
     NewGame,
     Position(PositionStruct),
     Quit,                                 // "quit" command to exit the engine
+    SetOption(String, Option<String>),    // "setoption name <id> [value <x>]"
     Stop,                                 // "stop" command to stop search
     Uci(Addr<game_manager::GameManager>), // "uci" command, no additional data needed
     Wait100ms,                            // for test purpose
@@ -64,6 +65,7 @@ pub struct GoStruct {
     btime: Option<u64>,        // Black time left
     wtime_inc: Option<u64>,    // White time increment per move
     btime_inc: Option<u64>,    // Black time increment per move
+    moves_to_go: Option<u32>,  // Moves to play before the next time control
     search_moves: Vec<String>, // Restrict search to this moves only
 }
 
@@ -89,6 +91,9 @@ impl GoStruct {
     pub fn btime_inc(&self) -> Option<u64> {
         self.btime_inc
     }
+    pub fn moves_to_go(&self) -> Option<u32> {
+        self.moves_to_go
+    }
     pub fn search_moves(&self) -> &Vec<String> {
         &self.search_moves
     }
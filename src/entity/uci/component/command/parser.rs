@@ -32,6 +32,7 @@ impl<'a> InputParser<'a> {
             cmd if cmd.starts_with("debug") => parse_debug(cmd.to_string()),
             cmd if cmd.starts_with("position") => parse_position(cmd.to_string()),
             cmd if cmd.starts_with("go") => parse_go(cmd.to_string()),
+            cmd if cmd.starts_with("setoption") => parse_setoption(cmd.to_string()),
             "ucinewgame" => Ok(Command::NewGame),
             "stop" => Ok(Command::Stop),
             "quit" => Ok(Command::Quit),
@@ -94,6 +95,28 @@ fn parse_position(position_command: String) -> Result<Command, CommandError> {
     }
 }
 
+// option names and values can contain spaces
+fn parse_setoption(setoption_command: String) -> Result<Command, CommandError> {
+    let setoption_vec = setoption_command.split_whitespace().collect::<Vec<&str>>();
+    match setoption_vec.as_slice() {
+        ["setoption", "name", rest @ ..] if !rest.is_empty() => {
+            match rest.iter().position(|&token| token == "value") {
+                Some(idx) if idx > 0 && idx + 1 < rest.len() => Ok(Command::SetOption(
+                    rest[..idx].join(" "),
+                    Some(rest[idx + 1..].join(" ")),
+                )),
+                None => Ok(Command::SetOption(rest.join(" "), None)),
+                _ => Err(CommandError::new(
+                    format!("setoption command error: {}", setoption_command).to_string(),
+                )),
+            }
+        }
+        _ => Err(CommandError::new(
+            format!("setoption command error: {}", setoption_command).to_string(),
+        )),
+    }
+}
+
 fn parse_go(go_command: String) -> Result<Command, CommandError> {
     let mut result = true;
     let go_vec = go_command.split_whitespace().collect::<Vec<&str>>();
@@ -118,6 +141,9 @@ fn parse_go(go_command: String) -> Result<Command, CommandError> {
             "binc" => {
                 parsed.btime_inc = Some(go_vec[i + 1].parse().unwrap());
             }
+            "movestogo" => {
+                parsed.moves_to_go = Some(go_vec[i + 1].parse().unwrap());
+            }
             "infinite" => {
                 parsed.infinite = true;
             }
@@ -10,7 +10,9 @@ pub enum Event {
     Depth(u32),
     Fen(String),
     MaxTimePerMoveInMs(u32),
+    MoveOverhead(u64),
     Moves(Vec<String>),
+    MovesToGo(u32),
     Quit,
     SearchInfinite,
     SearchMoves(Vec<String>),
@@ -1,4 +1,8 @@
 pub mod benchmark;
+pub mod datagen;
 pub mod entity;
+pub mod match_runner;
 pub mod monitoring;
+pub mod problem;
+pub mod tournament;
 pub mod ui;
@@ -1,14 +1,24 @@
 use chess_actix::benchmark;
+use chess_actix::datagen;
+use chess_actix::match_runner;
+use chess_actix::problem;
+use chess_actix::tournament;
+use chess_actix::entity::clock::actor::chessclock;
+use chess_actix::entity::clock::component::time_control;
+use chess_actix::entity::engine::component::adjudication;
+use chess_actix::entity::engine::component::bitbase;
 use chess_actix::entity::engine::component::config::config;
 #[allow(unused_imports)]
 use chess_actix::entity::engine::component::engine_alphabeta;
 use chess_actix::entity::engine::component::engine_iddfs;
+use chess_actix::entity::engine::component::evaluation::{evaluator, nn_evaluator};
 #[allow(unused_imports)]
 use chess_actix::entity::engine::component::engine_mat;
 #[allow(unused_imports)]
 use chess_actix::entity::engine::component::engine_mcts;
 #[allow(unused_imports)]
 use chess_actix::entity::engine::component::engine_minimax;
+use chess_actix::entity::game::component::bitboard::piece_move::table::table_magic;
 use chess_actix::entity::game::component::bitboard::zobrist;
 use chrono::{Local, TimeZone, Utc};
 
@@ -46,6 +56,8 @@ use tracing_subscriber::{self, layer::SubscriberExt};
 
 const DEPTH: u8 = 4;
 const LOG_FILE_ONLY: bool = false;
+// time given to the engine of the human player for a hint
+const HINT_TIME_MS: u64 = 2000;
 
 #[allow(dead_code)]
 fn fen() {
@@ -118,6 +130,7 @@ fn uci_loop(
 async fn tui_loop(
     game_manager_actor: &game_manager::GameManagerActor,
     stdin: &mut Arc<Mutex<io::Stdin>>,
+    time_control_opt: Option<time_control::TimeControl>,
 ) {
     // init the game
     let inputs = vec!["position startpos"];
@@ -139,6 +152,23 @@ async fn tui_loop(
     }
     //let r = uci::uci_loop(uci_reader, game_actor).await;
     //println!("{:?}", r.err());
+    // without time control, the game is played without clocks
+    let has_clocks = time_control_opt.is_some();
+    if let Some(time_control) = time_control_opt {
+        let white_clock_actor = chessclock::Clock::with_time_control(
+            "white",
+            time_control.clone(),
+            game_manager_actor.clone(),
+        )
+        .start();
+        let black_clock_actor =
+            chessclock::Clock::with_time_control("black", time_control, game_manager_actor.clone())
+                .start();
+        game_manager_actor.do_send(game_manager::handler_clock::SetClocks::new(
+            Some(white_clock_actor),
+            Some(black_clock_actor),
+        ));
+    }
     let mut stdin_reader = uci_entity::UciReadWrapper::new(stdin.clone());
     // loop
     loop {
@@ -148,10 +178,81 @@ async fn tui_loop(
             .expect("actix error")
             .expect("Error when retrieving game_state");
         println!("\n{}", game_state.bit_position().to().chessboard());
+        if has_clocks {
+            // the clock of the player to move is running
+            game_manager_actor.do_send(game_manager::handler_clock::StartOrSwitchClocks);
+            for color in [square::Color::White, square::Color::Black] {
+                let remaining_opt = game_manager_actor
+                    .send(game_manager::handler_clock::GetClockRemainingTime::new(color))
+                    .await
+                    .expect("actix error");
+                if let Some(remaining) = remaining_opt {
+                    println!("{:?}: {} ms", color, remaining);
+                }
+            }
+        }
         let input_opt = stdin_reader.uci_read();
         match input_opt.as_deref() {
             None => {}
             Some("quit") => break,
+            Some("undo") => {
+                let result = game_manager_actor
+                    .send(game_manager::handler_game::UndoMove(1))
+                    .await
+                    .unwrap();
+                if let Some(err) = result.err() {
+                    println!("Undo error: {}", err);
+                }
+            }
+            Some("hint") => {
+                let result = game_manager_actor
+                    .send(game_manager::handler_engine::RequestHint::new(HINT_TIME_MS))
+                    .await
+                    .unwrap();
+                match result {
+                    Ok(hint) => println!("Hint: {}", hint),
+                    Err(err) => println!("Hint error: {}", err),
+                }
+            }
+            Some("resign") => {
+                let color = game_state.bit_position().bit_position_status().player_turn();
+                let result = game_manager_actor
+                    .send(game_manager::handler_game::Resign(color))
+                    .await
+                    .unwrap();
+                if let Some(err) = result.err() {
+                    println!("Resign error: {}", err);
+                }
+            }
+            Some("draw") => {
+                let color = game_state.bit_position().bit_position_status().player_turn();
+                let result = game_manager_actor
+                    .send(game_manager::handler_game::OfferDraw(color))
+                    .await
+                    .unwrap();
+                if let Some(err) = result.err() {
+                    println!("Draw offer error: {}", err);
+                }
+            }
+            Some("claim") => {
+                let result = game_manager_actor
+                    .send(game_manager::handler_game::ClaimDraw)
+                    .await
+                    .unwrap();
+                match result {
+                    Ok(end_game) => println!("Draw: {:?}", end_game),
+                    Err(err) => println!("Claim error: {}", err),
+                }
+            }
+            Some("redo") => {
+                let result = game_manager_actor
+                    .send(game_manager::handler_game::RedoMove)
+                    .await
+                    .unwrap();
+                if let Some(err) = result.err() {
+                    println!("Redo error: {}", err);
+                }
+            }
             // e2e4 for example
             Some(input) if input.len() == 4 => {
                 let moves = vec![input.to_string()];
@@ -170,7 +271,9 @@ async fn tui_loop(
                     }
                 }
             }
-            _ => println!("Please enter a move to a format like e2e4"),
+            _ => println!(
+                "Please enter a move to a format like e2e4, 'hint', 'undo', 'redo', 'resign', 'draw', 'claim' or 'quit'"
+            ),
         }
     }
 }
@@ -253,7 +356,101 @@ struct BuildParams {
     stat_actor_opt: Option<actix::Addr<stat_entity::StatEntity>>,
     stdin: Arc<Mutex<io::Stdin>>,
 }
-fn init_game_params() -> BuildParams {
+fn build_adjudication_conf(
+    resign_score_opt: Option<i32>,
+    resign_moves: u32,
+    draw_score_opt: Option<i32>,
+    draw_move: u32,
+) -> adjudication::AdjudicationConf {
+    adjudication::AdjudicationConf {
+        resign_opt: resign_score_opt.map(|score| adjudication::ResignRule {
+            score,
+            n_moves: resign_moves,
+        }),
+        draw_opt: draw_score_opt.map(|score| adjudication::DrawRule {
+            score,
+            min_move_number: draw_move,
+        }),
+    }
+}
+
+fn build_tournament_conf(
+    engines: &[String],
+    format: &str,
+    games: u64,
+    openings_opt: Option<&str>,
+    results: String,
+) -> Result<tournament::TournamentConf, String> {
+    let engines = engines
+        .iter()
+        .map(|engine| match_runner::EngineSpec::parse(engine))
+        .collect::<Result<_, _>>()?;
+    let format = tournament::TournamentFormat::from_name(format)?;
+    let mut conf = tournament::TournamentConf::new(engines, format, games, results)?;
+    if let Some(path) = openings_opt {
+        conf.openings = match_runner::read_openings(path)?;
+    }
+    Ok(conf)
+}
+
+fn build_time_control(
+    time_control_opt: Option<&str>,
+    time_ms: u64,
+    inc_ms: u64,
+) -> Result<time_control::TimeControl, String> {
+    match time_control_opt {
+        Some(spec) => time_control::TimeControl::parse(spec),
+        None => Ok(time_control::TimeControl::fischer(time_ms, inc_ms)),
+    }
+}
+
+fn build_match_conf(
+    engine1: &str,
+    engine2: &str,
+    games: u64,
+    openings_opt: Option<&str>,
+) -> Result<match_runner::MatchConf, String> {
+    let engine1 = match_runner::EngineSpec::parse(engine1)?;
+    let engine2 = match_runner::EngineSpec::parse(engine2)?;
+    let mut conf = match_runner::MatchConf::new(engine1, engine2, games);
+    if let Some(path) = openings_opt {
+        conf.openings = match_runner::read_openings(path)?;
+    }
+    Ok(conf)
+}
+
+fn build_evaluator(nn_weights_opt: Option<&str>) -> Arc<dyn evaluator::Evaluator> {
+    match nn_weights_opt {
+        Some(path) => match nn_evaluator::NnEvaluator::load(path) {
+            Ok(nn) => Arc::new(nn),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        },
+        None => Arc::new(evaluator::ClassicEvaluator::default()),
+    }
+}
+
+fn load_bitbases(dir: &str) {
+    let path = std::path::Path::new(dir);
+    if !path.is_dir() {
+        tracing::info!("No bitbases directory '{}'", dir);
+        return;
+    }
+    match bitbase::Bitbases::load_dir(path) {
+        Ok(bitbases) => {
+            tracing::info!("Bitbases loaded: {:?}", bitbases.kinds());
+            bitbase::init(bitbases).expect("bitbases already loaded");
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn init_game_params(evaluator: Arc<dyn evaluator::Evaluator>) -> BuildParams {
     let conf = config::IDDFSConfig::new(
         2*DEPTH -1,
         config::IddfsFeatureConf::default(),
@@ -273,6 +470,7 @@ fn init_game_params() -> BuildParams {
         &conf
     );
     engine_player1.set_id_number("white");
+    engine_player1.set_evaluator(evaluator.clone());
     let engine_player1_dispatcher =
         dispatcher::EngineDispatcher::new(Arc::new(engine_player1), debug_actor_opt.clone(), None);
     //let mut engine_player2 = dummy::EngineDummy::new(debug_actor_opt.clone());
@@ -284,6 +482,7 @@ fn init_game_params() -> BuildParams {
         &conf,
     );
     engine_player2.set_id_number("black");
+    engine_player2.set_evaluator(evaluator);
     let engine_player2_dispatcher =
         dispatcher::EngineDispatcher::new(Arc::new(engine_player2), debug_actor_opt.clone(), None);
     let player1 = player::Player::Human {
@@ -362,20 +561,182 @@ struct Cli {
     /// Commandes spécifiques
     #[command(subcommand)]
     command: Option<Command>,
+    /// Fichier de poids du réseau de neurones (évaluation classique par défaut)
+    #[arg(long, global = true)]
+    nn_weights: Option<String>,
+    /// Répertoire des bitbases chargées au démarrage
+    #[arg(long, global = true, default_value = "bitbases")]
+    bitbases: String,
 }
 
 #[derive(clap::Subcommand)]
 enum Command {
     /// Mode humain (TUI)
-    Human,
+    Human {
+        /// Pendule: périodes séparées par ':' (coups/ms ou ms) puis +incrément ou +delay=fischer|bronstein|simple:ms (ex: 40/5400000:1800000+delay=bronstein:2000)
+        #[arg(long)]
+        time_control: Option<String>,
+    },
     /// Benchmark de performance
-    Benchmark,
+    Benchmark {
+        /// Termes d'évaluation désactivés (mobility, bishop_pair, rook_open_file, rook_7th_rank, knight_outpost, bad_bishop, endgame)
+        #[arg(long, value_delimiter = ',')]
+        eval_off: Vec<String>,
+    },
+    /// Génération de données d'entraînement par self-play
+    Datagen {
+        /// Nombre de parties
+        #[arg(long, default_value_t = 100)]
+        games: u64,
+        /// Profondeur de recherche (demi-coups)
+        #[arg(long, default_value_t = 4)]
+        depth: u8,
+        /// Nombre maximum de positions évaluées par coup
+        #[arg(long)]
+        nodes: Option<u64>,
+        /// Nombre de demi-coups aléatoires en ouverture
+        #[arg(long, default_value_t = 8)]
+        random_plies: u8,
+        /// Graine du générateur aléatoire
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Nombre de parties jouées en parallèle
+        #[arg(long)]
+        threads: Option<usize>,
+        /// Fichier de sortie (reprise possible)
+        #[arg(long, default_value = "datagen.txt")]
+        output: String,
+    },
+    /// Génération des bitbases par analyse rétrograde (dans le répertoire --bitbases)
+    Bitbase {
+        /// Finales à générer (KPK, KRK, KQK, KBNK)
+        #[arg(long, value_delimiter = ',', default_value = "KPK,KRK,KQK,KBNK")]
+        tables: Vec<String>,
+    },
+    /// Résolution d'un problème: toutes les clés, essais et réfutations
+    Solve {
+        /// Position FEN du problème (le camp au trait commence)
+        #[arg(long)]
+        fen: String,
+        /// Énoncé: #n (mat direct), h#n (mat aidé) ou s#n (automat)
+        #[arg(long)]
+        stipulation: String,
+    },
+    /// Match entre deux configurations de moteur (ex: iddfs,depth=5,lmr=off)
+    Match {
+        /// Premier moteur: type (dummy, minimax, alphabeta, iddfs, mat, pns, mcts, uci) suivi des options (uci,path=...,option.Hash=16)
+        #[arg(long)]
+        engine1: String,
+        /// Second moteur
+        #[arg(long)]
+        engine2: String,
+        /// Nombre de parties (chaque ouverture est jouée avec les deux couleurs)
+        #[arg(long, default_value_t = 10)]
+        games: u64,
+        /// Fichier d'ouvertures (une position FEN ou EPD par ligne)
+        #[arg(long)]
+        openings: Option<String>,
+        /// Temps initial de chaque joueur (ms)
+        #[arg(long, default_value_t = 10_000)]
+        time_ms: u64,
+        /// Incrément par coup (ms)
+        #[arg(long, default_value_t = 100)]
+        inc_ms: u64,
+        /// Pendule remplaçant --time-ms et --inc-ms, même syntaxe que pour human (ex: 40/60000:30000+delay=simple:500)
+        #[arg(long)]
+        time_control: Option<String>,
+        /// Nulle adjugée après ce nombre de demi-coups
+        #[arg(long, default_value_t = 400)]
+        max_plies: usize,
+        /// Abandon quand le score est inférieur à -resign_score (1000 = un pion)
+        #[arg(long)]
+        resign_score: Option<i32>,
+        /// Nombre de coups consécutifs sous le score d'abandon
+        #[arg(long, default_value_t = 3)]
+        resign_moves: u32,
+        /// Nulle acceptée quand |score| est inférieur à draw_score
+        #[arg(long)]
+        draw_score: Option<i32>,
+        /// Numéro de coup à partir duquel la nulle peut être acceptée
+        #[arg(long, default_value_t = 40)]
+        draw_move: u32,
+        /// Fichier PGN des parties jouées
+        #[arg(long)]
+        pgn: Option<String>,
+        /// Nombre de paires de parties jouées en parallèle (nombre de coeurs par défaut)
+        #[arg(long)]
+        concurrency: Option<usize>,
+        /// Test SPRT: arrêt dès qu'une borne est franchie (--games est alors le maximum)
+        #[arg(long)]
+        sprt: bool,
+        /// Hypothèse H0 du SPRT (Elo)
+        #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+        elo0: f64,
+        /// Hypothèse H1 du SPRT (Elo)
+        #[arg(long, default_value_t = 5.0, allow_hyphen_values = true)]
+        elo1: f64,
+        /// Risque de première espèce du SPRT
+        #[arg(long, default_value_t = 0.05)]
+        alpha: f64,
+        /// Risque de seconde espèce du SPRT
+        #[arg(long, default_value_t = 0.05)]
+        beta: f64,
+    },
+    /// Tournoi entre plusieurs configurations de moteur (reprise possible)
+    Tournament {
+        /// Moteur participant (option répétée), même syntaxe que pour match
+        #[arg(long = "engine", required = true)]
+        engines: Vec<String>,
+        /// Format du tournoi: round-robin ou gauntlet (le premier moteur contre les autres)
+        #[arg(long, default_value = "round-robin")]
+        format: String,
+        /// Nombre de parties par paire de moteurs
+        #[arg(long, default_value_t = 2)]
+        games: u64,
+        /// Fichier d'ouvertures (une position FEN ou EPD par ligne)
+        #[arg(long)]
+        openings: Option<String>,
+        /// Temps initial de chaque joueur (ms)
+        #[arg(long, default_value_t = 10_000)]
+        time_ms: u64,
+        /// Incrément par coup (ms)
+        #[arg(long, default_value_t = 100)]
+        inc_ms: u64,
+        /// Pendule remplaçant --time-ms et --inc-ms, même syntaxe que pour human (ex: 40/60000:30000+delay=simple:500)
+        #[arg(long)]
+        time_control: Option<String>,
+        /// Nulle adjugée après ce nombre de demi-coups
+        #[arg(long, default_value_t = 400)]
+        max_plies: usize,
+        /// Abandon quand le score est inférieur à -resign_score (1000 = un pion)
+        #[arg(long)]
+        resign_score: Option<i32>,
+        /// Nombre de coups consécutifs sous le score d'abandon
+        #[arg(long, default_value_t = 3)]
+        resign_moves: u32,
+        /// Nulle acceptée quand |score| est inférieur à draw_score
+        #[arg(long)]
+        draw_score: Option<i32>,
+        /// Numéro de coup à partir duquel la nulle peut être acceptée
+        #[arg(long, default_value_t = 40)]
+        draw_move: u32,
+        /// Nombre de parties jouées en parallèle (nombre de coeurs par défaut)
+        #[arg(long)]
+        concurrency: Option<usize>,
+        /// Fichier PGN des parties jouées
+        #[arg(long)]
+        pgn: Option<String>,
+        /// Fichier des résultats: le tournoi reprend à partir des parties déjà jouées
+        #[arg(long, default_value = "tournament.txt")]
+        results: String,
+    },
 }
 
 #[actix::main]
 async fn main() {
     init_trace();
     trace_build_info();
+    table_magic::init();
 
     let cli = match Cli::try_parse() {
         Ok(cli) => cli,
@@ -384,19 +745,237 @@ async fn main() {
             std::process::exit(1);
         }
     };
+    let evaluator = build_evaluator(cli.nn_weights.as_deref());
+    if !matches!(cli.command, Some(Command::Bitbase { .. })) {
+        load_bitbases(&cli.bitbases);
+    }
     match cli.command {
-        Some(Command::Human) => {
+        Some(Command::Human { time_control }) => {
             println!("Entering in tui mode");
+            let time_control_opt = time_control.map(|spec| {
+                time_control::TimeControl::parse(&spec).unwrap_or_else(|err| {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                })
+            });
             let BuildParams {
                 game_manager_actor,
                 debug_actor_opt: _,
                 stat_actor_opt: _,
                 mut stdin,
-            } = init_game_params();
-            tui_loop(&game_manager_actor, &mut stdin).await;
+            } = init_game_params(evaluator);
+            tui_loop(&game_manager_actor, &mut stdin, time_control_opt).await;
         }
-        Some(Command::Benchmark) => {
-            benchmark::launcher::benchmark("epd").unwrap();
+        Some(Command::Benchmark { eval_off }) => {
+            // the terms only apply to the classic evaluation
+            let evaluator: Arc<dyn evaluator::Evaluator> = if eval_off.is_empty() {
+                evaluator
+            } else if cli.nn_weights.is_some() {
+                eprintln!("--eval-off cannot be used with --nn-weights");
+                std::process::exit(1);
+            } else {
+                let mut eval_conf = config::EvalConf::default();
+                for term in eval_off {
+                    if let Err(err) = eval_conf.set_term(&term, false) {
+                        eprintln!("{}", err);
+                        std::process::exit(1);
+                    }
+                }
+                println!("evaluation terms:\n{}", eval_conf);
+                Arc::new(evaluator::ClassicEvaluator::new(eval_conf))
+            };
+            benchmark::launcher::benchmark("epd", evaluator).unwrap();
+        }
+        Some(Command::Datagen {
+            games,
+            depth,
+            nodes,
+            random_plies,
+            seed,
+            threads,
+            output,
+        }) => {
+            let mut conf = datagen::DatagenConf::new(games, depth, seed, output);
+            conf.max_nodes_opt = nodes;
+            conf.random_plies = random_plies;
+            if let Some(threads) = threads {
+                conf.n_threads = threads;
+            }
+            println!("{}", conf);
+            let iddfs_conf = config::IDDFSConfig::new(
+                depth,
+                config::IddfsFeatureConf::default(),
+                config::AlphabetaFeatureConf::default(),
+            );
+            let mut engine =
+                engine_iddfs::EngineIddfs::new(None, zobrist::Zobrist::new(), &iddfs_conf);
+            engine.set_id_number("datagen");
+            engine.set_evaluator(evaluator);
+            engine.set_max_nodes(nodes);
+            let self_actor =
+                dispatcher::EngineDispatcher::new(Arc::new(engine.clone()), None, None).start();
+            // the search is blocking: keep the actix system free for the dispatcher
+            let result = actix_rt::task::spawn_blocking(move || {
+                datagen::datagen(&conf, &engine, self_actor)
+            })
+            .await
+            .expect("datagen task failed");
+            match result {
+                Ok(n_positions) => println!("{} positions written", n_positions),
+                Err(err) => {
+                    eprintln!("datagen error: {}", err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(Command::Bitbase { tables }) => {
+            let kinds: Vec<bitbase::BitbaseKind> = tables
+                .iter()
+                .map(|name| bitbase::BitbaseKind::from_name(name))
+                .collect::<Result<_, _>>()
+                .unwrap_or_else(|err| {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                });
+            let bitbases = bitbase::Bitbases::generate(&kinds);
+            for kind in bitbases.kinds() {
+                let (n_win, n_loss, n_draw) = bitbases.get(kind).unwrap().count();
+                println!("{}: {} wins, {} losses, {} draws", kind, n_win, n_loss, n_draw);
+            }
+            if let Err(err) = bitbases.save_dir(std::path::Path::new(&cli.bitbases)) {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Solve { fen, stipulation }) => {
+            let stipulation = problem::Stipulation::parse(&stipulation).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            });
+            let position = fen::Fen::decode(&fen).unwrap_or_else(|err| {
+                eprintln!("Invalid FEN '{}': {:?}", fen, err);
+                std::process::exit(1);
+            });
+            let zobrist_table = zobrist::Zobrist::new();
+            let game =
+                entity::game::component::game_state::GameState::new(position, &zobrist_table);
+            print!("{}", problem::solve(&game, stipulation, &zobrist_table));
+        }
+        Some(Command::Match {
+            engine1,
+            engine2,
+            games,
+            openings,
+            time_ms,
+            inc_ms,
+            time_control,
+            max_plies,
+            resign_score,
+            resign_moves,
+            draw_score,
+            draw_move,
+            pgn,
+            concurrency,
+            sprt,
+            elo0,
+            elo1,
+            alpha,
+            beta,
+        }) => {
+            let mut conf = build_match_conf(&engine1, &engine2, games, openings.as_deref())
+                .unwrap_or_else(|err| {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                });
+            conf.time_control = build_time_control(time_control.as_deref(), time_ms, inc_ms)
+                .unwrap_or_else(|err| {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                });
+            conf.max_plies = max_plies;
+            conf.adjudication =
+                build_adjudication_conf(resign_score, resign_moves, draw_score, draw_move);
+            conf.pgn_output_opt = pgn;
+            if let Some(concurrency) = concurrency {
+                conf.concurrency = concurrency;
+            }
+            if sprt {
+                conf.sprt_opt = Some(
+                    match_runner::stats::Sprt::new(elo0, elo1, alpha, beta).unwrap_or_else(
+                        |err| {
+                            eprintln!("{}", err);
+                            std::process::exit(1);
+                        },
+                    ),
+                );
+            }
+            println!("{}", conf);
+            match match_runner::run_match(&conf, evaluator).await {
+                Ok(result) => {
+                    println!(
+                        "{}",
+                        match_runner::stats::MatchTable::new(
+                            conf.engine1.name(),
+                            conf.engine2.name(),
+                            result.wdl
+                        )
+                    );
+                    if let (Some(llr), Some(status)) = (result.llr_opt, result.sprt_status_opt) {
+                        println!(
+                            "Pentanomial: {} LLR: {:.2} {:?}",
+                            result.pentanomial, llr, status
+                        );
+                    }
+                }
+                Err(err) => {
+                    eprintln!("match error: {}", err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(Command::Tournament {
+            engines,
+            format,
+            games,
+            openings,
+            time_ms,
+            inc_ms,
+            time_control,
+            max_plies,
+            resign_score,
+            resign_moves,
+            draw_score,
+            draw_move,
+            concurrency,
+            pgn,
+            results,
+        }) => {
+            let mut conf =
+                build_tournament_conf(&engines, &format, games, openings.as_deref(), results)
+                    .unwrap_or_else(|err| {
+                        eprintln!("{}", err);
+                        std::process::exit(1);
+                    });
+            conf.time_control = build_time_control(time_control.as_deref(), time_ms, inc_ms)
+                .unwrap_or_else(|err| {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                });
+            conf.max_plies = max_plies;
+            conf.adjudication =
+                build_adjudication_conf(resign_score, resign_moves, draw_score, draw_move);
+            conf.pgn_output_opt = pgn;
+            if let Some(concurrency) = concurrency {
+                conf.concurrency = concurrency;
+            }
+            println!("{}", conf);
+            match tournament::run_tournament(&conf, evaluator).await {
+                Ok(crosstable) => println!("{}", crosstable),
+                Err(err) => {
+                    eprintln!("tournament error: {}", err);
+                    std::process::exit(1);
+                }
+            }
         }
         None => {
             let BuildParams {
@@ -404,7 +983,7 @@ async fn main() {
                 debug_actor_opt,
                 stat_actor_opt,
                 mut stdin,
-            } = init_game_params();
+            } = init_game_params(evaluator);
             uci_mode(
                 &game_manager_actor,
                 debug_actor_opt,
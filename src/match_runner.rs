@@ -0,0 +1,766 @@
+pub mod stats;
+
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix::Actor;
+
+use crate::entity::clock::actor::chessclock;
+use crate::entity::clock::component::time_control;
+use crate::entity::engine::actor::engine_dispatcher as dispatcher;
+use crate::entity::engine::component::config::config;
+use crate::entity::engine::component::evaluation::evaluator;
+use crate::entity::engine::component::{
+    adjudication, engine_alphabeta, engine_dummy, engine_external, engine_iddfs,
+    engine_logic as logic, engine_mat, engine_mcts, engine_minimax, engine_pns,
+};
+use crate::entity::game::actor::game_manager;
+use crate::entity::game::component::bitboard::zobrist;
+use crate::entity::game::component::{game_state, player, square};
+use crate::ui::notation::fen::{self, EncodeUserInput};
+use crate::ui::notation::pgn;
+
+// the game manager is polled at this period during a game
+const POLLING_MS: u64 = 10;
+const DEFAULT_DEPTH: u8 = 4;
+// playouts per expanded node: the number of tree iterations depends on the clock
+const DEFAULT_MCTS_ITERATIONS: u64 = 10;
+const PGN_EVENT: &str = "chess_rust match";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EngineKind {
+    Dummy,
+    Minimax,
+    Alphabeta,
+    Iddfs,
+    Mat,
+    // proof-number mate search
+    Pns,
+    Mcts,
+    // external executable speaking UCI
+    Uci,
+}
+impl EngineKind {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "dummy" => Ok(Self::Dummy),
+            "minimax" => Ok(Self::Minimax),
+            "alphabeta" => Ok(Self::Alphabeta),
+            "iddfs" => Ok(Self::Iddfs),
+            "mat" => Ok(Self::Mat),
+            "pns" => Ok(Self::Pns),
+            "mcts" => Ok(Self::Mcts),
+            "uci" => Ok(Self::Uci),
+            _ => Err(format!(
+                "Unknown engine '{}'. Expected one of: dummy, minimax, alphabeta, iddfs, mat, pns, mcts, uci",
+                name
+            )),
+        }
+    }
+}
+
+// Engine configuration: the engine type followed by its options,
+// e.g. 'iddfs,depth=5,lmr=off,aspiration_window=on',
+// 'mcts,iterations=5,playout=softmax,cutoff=20,selection=puct,prior=heuristic,widening=2'
+// ('tree_export=tree.dot,export_depth=3,export_visits=10' writes the tree after each move)
+// or 'uci,path=/usr/games/stockfish,option.Hash=16'
+#[derive(Debug, Clone)]
+pub struct EngineSpec {
+    name: String,
+    kind: EngineKind,
+    depth: u8,
+    mcts_conf: config::MctsConfig,
+    alphabeta_features: config::AlphabetaFeatureConf,
+    iddfs_features: config::IddfsFeatureConf,
+    path_opt: Option<String>,
+    uci_options: Vec<(String, String)>,
+}
+impl EngineSpec {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut fields = spec.split(',').map(str::trim);
+        let kind = EngineKind::from_name(fields.next().unwrap_or_default())?;
+        let mut engine_spec = Self {
+            name: spec.trim().to_string(),
+            kind,
+            depth: DEFAULT_DEPTH,
+            mcts_conf: config::MctsConfig::new(DEFAULT_MCTS_ITERATIONS),
+            alphabeta_features: config::AlphabetaFeatureConf::default(),
+            iddfs_features: config::IddfsFeatureConf::default(),
+            path_opt: None,
+            uci_options: vec![],
+        };
+        for field in fields {
+            let (key, value) = field
+                .split_once('=')
+                .ok_or(format!("Expected 'option=value' instead of '{}'", field))?;
+            match (key, value) {
+                ("depth", _) => {
+                    engine_spec.depth = value
+                        .parse()
+                        .ok()
+                        .filter(|depth| *depth >= 1)
+                        .ok_or(format!("Invalid depth '{}'", value))?;
+                }
+                ("iterations", _) => {
+                    engine_spec.mcts_conf.iterations_per_move = value
+                        .parse()
+                        .map_err(|_| format!("Invalid number of iterations '{}'", value))?;
+                }
+                ("playout", _) => {
+                    engine_spec.mcts_conf.playout = config::PlayoutPolicy::from_name(value)?
+                }
+                ("cutoff", _) => {
+                    engine_spec.mcts_conf.playout_cutoff_plies = value
+                        .parse()
+                        .map_err(|_| format!("Invalid playout cutoff '{}'", value))?;
+                }
+                ("selection", _) => {
+                    engine_spec.mcts_conf.selection = config::SelectionFormula::from_name(value)?
+                }
+                ("prior", _) => {
+                    engine_spec.mcts_conf.prior = config::PriorSource::from_name(value)?
+                }
+                ("c_puct", _) => {
+                    engine_spec.mcts_conf.c_puct = value
+                        .parse()
+                        .map_err(|_| format!("Invalid c_puct '{}'", value))?;
+                }
+                ("nodes", _) => {
+                    engine_spec.mcts_conf.node_budget_opt = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid node budget '{}'", value))?,
+                    );
+                }
+                ("tree_reuse", "on" | "off") => engine_spec.mcts_conf.f_tree_reuse = value == "on",
+                ("threads", _) => {
+                    engine_spec.mcts_conf.threads = value
+                        .parse()
+                        .ok()
+                        .filter(|threads| *threads >= 1)
+                        .ok_or(format!("Invalid number of threads '{}'", value))?;
+                }
+                ("seed", _) => {
+                    engine_spec.mcts_conf.seed_opt = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid seed '{}'", value))?,
+                    );
+                }
+                ("widening", _) => {
+                    engine_spec.mcts_conf.widening_k = value
+                        .parse()
+                        .map_err(|_| format!("Invalid progressive widening '{}'", value))?;
+                }
+                ("tree_export", _) => {
+                    engine_spec.mcts_conf.tree_export_opt = Some(config::TreeExportConf::new(value))
+                }
+                ("export_depth" | "export_visits", _) => {
+                    let tree_export = engine_spec
+                        .mcts_conf
+                        .tree_export_opt
+                        .as_mut()
+                        .ok_or(format!("'{}' needs a tree_export path before it", key))?;
+                    let n = value
+                        .parse()
+                        .map_err(|_| format!("Invalid value '{}' for '{}'", value, key))?;
+                    if key == "export_depth" {
+                        tree_export.max_depth = n as usize;
+                    } else {
+                        tree_export.min_visits = n;
+                    }
+                }
+                ("path", _) => engine_spec.path_opt = Some(value.to_string()),
+                (option, _) if option.starts_with("option.") => engine_spec
+                    .uci_options
+                    .push((option["option.".len()..].to_string(), value.to_string())),
+                (feature, "on" | "off") => {
+                    let is_on = value == "on";
+                    // a feature of the alphabeta search or of the iterative deepening
+                    if engine_spec
+                        .alphabeta_features
+                        .set_feature(feature, is_on)
+                        .is_err()
+                    {
+                        engine_spec
+                            .iddfs_features
+                            .set_feature(feature, is_on)
+                            .map_err(|_| {
+                                format!(
+                                    "Unknown feature '{}'. Expected one of: {}, {}",
+                                    feature,
+                                    config::ALPHABETA_FEATURES.join(", "),
+                                    config::IDDFS_FEATURES.join(", ")
+                                )
+                            })?;
+                    }
+                }
+                _ => return Err(format!("Invalid option '{}'", field)),
+            }
+        }
+        if engine_spec.kind == EngineKind::Pns && engine_spec.depth > config::MAX_PNS_MATE_MOVES {
+            return Err(format!(
+                "Invalid depth '{}': the mate search is limited to {} moves",
+                engine_spec.depth,
+                config::MAX_PNS_MATE_MOVES
+            ));
+        }
+        if (engine_spec.kind == EngineKind::Uci) != engine_spec.path_opt.is_some() {
+            return Err(format!(
+                "'path' is required by uci engines only: '{}'",
+                spec
+            ));
+        }
+        Ok(engine_spec)
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn build_engine(
+        &self,
+        evaluator: Arc<dyn evaluator::Evaluator>,
+        zobrist_table: zobrist::Zobrist,
+        id_number: &str,
+    ) -> Result<Arc<dyn logic::Engine + Send + Sync>, String> {
+        let engine: Arc<dyn logic::Engine + Send + Sync> = match self.kind {
+            EngineKind::Dummy => {
+                let mut engine = engine_dummy::EngineDummy::new(None);
+                engine.set_id_number(id_number);
+                Arc::new(engine)
+            }
+            EngineKind::Minimax => {
+                let conf = config::MinimaxConf {
+                    max_depth: self.depth,
+                };
+                let mut engine = engine_minimax::EngineMinimax::new(None, zobrist_table, &conf);
+                engine.set_id_number(id_number);
+                engine.set_evaluator(evaluator);
+                Arc::new(engine)
+            }
+            EngineKind::Alphabeta => {
+                let mut engine = engine_alphabeta::EngineAlphaBeta::new(
+                    None,
+                    zobrist_table,
+                    self.depth,
+                    self.alphabeta_features.clone(),
+                    true,
+                );
+                engine.set_id_number(id_number);
+                engine.set_evaluator(evaluator);
+                Arc::new(engine)
+            }
+            EngineKind::Iddfs => {
+                let conf = config::IDDFSConfig::new(
+                    self.depth,
+                    self.iddfs_features.clone(),
+                    self.alphabeta_features.clone(),
+                );
+                let mut engine = engine_iddfs::EngineIddfs::new(None, zobrist_table, &conf);
+                engine.set_id_number(id_number);
+                engine.set_evaluator(evaluator);
+                Arc::new(engine)
+            }
+            EngineKind::Mat => {
+                let conf = config::MatConfig::new(self.depth);
+                let mut engine = engine_mat::EngineMat::new(None, zobrist_table, &conf);
+                engine.set_id_number(id_number);
+                Arc::new(engine)
+            }
+            EngineKind::Pns => {
+                // the depth is the maximum number of moves of the mate
+                let conf = config::PnsConfig::new(self.depth);
+                let mut engine = engine_pns::EnginePns::new(None, zobrist_table, &conf);
+                engine.set_id_number(id_number);
+                Arc::new(engine)
+            }
+            EngineKind::Mcts => {
+                let mut engine = engine_mcts::EngineMcts::new(None, zobrist_table, &self.mcts_conf);
+                engine.set_id_number(id_number);
+                engine.set_evaluator(evaluator);
+                Arc::new(engine)
+            }
+            EngineKind::Uci => {
+                let path = self.path_opt.as_deref().unwrap_or_default();
+                let mut engine =
+                    engine_external::ExternalUciEngine::start(path, &[], &self.uci_options, None)?;
+                engine.set_id_number(id_number);
+                engine.set_depth(self.depth);
+                Arc::new(engine)
+            }
+        };
+        Ok(engine)
+    }
+}
+
+// Match between two engines. Each opening is played twice, colours reversed:
+// engine1 has white in the even games.
+#[derive(Debug, Clone)]
+pub struct MatchConf {
+    pub engine1: EngineSpec,
+    pub engine2: EngineSpec,
+    pub n_games: u64,
+    pub openings: Vec<String>, // FEN
+    pub time_control: time_control::TimeControl,
+    // draw adjudicated after this number of half moves
+    pub max_plies: usize,
+    pub adjudication: adjudication::AdjudicationConf,
+    pub pgn_output_opt: Option<String>,
+    // number of game pairs played at the same time
+    pub concurrency: usize,
+    // with a SPRT, n_games is the maximum number of games
+    pub sprt_opt: Option<stats::Sprt>,
+}
+impl MatchConf {
+    pub fn new(engine1: EngineSpec, engine2: EngineSpec, n_games: u64) -> Self {
+        Self {
+            engine1,
+            engine2,
+            n_games,
+            openings: vec![fen::FEN_START_POSITION.to_string()],
+            time_control: time_control::TimeControl::fischer(10_000, 100),
+            max_plies: 400,
+            adjudication: adjudication::AdjudicationConf::default(),
+            pgn_output_opt: None,
+            concurrency: num_cpus::get(),
+            sprt_opt: None,
+        }
+    }
+}
+impl fmt::Display for MatchConf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "engine1: {}", self.engine1.name())?;
+        writeln!(f, "engine2: {}", self.engine2.name())?;
+        writeln!(f, "n_games: {}", self.n_games)?;
+        writeln!(f, "n_openings: {}", self.openings.len())?;
+        writeln!(f, "time: {}", self.time_control)?;
+        writeln!(f, "max_plies: {}", self.max_plies)?;
+        writeln!(f, "adjudication: {:?}", self.adjudication)?;
+        writeln!(f, "pgn: {:?}", self.pgn_output_opt)?;
+        writeln!(f, "concurrency: {}", self.concurrency)?;
+        match &self.sprt_opt {
+            Some(sprt) => writeln!(f, "{}", sprt),
+            None => writeln!(f, "no SPRT"),
+        }
+    }
+}
+
+// One position per line: FEN or EPD (the operations are ignored). '#' starts a comment.
+pub fn read_openings(path: &str) -> Result<Vec<String>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|err| format!("Cannot read '{}': {}", path, err))?;
+    let mut openings = vec![];
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 4 {
+            return Err(format!("Invalid opening '{}'", line));
+        }
+        let is_fen = fields.len() >= 6 && fields[4..6].iter().all(|f| f.parse::<u16>().is_ok());
+        let fen_str = if is_fen {
+            fields[..6].join(" ")
+        } else {
+            format!("{} 0 1", fields[..4].join(" "))
+        };
+        fen::Fen::decode(&fen_str)
+            .map_err(|err| format!("Invalid opening '{}': {:?}", line, err))?;
+        openings.push(fen_str);
+    }
+    if openings.is_empty() {
+        return Err(format!("No opening in '{}'", path));
+    }
+    Ok(openings)
+}
+
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    pub game_idx: u64,
+    pub engine1_color: square::Color,
+    pub end_game: game_state::EndGame,
+    pub pgn: pgn::PgnGame,
+}
+impl GameRecord {
+    // 1.0: engine1 wins, 0.5: draw, 0.0: engine2 wins
+    pub fn engine1_score(&self) -> f32 {
+        let white_score = match self.pgn.result() {
+            pgn::RESULT_WHITE_WINS => 1.0,
+            pgn::RESULT_BLACK_WINS => 0.0,
+            _ => 0.5,
+        };
+        match self.engine1_color {
+            square::Color::White => white_score,
+            square::Color::Black => 1.0 - white_score,
+        }
+    }
+}
+
+fn actix_error(err: actix::MailboxError) -> String {
+    format!("actix error: {:?}", err)
+}
+
+fn build_engine_actor(
+    spec: &EngineSpec,
+    evaluator: Arc<dyn evaluator::Evaluator>,
+    zobrist_table: zobrist::Zobrist,
+    id_number: &str,
+    adjudication: &adjudication::AdjudicationConf,
+) -> Result<dispatcher::EngineDispatcherActor, String> {
+    let engine = spec.build_engine(evaluator, zobrist_table, id_number)?;
+    let engine_actor = dispatcher::EngineDispatcher::new(engine, None, None).start();
+    engine_actor.do_send(dispatcher::handler_engine::EngineSetAdjudication(
+        adjudication.clone(),
+    ));
+    Ok(engine_actor)
+}
+
+// Play one game between engine actors driven by a game manager with clocks
+pub async fn play_game(
+    conf: &MatchConf,
+    game_idx: u64,
+    evaluator: Arc<dyn evaluator::Evaluator>,
+) -> Result<GameRecord, String> {
+    let opening = &conf.openings[(game_idx / 2) as usize % conf.openings.len()];
+    let (engine1_color, white_spec, black_spec) = if game_idx.is_multiple_of(2) {
+        (square::Color::White, &conf.engine1, &conf.engine2)
+    } else {
+        (square::Color::Black, &conf.engine2, &conf.engine1)
+    };
+    let mut game_manager = game_manager::GameManager::new(None);
+    let zobrist_table = game_manager.zobrist_table();
+    let white_engine = build_engine_actor(
+        white_spec,
+        evaluator.clone(),
+        zobrist_table.clone(),
+        "white",
+        &conf.adjudication,
+    )?;
+    let black_engine = build_engine_actor(
+        black_spec,
+        evaluator,
+        zobrist_table.clone(),
+        "black",
+        &conf.adjudication,
+    )?;
+    game_manager.set_players(player::Players::new(
+        player::Player::Computer {
+            engine: white_engine,
+        },
+        player::Player::Computer {
+            engine: black_engine,
+        },
+    ));
+    let game_manager_actor = game_manager.start();
+    let position = fen::Fen::decode(opening).map_err(|err| format!("{:?}", err))?;
+    game_manager_actor
+        .send(
+            game_manager::handler_uci_command::UciCommand::UpdatePosition(
+                opening.clone(),
+                position,
+            ),
+        )
+        .await
+        .map_err(actix_error)??;
+    let initial = game_manager_actor
+        .send(game_manager::handler_game::GetGameState)
+        .await
+        .map_err(actix_error)?
+        .ok_or("No position defined".to_string())?;
+    let white_clock = chessclock::Clock::with_time_control(
+        "white",
+        conf.time_control.clone(),
+        game_manager_actor.clone(),
+    )
+    .start();
+    let black_clock = chessclock::Clock::with_time_control(
+        "black",
+        conf.time_control.clone(),
+        game_manager_actor.clone(),
+    )
+    .start();
+    game_manager_actor
+        .send(game_manager::handler_clock::SetClocks::new(
+            Some(white_clock),
+            Some(black_clock),
+        ))
+        .await
+        .map_err(actix_error)?;
+    game_manager_actor
+        .send(game_manager::handler_engine::StartAutoPlay)
+        .await
+        .map_err(actix_error)??;
+    let mut is_max_plies = false;
+    let end_game = loop {
+        actix::clock::sleep(Duration::from_millis(POLLING_MS)).await;
+        let end_game = game_manager_actor
+            .send(game_manager::handler_game::GetEndGame)
+            .await
+            .map_err(actix_error)?
+            .unwrap_or_default();
+        if end_game != game_state::EndGame::None {
+            break end_game;
+        }
+        let history = game_manager_actor
+            .send(game_manager::handler_game::GetHistory)
+            .await
+            .map_err(actix_error)?
+            .unwrap_or_default();
+        if history.moves().len() >= conf.max_plies {
+            is_max_plies = true;
+            break end_game;
+        }
+    };
+    game_manager_actor
+        .send(game_manager::handler_engine::StopAutoPlay)
+        .await
+        .map_err(actix_error)?;
+    let history = game_manager_actor
+        .send(game_manager::handler_game::GetHistory)
+        .await
+        .map_err(actix_error)?
+        .unwrap_or_default();
+    game_manager_actor.do_send(game_manager::handler_game::StopActor);
+    // standard values of the PGN Termination tag
+    let (result, termination) = match &end_game {
+        _ if is_max_plies => (pgn::RESULT_DRAW, "adjudication"),
+        game_state::EndGame::TimeOutLost(_) | game_state::EndGame::TimeOutDraw => {
+            (pgn::result(&end_game), "time forfeit")
+        }
+        // resign or draw agreed by the adjudication rules of the engines
+        game_state::EndGame::Resign(_) | game_state::EndGame::NullAgreement => {
+            (pgn::result(&end_game), "adjudication")
+        }
+        _ => (pgn::result(&end_game), "normal"),
+    };
+    let mut pgn = pgn::PgnGame::build(&initial, history.moves(), &zobrist_table, result)?;
+    pgn.set_tag("Event", PGN_EVENT);
+    pgn.set_tag("Site", "?");
+    pgn.set_tag("Date", &chrono::Local::now().format("%Y.%m.%d").to_string());
+    pgn.set_tag("Round", &(game_idx + 1).to_string());
+    pgn.set_tag("White", white_spec.name());
+    pgn.set_tag("Black", black_spec.name());
+    pgn.set_tag("Termination", termination);
+    Ok(GameRecord {
+        game_idx,
+        engine1_color,
+        end_game,
+        pgn,
+    })
+}
+
+fn append_pgn(path: &str, pgn: &pgn::PgnGame) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|err| format!("Cannot open '{}': {}", path, err))?;
+    writeln!(file, "{}", pgn).map_err(|err| format!("Write error: {}", err))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MatchResult {
+    pub wdl: stats::Wdl,
+    pub pentanomial: stats::Pentanomial,
+    pub llr_opt: Option<f64>,
+    pub sprt_status_opt: Option<stats::SprtStatus>,
+}
+
+// The two games of an opening, colours reversed (the last pair of an odd match has one game)
+async fn play_pair(
+    conf: Arc<MatchConf>,
+    pair_idx: u64,
+    evaluator: Arc<dyn evaluator::Evaluator>,
+) -> Result<Vec<GameRecord>, String> {
+    let mut records = vec![];
+    for game_idx in (2 * pair_idx..2 * pair_idx + 2).filter(|idx| *idx < conf.n_games) {
+        records.push(play_game(&conf, game_idx, evaluator.clone()).await?);
+    }
+    Ok(records)
+}
+
+// Play the game pairs in parallel: each engine searches in its own thread.
+// With a SPRT, the results are reported after each pair and the match stops
+// as soon as a bound is crossed (the pairs still running are aborted).
+pub async fn run_match(
+    conf: &MatchConf,
+    evaluator: Arc<dyn evaluator::Evaluator>,
+) -> Result<MatchResult, String> {
+    let conf = Arc::new(conf.clone());
+    let n_pairs = conf.n_games.div_ceil(2);
+    let mut result = MatchResult::default();
+    let mut pairs = tokio::task::JoinSet::new();
+    let mut next_pair_idx = 0;
+    loop {
+        while next_pair_idx < n_pairs && pairs.len() < conf.concurrency.max(1) {
+            pairs.spawn_local(play_pair(conf.clone(), next_pair_idx, evaluator.clone()));
+            next_pair_idx += 1;
+        }
+        let Some(joined) = pairs.join_next().await else {
+            break;
+        };
+        let records = joined.map_err(|err| format!("game pair failed: {}", err))??;
+        for record in &records {
+            if let Some(path) = &conf.pgn_output_opt {
+                append_pgn(path, &record.pgn)?;
+            }
+            result.wdl.add(record.engine1_score());
+            println!(
+                "Game {} ({} vs {}): {} {:?}",
+                record.game_idx + 1,
+                record.pgn.tag("White").unwrap_or_default(),
+                record.pgn.tag("Black").unwrap_or_default(),
+                record.pgn.result(),
+                record.end_game
+            );
+        }
+        if let [record1, record2] = records.as_slice() {
+            result
+                .pentanomial
+                .add_pair(record1.engine1_score(), record2.engine1_score());
+        }
+        if let Some(sprt) = &conf.sprt_opt {
+            let llr = sprt.llr(&result.pentanomial);
+            let (lower, upper) = sprt.bounds();
+            println!(
+                "Pairs: {} {} LLR: {:.2} [{:.2}, {:.2}]",
+                result.pentanomial.n_pairs(),
+                result.pentanomial,
+                llr,
+                lower,
+                upper
+            );
+            result.llr_opt = Some(llr);
+            let status = sprt.status(llr);
+            result.sprt_status_opt = Some(status);
+            if status != stats::SprtStatus::Continue {
+                pairs.abort_all();
+                break;
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engine_spec() {
+        let spec = EngineSpec::parse("iddfs,depth=5,lmr=off,aspiration_window=on").unwrap();
+        assert_eq!(spec.kind, EngineKind::Iddfs);
+        assert_eq!(spec.depth, 5);
+        assert!(!spec.alphabeta_features.f_lmr);
+        assert!(spec.iddfs_features.f_aspiration_window);
+        assert_eq!(spec.name(), "iddfs,depth=5,lmr=off,aspiration_window=on");
+        assert!(EngineSpec::parse("stockfish").is_err());
+        assert!(EngineSpec::parse("iddfs,depth=0").is_err());
+        assert!(EngineSpec::parse("iddfs,unknown=on").is_err());
+        assert!(EngineSpec::parse("iddfs,lmr").is_err());
+        let spec = EngineSpec::parse("uci,path=/usr/games/stockfish,option.Hash=16").unwrap();
+        assert_eq!(spec.kind, EngineKind::Uci);
+        assert_eq!(spec.path_opt.as_deref(), Some("/usr/games/stockfish"));
+        assert_eq!(
+            spec.uci_options,
+            vec![("Hash".to_string(), "16".to_string())]
+        );
+        let spec = EngineSpec::parse("mcts,iterations=50,playout=capture_check,cutoff=20").unwrap();
+        assert_eq!(spec.mcts_conf.iterations_per_move, 50);
+        assert_eq!(
+            spec.mcts_conf.playout,
+            config::PlayoutPolicy::CaptureCheckBiased
+        );
+        assert_eq!(spec.mcts_conf.playout_cutoff_plies, 20);
+        assert!(EngineSpec::parse("mcts,playout=greedy").is_err());
+        let spec = EngineSpec::parse("mcts,selection=puct,prior=heuristic,c_puct=2.5").unwrap();
+        assert_eq!(spec.mcts_conf.selection, config::SelectionFormula::Puct);
+        assert_eq!(spec.mcts_conf.prior, config::PriorSource::Heuristic);
+        assert_eq!(spec.mcts_conf.c_puct, 2.5);
+        assert!(EngineSpec::parse("mcts,selection=uct").is_err());
+        let spec = EngineSpec::parse("mcts,nodes=300,tree_reuse=off,threads=4,seed=7").unwrap();
+        assert_eq!(spec.mcts_conf.node_budget_opt, Some(300));
+        assert_eq!(spec.mcts_conf.threads, 4);
+        assert_eq!(spec.mcts_conf.seed_opt, Some(7));
+        assert!(EngineSpec::parse("mcts,threads=0").is_err());
+        assert!(!spec.mcts_conf.f_tree_reuse);
+        let spec = EngineSpec::parse("mcts,tree_export=tree.json,export_depth=2,export_visits=10")
+            .unwrap();
+        let tree_export = spec.mcts_conf.tree_export_opt.unwrap();
+        assert_eq!(tree_export.format, config::TreeExportFormat::Json);
+        assert_eq!(tree_export.max_depth, 2);
+        assert_eq!(tree_export.min_visits, 10);
+        assert!(EngineSpec::parse("mcts,export_depth=2").is_err());
+        let spec = EngineSpec::parse("pns,depth=3").unwrap();
+        assert_eq!(spec.kind, EngineKind::Pns);
+        assert_eq!(spec.depth, 3);
+        assert!(EngineSpec::parse("pns,depth=128").is_err());
+        assert!(EngineSpec::parse("iddfs,depth=128").is_ok());
+        assert!(EngineSpec::parse("uci").is_err());
+        assert!(EngineSpec::parse("iddfs,path=/usr/games/stockfish").is_err());
+    }
+
+    #[test]
+    fn test_read_openings() {
+        let path = std::env::temp_dir()
+            .join(format!("chess_rust_openings_{}.epd", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        fs::write(
+            &path,
+            "# openings\n\
+             rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1\n\
+             rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - id \"open\";\n",
+        )
+        .unwrap();
+        let openings = read_openings(&path).unwrap();
+        assert_eq!(openings.len(), 2);
+        assert_eq!(
+            openings[1],
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1"
+        );
+        let _ = fs::remove_file(&path);
+    }
+
+    #[actix::test]
+    async fn test_run_match() {
+        let engine = EngineSpec::parse("dummy").unwrap();
+        let mut conf = MatchConf::new(engine.clone(), engine, 2);
+        conf.max_plies = 20;
+        let pgn_output = std::env::temp_dir()
+            .join(format!("chess_rust_match_{}.pgn", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let _ = fs::remove_file(&pgn_output);
+        conf.pgn_output_opt = Some(pgn_output.clone());
+        let evaluator: Arc<dyn evaluator::Evaluator> =
+            Arc::new(evaluator::ClassicEvaluator::default());
+        let result = run_match(&conf, evaluator).await.unwrap();
+        assert_eq!(result.wdl.n_games(), 2);
+        assert_eq!(result.pentanomial.n_pairs(), 1);
+        assert_eq!(result.sprt_status_opt, None);
+        let content = fs::read_to_string(&pgn_output).unwrap();
+        assert_eq!(content.matches("[Event ").count(), 2);
+        assert!(content.contains("[Round \"2\"]"));
+        let _ = fs::remove_file(&pgn_output);
+    }
+
+    #[actix::test]
+    async fn test_run_sprt() {
+        let engine = EngineSpec::parse("dummy").unwrap();
+        let mut conf = MatchConf::new(engine.clone(), engine, 6);
+        conf.max_plies = 10;
+        conf.concurrency = 2;
+        // huge error rates: the first pair with a winner crosses a bound
+        conf.sprt_opt = Some(stats::Sprt::new(-500.0, 500.0, 0.45, 0.45).unwrap());
+        let evaluator: Arc<dyn evaluator::Evaluator> =
+            Arc::new(evaluator::ClassicEvaluator::default());
+        let result = run_match(&conf, evaluator).await.unwrap();
+        assert!(result.pentanomial.n_pairs() >= 1);
+        assert!(result.pentanomial.n_pairs() <= 3);
+        assert!(result.llr_opt.is_some());
+        assert!(result.sprt_status_opt.is_some());
+    }
+}
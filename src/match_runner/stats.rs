@@ -0,0 +1,348 @@
+use std::fmt;
+
+// quantile of the normal distribution for a 95% confidence interval
+const Z_95: f64 = 1.959964;
+// count given to the empty cells of the pentanomial for the SPRT
+const EMPTY_CELL_COUNT: f64 = 0.5;
+
+pub fn elo_from_score(score: f64) -> f64 {
+    400.0 * (score / (1.0 - score)).log10()
+}
+
+// Results of engine1 against engine2
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Wdl {
+    pub wins: u64,
+    pub draws: u64,
+    pub losses: u64,
+}
+impl Wdl {
+    // score of one game: 1.0 win, 0.5 draw, 0.0 loss
+    pub fn add(&mut self, score: f32) {
+        if score > 0.75 {
+            self.wins += 1;
+        } else if score < 0.25 {
+            self.losses += 1;
+        } else {
+            self.draws += 1;
+        }
+    }
+    pub fn n_games(&self) -> u64 {
+        self.wins + self.draws + self.losses
+    }
+    pub fn score(&self) -> Option<f64> {
+        let n_games = self.n_games();
+        if n_games == 0 {
+            return None;
+        }
+        Some((self.wins as f64 + self.draws as f64 / 2.0) / n_games as f64)
+    }
+    // None when no game has been played or when all games are won or lost
+    pub fn elo_diff(&self) -> Option<f64> {
+        self.score()
+            .filter(|score| *score > 0.0 && *score < 1.0)
+            .map(elo_from_score)
+    }
+    // half width of the 95% confidence interval of the Elo difference
+    pub fn elo_error_margin(&self) -> Option<f64> {
+        let score = self.score()?;
+        let n_games = self.n_games() as f64;
+        let variance = (self.wins as f64 * (1.0 - score).powi(2)
+            + self.draws as f64 * (0.5 - score).powi(2)
+            + self.losses as f64 * score.powi(2))
+            / n_games;
+        let std_error = (variance / n_games).sqrt();
+        let low = score - Z_95 * std_error;
+        let high = score + Z_95 * std_error;
+        if low <= 0.0 || high >= 1.0 {
+            return None;
+        }
+        Some((elo_from_score(high) - elo_from_score(low)) / 2.0)
+    }
+    // the same results seen by engine2
+    pub fn reversed(&self) -> Self {
+        Self {
+            wins: self.losses,
+            draws: self.draws,
+            losses: self.wins,
+        }
+    }
+}
+
+// Results of game pairs (same opening, colours reversed) seen by engine1.
+// counts[i]: number of pairs where engine1 scored i/2 points (0: two losses, 4: two wins)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Pentanomial {
+    pub counts: [u64; 5],
+}
+impl Pentanomial {
+    // scores of the two games of the pair: 1.0 win, 0.5 draw, 0.0 loss
+    pub fn add_pair(&mut self, score1: f32, score2: f32) {
+        let idx = ((score1 + score2) * 2.0).round().clamp(0.0, 4.0) as usize;
+        self.counts[idx] += 1;
+    }
+    pub fn n_pairs(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+    // mean and variance of the score of a pair (between 0.0 and 1.0)
+    pub fn mean_variance(&self) -> Option<(f64, f64)> {
+        if self.n_pairs() == 0 {
+            return None;
+        }
+        Some(mean_variance(self.counts.map(|count| count as f64)))
+    }
+}
+fn mean_variance(counts: [f64; 5]) -> (f64, f64) {
+    let n_pairs: f64 = counts.iter().sum();
+    let probas = counts.map(|count| count / n_pairs);
+    let mean: f64 = (0..5).map(|idx| probas[idx] * idx as f64 / 4.0).sum();
+    let variance = (0..5)
+        .map(|idx| probas[idx] * (idx as f64 / 4.0 - mean).powi(2))
+        .sum();
+    (mean, variance)
+}
+impl fmt::Display for Pentanomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts: Vec<String> = self.counts.iter().map(|count| count.to_string()).collect();
+        write!(f, "[{}]", counts.join(", "))
+    }
+}
+
+pub fn score_from_elo(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SprtStatus {
+    Continue,
+    // engine1 is not stronger than elo0
+    AcceptH0,
+    // engine1 is stronger than elo1
+    AcceptH1,
+}
+
+// Sequential probability ratio test of H0: elo = elo0 against H1: elo = elo1
+// (logistic Elo of engine1 against engine2). alpha and beta are the error rates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64, alpha: f64, beta: f64) -> Result<Self, String> {
+        if elo0 >= elo1 {
+            return Err(format!(
+                "elo0 ({}) must be lower than elo1 ({})",
+                elo0, elo1
+            ));
+        }
+        for (name, value) in [("alpha", alpha), ("beta", beta)] {
+            if value <= 0.0 || value >= 0.5 {
+                return Err(format!("{} ({}) must be in ]0, 0.5[", name, value));
+            }
+        }
+        Ok(Self {
+            elo0,
+            elo1,
+            alpha,
+            beta,
+        })
+    }
+    // (lower, upper): H0 is accepted below lower, H1 above upper
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+    // Log likelihood ratio with a normal approximation of the pair scores (0.0 without pair).
+    // Empty cells get a small count so that the variance is never zero.
+    pub fn llr(&self, pentanomial: &Pentanomial) -> f64 {
+        let n_pairs = pentanomial.n_pairs();
+        if n_pairs == 0 {
+            return 0.0;
+        }
+        let counts = pentanomial.counts.map(|count| match count {
+            0 => EMPTY_CELL_COUNT,
+            _ => count as f64,
+        });
+        let (mean, variance) = mean_variance(counts);
+        let s0 = score_from_elo(self.elo0);
+        let s1 = score_from_elo(self.elo1);
+        n_pairs as f64 * (s1 - s0) * (2.0 * mean - s0 - s1) / (2.0 * variance)
+    }
+    pub fn status(&self, llr: f64) -> SprtStatus {
+        let (lower, upper) = self.bounds();
+        if llr <= lower {
+            SprtStatus::AcceptH0
+        } else if llr >= upper {
+            SprtStatus::AcceptH1
+        } else {
+            SprtStatus::Continue
+        }
+    }
+}
+impl fmt::Display for Sprt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SPRT elo0={} elo1={} alpha={} beta={}",
+            self.elo0, self.elo1, self.alpha, self.beta
+        )
+    }
+}
+
+// W/D/L table of a match followed by the Elo difference of engine1
+pub struct MatchTable<'a> {
+    engine1: &'a str,
+    engine2: &'a str,
+    wdl: Wdl,
+}
+impl<'a> MatchTable<'a> {
+    pub fn new(engine1: &'a str, engine2: &'a str, wdl: Wdl) -> Self {
+        Self {
+            engine1,
+            engine2,
+            wdl,
+        }
+    }
+}
+impl fmt::Display for MatchTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.engine1.len().max(self.engine2.len()).max(6);
+        writeln!(
+            f,
+            "{:<width$} {:>6} {:>6} {:>6} {:>7}",
+            "engine", "W", "D", "L", "score"
+        )?;
+        for (name, wdl) in [
+            (self.engine1, self.wdl),
+            (self.engine2, self.wdl.reversed()),
+        ] {
+            writeln!(
+                f,
+                "{:<width$} {:>6} {:>6} {:>6} {:>6.1}%",
+                name,
+                wdl.wins,
+                wdl.draws,
+                wdl.losses,
+                wdl.score().unwrap_or(0.0) * 100.0
+            )?;
+        }
+        match (self.wdl.elo_diff(), self.wdl.elo_error_margin()) {
+            (Some(elo), Some(margin)) => {
+                writeln!(f, "Elo difference: {:+.1} +/- {:.1} (95%)", elo, margin)
+            }
+            (Some(elo), None) => writeln!(f, "Elo difference: {:+.1}", elo),
+            _ => writeln!(f, "Elo difference: not available"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elo_diff() {
+        let wdl = Wdl {
+            wins: 30,
+            draws: 40,
+            losses: 30,
+        };
+        assert_eq!(wdl.elo_diff(), Some(0.0));
+        let wdl = Wdl {
+            wins: 60,
+            draws: 20,
+            losses: 20,
+        };
+        // score 70%
+        assert!((wdl.elo_diff().unwrap() - 147.2).abs() < 0.1);
+        assert!((wdl.reversed().elo_diff().unwrap() + 147.2).abs() < 0.1);
+        let margin = wdl.elo_error_margin().unwrap();
+        assert!(margin > 50.0 && margin < 80.0);
+        // more games: smaller margin
+        let wdl_x4 = Wdl {
+            wins: 240,
+            draws: 80,
+            losses: 80,
+        };
+        assert!(wdl_x4.elo_error_margin().unwrap() < margin / 1.5);
+        let wdl = Wdl {
+            wins: 5,
+            draws: 0,
+            losses: 0,
+        };
+        assert_eq!(wdl.elo_diff(), None);
+        assert_eq!(Wdl::default().score(), None);
+    }
+
+    #[test]
+    fn test_pentanomial() {
+        let mut pentanomial = Pentanomial::default();
+        pentanomial.add_pair(1.0, 0.0);
+        pentanomial.add_pair(0.5, 0.5);
+        pentanomial.add_pair(1.0, 0.5);
+        pentanomial.add_pair(0.0, 0.0);
+        assert_eq!(pentanomial.counts, [1, 0, 2, 1, 0]);
+        assert_eq!(pentanomial.n_pairs(), 4);
+        let (mean, variance) = pentanomial.mean_variance().unwrap();
+        assert!((mean - 0.4375).abs() < 1e-9);
+        assert!((variance - 0.07421875).abs() < 1e-9);
+        assert_eq!(pentanomial.to_string(), "[1, 0, 2, 1, 0]");
+    }
+
+    #[test]
+    fn test_sprt() {
+        let sprt = Sprt::new(0.0, 5.0, 0.05, 0.05).unwrap();
+        let (lower, upper) = sprt.bounds();
+        assert!((lower + 2.944).abs() < 1e-3);
+        assert!((upper - 2.944).abs() < 1e-3);
+        assert!(Sprt::new(5.0, 0.0, 0.05, 0.05).is_err());
+        assert!(Sprt::new(0.0, 5.0, 0.0, 0.05).is_err());
+        assert_eq!(sprt.llr(&Pentanomial::default()), 0.0);
+        // balanced results: the LLR goes down to H0
+        let even = Pentanomial {
+            counts: [10, 20, 40, 20, 10],
+        };
+        assert!(sprt.llr(&even) < 0.0);
+        let even_x100 = Pentanomial {
+            counts: even.counts.map(|count| count * 100),
+        };
+        assert_eq!(sprt.status(sprt.llr(&even)), SprtStatus::Continue);
+        assert_eq!(sprt.status(sprt.llr(&even_x100)), SprtStatus::AcceptH0);
+        // engine1 stronger: about +50 Elo
+        let stronger = Pentanomial {
+            counts: [50, 150, 400, 250, 150],
+        };
+        assert!(sprt.llr(&stronger) > 0.0);
+        assert_eq!(sprt.status(sprt.llr(&stronger)), SprtStatus::AcceptH1);
+        // all pairs won or all pairs drawn: no variance
+        let sprt = Sprt::new(0.0, 200.0, 0.1, 0.1).unwrap();
+        let all_wins = Pentanomial {
+            counts: [0, 0, 0, 0, 10],
+        };
+        assert_eq!(sprt.status(sprt.llr(&all_wins)), SprtStatus::AcceptH1);
+        let all_draws = Pentanomial {
+            counts: [0, 0, 10, 0, 0],
+        };
+        assert_eq!(sprt.status(sprt.llr(&all_draws)), SprtStatus::AcceptH0);
+    }
+
+    #[test]
+    fn test_match_table() {
+        let mut wdl = Wdl::default();
+        for score in [1.0, 0.5, 0.5, 0.0, 1.0] {
+            wdl.add(score);
+        }
+        let table = MatchTable::new("iddfs,depth=4", "iddfs,depth=3", wdl).to_string();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].starts_with("iddfs,depth=4"));
+        assert!(lines[1].ends_with("2      2      1   60.0%"));
+        assert!(lines[2].ends_with("1      2      2   40.0%"));
+        assert!(lines[3].starts_with("Elo difference: +70.4"));
+    }
+}
@@ -0,0 +1,488 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::entity::game::component::bitboard::{self, zobrist};
+use crate::entity::game::component::game_state;
+use crate::entity::game::component::square::{self, Switch};
+use crate::ui::notation::san;
+
+// a first move is a try when it is refuted by at most this number of defences
+const MAX_TRY_REFUTATIONS: usize = 1;
+
+// Composed problems: the side to move in the FEN starts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stipulation {
+    // '#n': the side to move mates in n moves against any defence
+    Mate(u8),
+    // 'h#n': both sides cooperate, the side to move is mated on the n-th move of the other side
+    HelpMate(u8),
+    // 's#n': the side to move forces the other side to mate it in n moves
+    SelfMate(u8),
+}
+impl Stipulation {
+    pub fn parse(stipulation: &str) -> Result<Self, String> {
+        let stipulation = stipulation.trim();
+        let (build, n_str): (fn(u8) -> Self, &str) =
+            if let Some(n_str) = stipulation.strip_prefix("h#") {
+                (Self::HelpMate, n_str)
+            } else if let Some(n_str) = stipulation.strip_prefix("s#") {
+                (Self::SelfMate, n_str)
+            } else if let Some(n_str) = stipulation.strip_prefix('#') {
+                (Self::Mate, n_str)
+            } else {
+                return Err(format!(
+                    "Unknown stipulation '{}'. Expected #n, h#n or s#n",
+                    stipulation
+                ));
+            };
+        n_str
+            .parse::<u8>()
+            .ok()
+            .filter(|n| (1..=20).contains(n))
+            .map(build)
+            .ok_or(format!(
+                "Invalid number of moves in stipulation '{}'",
+                stipulation
+            ))
+    }
+    pub fn n_moves(&self) -> u8 {
+        match self {
+            Self::Mate(n) | Self::HelpMate(n) | Self::SelfMate(n) => *n,
+        }
+    }
+    fn max_plies(&self) -> u8 {
+        match self {
+            Self::Mate(n) => 2 * n - 1,
+            Self::HelpMate(n) | Self::SelfMate(n) => 2 * n,
+        }
+    }
+}
+impl fmt::Display for Stipulation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mate(n) => write!(f, "#{}", n),
+            Self::HelpMate(n) => write!(f, "h#{}", n),
+            Self::SelfMate(n) => write!(f, "s#{}", n),
+        }
+    }
+}
+
+// A move and what follows it in the solution:
+// the defences after a move of the starting side, its continuations after a defence
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variation {
+    pub b_move: bitboard::BitBoardMove,
+    pub san: String,
+    pub replies: Vec<Variation>,
+}
+
+// Key or try. The replies of a try are its refutations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FirstMove {
+    pub variation: Variation,
+    // None when the move gives check or ends the problem, empty in case of zugzwang
+    pub threat_opt: Option<Vec<Variation>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    pub stipulation: Stipulation,
+    pub keys: Vec<FirstMove>,
+    pub tries: Vec<FirstMove>,
+}
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.stipulation)?;
+        if self.keys.is_empty() {
+            writeln!(f, "no solution")?;
+        }
+        if let Stipulation::HelpMate(_) = self.stipulation {
+            for key in &self.keys {
+                write_help_lines(f, &key.variation, &mut vec![])?;
+            }
+            return Ok(());
+        }
+        for try_move in &self.tries {
+            writeln!(
+                f,
+                "1.{}?{}",
+                try_move.variation.san,
+                threat_as_str(&try_move.threat_opt, false)
+            )?;
+            for refutation in &try_move.variation.replies {
+                writeln!(f, "    but 1...{}!", refutation.san)?;
+            }
+        }
+        for key in &self.keys {
+            writeln!(
+                f,
+                "1.{}!{}",
+                key.variation.san,
+                threat_as_str(&key.threat_opt, true)
+            )?;
+            write_defences(f, &key.variation, 1, "    ")?;
+        }
+        Ok(())
+    }
+}
+
+// a key without threat puts the defender in zugzwang, a try without threat waits
+fn threat_as_str(threat_opt: &Option<Vec<Variation>>, is_key: bool) -> String {
+    match threat_opt {
+        None => "".to_string(),
+        Some(threats) if threats.is_empty() && is_key => " zugzwang".to_string(),
+        Some(threats) if threats.is_empty() => "".to_string(),
+        Some(threats) => {
+            let threats: Vec<String> = threats
+                .iter()
+                .map(|threat| format!("2.{}", threat.san))
+                .collect();
+            format!(" threat: {}", threats.join(" "))
+        }
+    }
+}
+
+// defences of a move of the starting side, each one followed by its continuations
+fn write_defences(
+    f: &mut fmt::Formatter<'_>,
+    variation: &Variation,
+    move_number: usize,
+    indent: &str,
+) -> fmt::Result {
+    for defence in &variation.replies {
+        let prefix = format!("{}{}...{}", indent, move_number, defence.san);
+        if defence.replies.is_empty() {
+            writeln!(f, "{}", prefix)?;
+        }
+        for (i, continuation) in defence.replies.iter().enumerate() {
+            if i == 0 {
+                writeln!(f, "{} {}.{}", prefix, move_number + 1, continuation.san)?;
+            } else {
+                // dual: another continuation after the same defence
+                let padding = " ".repeat(prefix.chars().count());
+                writeln!(f, "{} {}.{}", padding, move_number + 1, continuation.san)?;
+            }
+            write_defences(f, continuation, move_number + 1, &format!("{}    ", indent))?;
+        }
+    }
+    Ok(())
+}
+
+// one line per helpmate solution: '1.Kb8 Rh8#'
+fn write_help_lines(
+    f: &mut fmt::Formatter<'_>,
+    variation: &Variation,
+    line: &mut Vec<String>,
+) -> fmt::Result {
+    let ply = line.len();
+    if ply.is_multiple_of(2) {
+        line.push(format!("{}.{}", ply / 2 + 1, variation.san));
+    } else {
+        line.push(variation.san.clone());
+    }
+    if variation.replies.is_empty() {
+        writeln!(f, "{}", line.join(" "))?;
+    }
+    for reply in &variation.replies {
+        write_help_lines(f, reply, line)?;
+    }
+    line.pop();
+    Ok(())
+}
+
+pub fn solve(
+    game: &game_state::GameState,
+    stipulation: Stipulation,
+    zobrist_table: &zobrist::Zobrist,
+) -> Solution {
+    let start_color = game.bit_position().bit_position_status().player_turn();
+    let mut solver = ProblemSolver {
+        stipulation,
+        zobrist_table,
+        start_color,
+        goal_color: match stipulation {
+            Stipulation::Mate(_) => start_color.switch(),
+            Stipulation::HelpMate(_) | Stipulation::SelfMate(_) => start_color,
+        },
+        memo: HashMap::new(),
+    };
+    solver.solve(game)
+}
+
+struct ProblemSolver<'a> {
+    stipulation: Stipulation,
+    zobrist_table: &'a zobrist::Zobrist,
+    start_color: square::Color,
+    // color of the player to be mated
+    goal_color: square::Color,
+    // result of a position for a number of half moves left
+    memo: HashMap<(zobrist::ZobristHash, u8), bool>,
+}
+impl ProblemSolver<'_> {
+    fn solve(&mut self, game: &game_state::GameState) -> Solution {
+        let mut game = game.clone();
+        let mut keys = vec![];
+        let mut tries = vec![];
+        let moves = game.gen_moves();
+        for b_move in &moves {
+            let san = san::san_to_str(
+                b_move,
+                &moves,
+                &san::Lang::LangEn,
+                &game,
+                self.zobrist_table,
+                false,
+            );
+            self.play(&mut game, *b_move);
+            let san = self.san_with_mate(&game, san.info());
+            if self.after_move(&mut game, 1) {
+                let replies = self.variations(&mut game, 1);
+                let threat_opt = self.threat(&mut game);
+                keys.push(FirstMove {
+                    variation: Variation {
+                        b_move: *b_move,
+                        san,
+                        replies,
+                    },
+                    threat_opt,
+                });
+            } else if self.stipulation.max_plies() > 1
+                && !matches!(self.stipulation, Stipulation::HelpMate(_))
+                && game.end_game() == game_state::EndGame::None
+            {
+                let refutations = self.refutations(&mut game);
+                if refutations.len() <= MAX_TRY_REFUTATIONS {
+                    let threat_opt = self.threat(&mut game);
+                    tries.push(FirstMove {
+                        variation: Variation {
+                            b_move: *b_move,
+                            san,
+                            replies: refutations,
+                        },
+                        threat_opt,
+                    });
+                }
+            }
+            game.unmake_move();
+        }
+        Solution {
+            stipulation: self.stipulation,
+            keys,
+            tries,
+        }
+    }
+    fn play(&self, game: &mut game_state::GameState, b_move: bitboard::BitBoardMove) {
+        game.make_move(&b_move, self.zobrist_table);
+        game.update_endgame_status();
+    }
+    fn san_with_mate(&self, game: &game_state::GameState, san: &str) -> String {
+        if game.end_game().is_mat() {
+            format!("{}#", san.trim_end_matches('+'))
+        } else {
+            san.to_string()
+        }
+    }
+    // the attacker needs one good move, every defence must fail: in a helpmate both sides attack
+    fn is_attacker(&self, game: &game_state::GameState) -> bool {
+        matches!(self.stipulation, Stipulation::HelpMate(_))
+            || game.bit_position().bit_position_status().player_turn() == self.start_color
+    }
+    // is the stipulation fulfilled after a move, plies half moves after the start
+    fn after_move(&mut self, game: &mut game_state::GameState, plies: u8) -> bool {
+        match game.end_game() {
+            game_state::EndGame::Mat(color) => color == self.goal_color,
+            game_state::EndGame::None => {
+                plies < self.stipulation.max_plies() && self.search(game, plies)
+            }
+            _ => false,
+        }
+    }
+    fn search(&mut self, game: &mut game_state::GameState, plies: u8) -> bool {
+        let key = (game.last_hash(), self.stipulation.max_plies() - plies);
+        if let Some(result) = self.memo.get(&key) {
+            return *result;
+        }
+        let is_attacker = self.is_attacker(game);
+        let mut result = !is_attacker;
+        for b_move in game.gen_moves() {
+            self.play(game, b_move);
+            let is_fulfilled = self.after_move(game, plies + 1);
+            game.unmake_move();
+            if is_fulfilled == is_attacker {
+                result = is_attacker;
+                break;
+            }
+        }
+        self.memo.insert(key, result);
+        result
+    }
+    // the solution tree below a position of a solution: the moves fulfilling the stipulation
+    fn variations(&mut self, game: &mut game_state::GameState, plies: u8) -> Vec<Variation> {
+        let mut variations = vec![];
+        if game.end_game() != game_state::EndGame::None {
+            return variations;
+        }
+        let moves = game.gen_moves();
+        for b_move in &moves {
+            let san = san::san_to_str(
+                b_move,
+                &moves,
+                &san::Lang::LangEn,
+                game,
+                self.zobrist_table,
+                false,
+            );
+            self.play(game, *b_move);
+            if self.after_move(game, plies + 1) {
+                let san = self.san_with_mate(game, san.info());
+                let replies = self.variations(game, plies + 1);
+                variations.push(Variation {
+                    b_move: *b_move,
+                    san,
+                    replies,
+                });
+            }
+            game.unmake_move();
+        }
+        variations
+    }
+    // the defences after a first move that fails
+    fn refutations(&mut self, game: &mut game_state::GameState) -> Vec<Variation> {
+        let mut refutations = vec![];
+        let moves = game.gen_moves();
+        for b_move in &moves {
+            let san = san::san_to_str(
+                b_move,
+                &moves,
+                &san::Lang::LangEn,
+                game,
+                self.zobrist_table,
+                false,
+            );
+            self.play(game, *b_move);
+            if !self.after_move(game, 2) {
+                let san = self.san_with_mate(game, san.info());
+                refutations.push(Variation {
+                    b_move: *b_move,
+                    san,
+                    replies: vec![],
+                });
+            }
+            game.unmake_move();
+        }
+        refutations
+    }
+    // what the first move threatens if the defender could pass
+    fn threat(&mut self, game: &mut game_state::GameState) -> Option<Vec<Variation>> {
+        if matches!(self.stipulation, Stipulation::HelpMate(_))
+            || game.end_game() != game_state::EndGame::None
+            || game.check_status().is_check()
+            || self.stipulation.max_plies() <= 2
+        {
+            return None;
+        }
+        game.play_null_move(self.zobrist_table);
+        game.update_endgame_status();
+        let variations = self.variations(game, 2);
+        game.play_back_null_move();
+        Some(variations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::notation::fen::{self, EncodeUserInput};
+
+    fn solve_fen(fen: &str, stipulation: &str) -> Solution {
+        let zobrist_table = zobrist::Zobrist::new();
+        let position = fen::Fen::decode(fen).expect("Failed to decode FEN");
+        let game = game_state::GameState::new(position, &zobrist_table);
+        solve(
+            &game,
+            Stipulation::parse(stipulation).unwrap(),
+            &zobrist_table,
+        )
+    }
+
+    fn sans(variations: &[Variation]) -> Vec<&str> {
+        variations
+            .iter()
+            .map(|variation| variation.san.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_stipulation() {
+        assert_eq!(Stipulation::parse("#2"), Ok(Stipulation::Mate(2)));
+        assert_eq!(Stipulation::parse("h#3"), Ok(Stipulation::HelpMate(3)));
+        assert_eq!(Stipulation::parse("s#1"), Ok(Stipulation::SelfMate(1)));
+        assert_eq!(Stipulation::SelfMate(4).to_string(), "s#4");
+        assert!(Stipulation::parse("#0").is_err());
+        assert!(Stipulation::parse("=2").is_err());
+    }
+
+    #[test]
+    fn test_mate_in_one() {
+        let solution = solve_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "#1");
+        let keys: Vec<&str> = solution
+            .keys
+            .iter()
+            .map(|key| key.variation.san.as_str())
+            .collect();
+        assert_eq!(keys, vec!["Ra8#"]);
+        assert!(solution.tries.is_empty());
+        assert_eq!(solution.to_string(), "#1\n1.Ra8#!\n");
+    }
+
+    #[test]
+    fn test_mate_in_two() {
+        let solution = solve_fen("7k/8/5K2/8/8/8/8/6R1 w - - 0 1", "#2");
+        let key = solution
+            .keys
+            .iter()
+            .find(|key| key.variation.san == "Kf7")
+            .unwrap();
+        // the only defence, then the mate
+        assert_eq!(sans(&key.variation.replies), vec!["Kh7"]);
+        assert_eq!(sans(&key.variation.replies[0].replies), vec!["Rh1#"]);
+        let threats = key.threat_opt.as_ref().unwrap();
+        assert_eq!(sans(threats), vec!["Rh1#"]);
+        let output = solution.to_string();
+        assert!(output.contains("1.Kf7! threat: 2.Rh1#\n    1...Kh7 2.Rh1#\n"));
+        // every try has one refutation
+        assert!(solution
+            .tries
+            .iter()
+            .all(|try_move| try_move.variation.replies.len() == 1));
+    }
+
+    #[test]
+    fn test_selfmate() {
+        // black has only f2 left, which mates: the rooks must keep the black king in its box
+        let solution = solve_fen("3R4/8/8/8/R7/4kp1p/7P/5BKB w - - 0 1", "s#1");
+        assert!(solution.keys.iter().any(|key| key.variation.san == "Rb4"));
+        assert!(solution
+            .keys
+            .iter()
+            .all(|key| sans(&key.variation.replies) == vec!["f2#"]));
+        let output = solution.to_string();
+        assert!(output.contains("1.Rb4!\n    1...f2#\n"));
+        // the rook leaves the d file: the black king escapes
+        assert!(output.contains("1.Rb8?\n    but 1...Kd2!\n"));
+    }
+
+    #[test]
+    fn test_helpmate() {
+        let solution = solve_fen("k7/8/1K6/8/8/8/8/7R b - - 0 1", "h#1");
+        assert_eq!(solution.to_string(), "h#1\n1.Kb8 Rh8#\n");
+        assert!(solution.tries.is_empty());
+    }
+
+    #[test]
+    fn test_no_solution() {
+        let solution = solve_fen(fen::FEN_START_POSITION, "#1");
+        assert!(solution.keys.is_empty());
+        assert_eq!(solution.to_string(), "#1\nno solution\n");
+    }
+}
@@ -0,0 +1,462 @@
+pub mod crosstable;
+
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::entity::clock::component::time_control;
+use crate::entity::engine::component::adjudication;
+use crate::entity::engine::component::evaluation::evaluator;
+use crate::entity::game::component::square;
+use crate::match_runner::{self, EngineSpec, MatchConf};
+use crate::ui::notation::pgn;
+
+const PGN_EVENT: &str = "chess_rust tournament";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TournamentFormat {
+    // every engine plays every other engine
+    RoundRobin,
+    // the first engine plays all the others
+    Gauntlet,
+}
+impl TournamentFormat {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "round-robin" => Ok(Self::RoundRobin),
+            "gauntlet" => Ok(Self::Gauntlet),
+            _ => Err(format!(
+                "Unknown tournament format '{}'. Expected one of: round-robin, gauntlet",
+                name
+            )),
+        }
+    }
+}
+impl fmt::Display for TournamentFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TournamentFormat::RoundRobin => write!(f, "round-robin"),
+            TournamentFormat::Gauntlet => write!(f, "gauntlet"),
+        }
+    }
+}
+
+// A game of the schedule: game_idx is the index of the game in the pairing
+// (the colours and the opening depend on it as in a match)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScheduledGame {
+    pub id: u64,
+    pub engine1: usize,
+    pub engine2: usize,
+    pub game_idx: u64,
+}
+
+// Result of a played game, one line of the results file
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameResult {
+    pub id: u64,
+    pub white: usize,
+    pub black: usize,
+    pub result: String,
+}
+impl GameResult {
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.id, self.white, self.black, self.result
+        )
+    }
+    fn from_line(line: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let parse = |idx: usize| -> Result<u64, String> {
+            fields
+                .get(idx)
+                .and_then(|field| field.parse().ok())
+                .ok_or(format!("Invalid result line '{}'", line))
+        };
+        match fields.get(3) {
+            Some(result) if fields.len() == 4 => Ok(Self {
+                id: parse(0)?,
+                white: parse(1)? as usize,
+                black: parse(2)? as usize,
+                result: result.to_string(),
+            }),
+            _ => Err(format!("Invalid result line '{}'", line)),
+        }
+    }
+    // points of white and black. None for an unfinished game.
+    pub fn points(&self) -> Option<(f32, f32)> {
+        match self.result.as_str() {
+            pgn::RESULT_WHITE_WINS => Some((1.0, 0.0)),
+            pgn::RESULT_BLACK_WINS => Some((0.0, 1.0)),
+            pgn::RESULT_DRAW => Some((0.5, 0.5)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TournamentConf {
+    pub engines: Vec<EngineSpec>,
+    pub format: TournamentFormat,
+    pub games_per_pairing: u64,
+    pub openings: Vec<String>, // FEN
+    pub time_control: time_control::TimeControl,
+    pub max_plies: usize,
+    pub adjudication: adjudication::AdjudicationConf,
+    // number of games played at the same time
+    pub concurrency: usize,
+    pub pgn_output_opt: Option<String>,
+    // finished games: the tournament is resumed from this file
+    pub results_path: String,
+}
+impl TournamentConf {
+    pub fn new(
+        engines: Vec<EngineSpec>,
+        format: TournamentFormat,
+        games_per_pairing: u64,
+        results_path: String,
+    ) -> Result<Self, String> {
+        if engines.len() < 2 {
+            return Err("A tournament needs at least two engines".to_string());
+        }
+        // game settings of a match
+        let match_conf = MatchConf::new(engines[0].clone(), engines[1].clone(), 0);
+        Ok(Self {
+            engines,
+            format,
+            games_per_pairing,
+            openings: match_conf.openings,
+            time_control: match_conf.time_control,
+            max_plies: match_conf.max_plies,
+            adjudication: match_conf.adjudication,
+            concurrency: match_conf.concurrency,
+            pgn_output_opt: None,
+            results_path,
+        })
+    }
+    pub fn pairings(&self) -> Vec<(usize, usize)> {
+        let n_engines = self.engines.len();
+        match self.format {
+            TournamentFormat::RoundRobin => (0..n_engines)
+                .flat_map(|engine1| (engine1 + 1..n_engines).map(move |engine2| (engine1, engine2)))
+                .collect(),
+            TournamentFormat::Gauntlet => (1..n_engines).map(|engine2| (0, engine2)).collect(),
+        }
+    }
+    // the pairings are interleaved: the partial results stay balanced
+    pub fn schedule(&self) -> Vec<ScheduledGame> {
+        let pairings = self.pairings();
+        let mut games = vec![];
+        for game_idx in 0..self.games_per_pairing {
+            for (engine1, engine2) in &pairings {
+                games.push(ScheduledGame {
+                    id: games.len() as u64,
+                    engine1: *engine1,
+                    engine2: *engine2,
+                    game_idx,
+                });
+            }
+        }
+        games
+    }
+    fn match_conf(&self, game: &ScheduledGame) -> MatchConf {
+        let mut conf = MatchConf::new(
+            self.engines[game.engine1].clone(),
+            self.engines[game.engine2].clone(),
+            self.games_per_pairing,
+        );
+        conf.openings = self.openings.clone();
+        conf.time_control = self.time_control.clone();
+        conf.max_plies = self.max_plies;
+        conf.adjudication = self.adjudication.clone();
+        conf
+    }
+    // first lines of the results file: a file can only resume the same tournament
+    fn header(&self) -> Vec<String> {
+        let mut header = vec![
+            format!("#format\t{}", self.format),
+            format!("#games\t{}", self.games_per_pairing),
+        ];
+        for engine in &self.engines {
+            header.push(format!("#engine\t{}", engine.name()));
+        }
+        header
+    }
+}
+impl fmt::Display for TournamentConf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "format: {}", self.format)?;
+        for (idx, engine) in self.engines.iter().enumerate() {
+            writeln!(f, "engine {}: {}", idx + 1, engine.name())?;
+        }
+        writeln!(f, "games_per_pairing: {}", self.games_per_pairing)?;
+        writeln!(f, "n_openings: {}", self.openings.len())?;
+        writeln!(f, "time: {}", self.time_control)?;
+        writeln!(f, "max_plies: {}", self.max_plies)?;
+        writeln!(f, "adjudication: {:?}", self.adjudication)?;
+        writeln!(f, "concurrency: {}", self.concurrency)?;
+        writeln!(f, "pgn: {:?}", self.pgn_output_opt)?;
+        writeln!(f, "results: {}", self.results_path)
+    }
+}
+
+// Results already in the file. A new file is created with the header of the tournament.
+// The last line of a run interrupted while writing it is removed from the file.
+pub fn load_results(conf: &TournamentConf) -> Result<Vec<GameResult>, String> {
+    let path = &conf.results_path;
+    let header = conf.header();
+    if !Path::new(path).exists() {
+        fs::write(path, header.join("\n") + "\n")
+            .map_err(|err| format!("Cannot create '{}': {}", path, err))?;
+        return Ok(vec![]);
+    }
+    let content =
+        fs::read_to_string(path).map_err(|err| format!("Cannot read '{}': {}", path, err))?;
+    let file_header: Vec<String> = content
+        .lines()
+        .filter(|line| line.starts_with('#'))
+        .map(str::to_string)
+        .collect();
+    if file_header != header {
+        return Err(format!(
+            "'{}' contains the results of another tournament",
+            path
+        ));
+    }
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let mut results = vec![];
+    let mut complete_len = 0;
+    for (idx, line) in lines.iter().enumerate() {
+        let text = line.trim_end_matches('\n');
+        if !text.starts_with('#') && !text.trim().is_empty() {
+            let is_last = idx + 1 == lines.len();
+            match GameResult::from_line(text) {
+                Ok(result) if line.ends_with('\n') => results.push(result),
+                Err(err) if !is_last => return Err(err),
+                // no end of line or a field cut short: the game is played again
+                _ => {
+                    println!("Incomplete last line '{}' removed from '{}'", text, path);
+                    OpenOptions::new()
+                        .write(true)
+                        .open(path)
+                        .and_then(|file| file.set_len(complete_len as u64))
+                        .map_err(|err| format!("Cannot truncate '{}': {}", path, err))?;
+                    break;
+                }
+            }
+        }
+        complete_len += line.len();
+    }
+    Ok(results)
+}
+
+fn append_line(path: &str, line: &str) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|err| format!("Cannot open '{}': {}", path, err))?;
+    writeln!(file, "{}", line).map_err(|err| format!("Write error: {}", err))
+}
+
+// Play the games not yet in the results file, several game managers at the same time
+pub async fn run_tournament(
+    conf: &TournamentConf,
+    evaluator: Arc<dyn evaluator::Evaluator>,
+) -> Result<crosstable::Crosstable, String> {
+    let mut results = load_results(conf)?;
+    let played: HashSet<u64> = results.iter().map(|result| result.id).collect();
+    let mut pending: VecDeque<ScheduledGame> = conf
+        .schedule()
+        .into_iter()
+        .filter(|game| !played.contains(&game.id))
+        .collect();
+    println!(
+        "{} games already played, {} games to play",
+        results.len(),
+        pending.len()
+    );
+    let mut games = tokio::task::JoinSet::new();
+    loop {
+        while games.len() < conf.concurrency.max(1) {
+            let Some(game) = pending.pop_front() else {
+                break;
+            };
+            let match_conf = conf.match_conf(&game);
+            let evaluator = evaluator.clone();
+            games.spawn_local(async move {
+                let record = match_runner::play_game(&match_conf, game.game_idx, evaluator).await;
+                (game, record)
+            });
+        }
+        let Some(joined) = games.join_next().await else {
+            break;
+        };
+        let (game, record) = joined.map_err(|err| format!("game failed: {}", err))?;
+        let mut record = record?;
+        let (white, black) = match record.engine1_color {
+            square::Color::White => (game.engine1, game.engine2),
+            square::Color::Black => (game.engine2, game.engine1),
+        };
+        record.pgn.set_tag("Event", PGN_EVENT);
+        record.pgn.set_tag("Round", &(game.id + 1).to_string());
+        let result = GameResult {
+            id: game.id,
+            white,
+            black,
+            result: record.pgn.result().to_string(),
+        };
+        // the game is recorded before its PGN: a resumed tournament never writes a PGN twice
+        append_line(&conf.results_path, &result.to_line())?;
+        if let Some(path) = &conf.pgn_output_opt {
+            append_line(path, &record.pgn.to_string())?;
+        }
+        println!(
+            "Game {} ({} vs {}): {} {:?}",
+            game.id + 1,
+            conf.engines[white].name(),
+            conf.engines[black].name(),
+            result.result,
+            record.end_game
+        );
+        results.push(result);
+    }
+    let names = conf
+        .engines
+        .iter()
+        .map(|engine| engine.name().to_string())
+        .collect();
+    Ok(crosstable::Crosstable::new(names, &results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("chess_rust_{}_{}", name, std::process::id()))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn conf(format: TournamentFormat, results_path: String) -> TournamentConf {
+        let engines = ["dummy", "minimax,depth=1", "alphabeta,depth=1"]
+            .iter()
+            .map(|spec| EngineSpec::parse(spec).unwrap())
+            .collect();
+        TournamentConf::new(engines, format, 2, results_path).unwrap()
+    }
+
+    #[test]
+    fn test_schedule() {
+        let conf_rr = conf(TournamentFormat::RoundRobin, temp_path("unused"));
+        assert_eq!(conf_rr.pairings(), vec![(0, 1), (0, 2), (1, 2)]);
+        let schedule = conf_rr.schedule();
+        assert_eq!(schedule.len(), 6);
+        assert_eq!(
+            schedule[4],
+            ScheduledGame {
+                id: 4,
+                engine1: 0,
+                engine2: 2,
+                game_idx: 1
+            }
+        );
+        let conf_gauntlet = conf(TournamentFormat::Gauntlet, temp_path("unused"));
+        assert_eq!(conf_gauntlet.pairings(), vec![(0, 1), (0, 2)]);
+        assert_eq!(conf_gauntlet.schedule().len(), 4);
+        assert!(
+            TournamentConf::new(vec![], TournamentFormat::Gauntlet, 2, "".to_string()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_results_file() {
+        let path = temp_path("results.txt");
+        let _ = fs::remove_file(&path);
+        let conf_rr = conf(TournamentFormat::RoundRobin, path.clone());
+        assert!(load_results(&conf_rr).unwrap().is_empty());
+        let result = GameResult {
+            id: 3,
+            white: 2,
+            black: 0,
+            result: "1/2-1/2".to_string(),
+        };
+        append_line(&path, &result.to_line()).unwrap();
+        assert_eq!(load_results(&conf_rr).unwrap(), vec![result]);
+        // another tournament cannot use this file
+        let conf_gauntlet = conf(TournamentFormat::Gauntlet, path.clone());
+        assert!(load_results(&conf_gauntlet).is_err());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_results_file_partial_line() {
+        let path = temp_path("results_partial.txt");
+        let _ = fs::remove_file(&path);
+        let conf_rr = conf(TournamentFormat::RoundRobin, path.clone());
+        assert!(load_results(&conf_rr).unwrap().is_empty());
+        let result = GameResult {
+            id: 0,
+            white: 0,
+            black: 1,
+            result: "1-0".to_string(),
+        };
+        append_line(&path, &result.to_line()).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        // the run was interrupted while writing the result of game 1
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "1\t1").unwrap();
+        assert_eq!(load_results(&conf_rr).unwrap(), vec![result.clone()]);
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        // a line which does not parse
+        writeln!(file, "1\t1").unwrap();
+        assert_eq!(load_results(&conf_rr).unwrap(), vec![result.clone()]);
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        // the next result starts on a new line
+        let result1 = GameResult {
+            id: 1,
+            white: 1,
+            black: 0,
+            result: "1/2-1/2".to_string(),
+        };
+        append_line(&path, &result1.to_line()).unwrap();
+        assert_eq!(load_results(&conf_rr).unwrap(), vec![result, result1]);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[actix::test]
+    async fn test_run_tournament() {
+        let path = temp_path("tournament.txt");
+        let pgn_path = temp_path("tournament.pgn");
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&pgn_path);
+        let mut conf_rr = conf(TournamentFormat::RoundRobin, path.clone());
+        conf_rr.max_plies = 10;
+        conf_rr.concurrency = 3;
+        conf_rr.pgn_output_opt = Some(pgn_path.clone());
+        let evaluator: Arc<dyn evaluator::Evaluator> =
+            Arc::new(evaluator::ClassicEvaluator::default());
+        let crosstable = run_tournament(&conf_rr, evaluator.clone()).await.unwrap();
+        assert_eq!(crosstable.n_games(), 6);
+        let n_pgn_games = || {
+            fs::read_to_string(&pgn_path)
+                .unwrap()
+                .matches("[Round ")
+                .count()
+        };
+        assert_eq!(n_pgn_games(), 6);
+        // resumed: nothing left to play
+        let resumed = run_tournament(&conf_rr, evaluator).await.unwrap();
+        assert_eq!(resumed.n_games(), 6);
+        assert_eq!(resumed.to_string(), crosstable.to_string());
+        assert_eq!(n_pgn_games(), 6);
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&pgn_path);
+    }
+}
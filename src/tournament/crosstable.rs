@@ -0,0 +1,124 @@
+use std::fmt;
+
+use super::GameResult;
+use crate::match_runner::stats;
+
+// Results of a tournament: points[i][j] are the points of engine i against engine j
+#[derive(Debug, Clone)]
+pub struct Crosstable {
+    names: Vec<String>,
+    points: Vec<Vec<f32>>,
+    games: Vec<Vec<u64>>,
+    wdl: Vec<stats::Wdl>,
+}
+impl Crosstable {
+    pub fn new(names: Vec<String>, results: &[GameResult]) -> Self {
+        let n_engines = names.len();
+        let mut crosstable = Self {
+            names,
+            points: vec![vec![0.0; n_engines]; n_engines],
+            games: vec![vec![0; n_engines]; n_engines],
+            wdl: vec![stats::Wdl::default(); n_engines],
+        };
+        for result in results {
+            let Some((white_points, black_points)) = result.points() else {
+                continue;
+            };
+            for (engine, opponent, points) in [
+                (result.white, result.black, white_points),
+                (result.black, result.white, black_points),
+            ] {
+                crosstable.points[engine][opponent] += points;
+                crosstable.games[engine][opponent] += 1;
+                crosstable.wdl[engine].add(points);
+            }
+        }
+        crosstable
+    }
+    pub fn n_games(&self) -> u64 {
+        self.games.iter().flatten().sum::<u64>() / 2
+    }
+    pub fn score(&self, engine: usize) -> f32 {
+        self.points[engine].iter().sum()
+    }
+    // engines sorted by score
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.names.len()).collect();
+        ranking.sort_by(|a, b| self.score(*b).total_cmp(&self.score(*a)));
+        ranking
+    }
+}
+impl fmt::Display for Crosstable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.names.iter().map(String::len).max().unwrap_or(0).max(6);
+        let ranking = self.ranking();
+        write!(f, "{:>3} {:<width$}", "#", "engine")?;
+        for rank in 1..=ranking.len() {
+            write!(f, " {:>5}", rank)?;
+        }
+        writeln!(f, " {:>6} {:>5}  elo", "score", "games")?;
+        for (rank, engine) in ranking.iter().enumerate() {
+            write!(f, "{:>3} {:<width$}", rank + 1, self.names[*engine])?;
+            for opponent in &ranking {
+                if opponent == engine {
+                    write!(f, " {:>5}", "-")?;
+                } else if self.games[*engine][*opponent] == 0 {
+                    write!(f, " {:>5}", ".")?;
+                } else {
+                    write!(f, " {:>5.1}", self.points[*engine][*opponent])?;
+                }
+            }
+            let wdl = &self.wdl[*engine];
+            write!(f, " {:>6.1} {:>5}", self.score(*engine), wdl.n_games())?;
+            // Elo against the average of the opponents
+            match (wdl.elo_diff(), wdl.elo_error_margin()) {
+                (Some(elo), Some(margin)) => writeln!(f, "  {:+.0} +/- {:.0}", elo, margin)?,
+                (Some(elo), None) => writeln!(f, "  {:+.0}", elo)?,
+                _ => writeln!(f, "  -")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(id: u64, white: usize, black: usize, result: &str) -> GameResult {
+        GameResult {
+            id,
+            white,
+            black,
+            result: result.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_crosstable() {
+        let names = vec![
+            "dummy".to_string(),
+            "iddfs,depth=4".to_string(),
+            "mcts".to_string(),
+        ];
+        let results = vec![
+            result(0, 1, 0, "1-0"),
+            result(1, 0, 1, "0-1"),
+            result(2, 2, 0, "1-0"),
+            result(3, 1, 2, "1-0"),
+            result(4, 2, 1, "*"),
+        ];
+        let crosstable = Crosstable::new(names, &results);
+        assert_eq!(crosstable.n_games(), 4);
+        assert_eq!(crosstable.score(1), 3.0);
+        assert_eq!(crosstable.score(0), 0.0);
+        assert_eq!(crosstable.ranking(), vec![1, 2, 0]);
+        let table = crosstable.to_string();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("  # engine"));
+        assert!(lines[1].starts_with("  1 iddfs,depth=4     -   1.0   2.0    3.0     3"));
+        assert!(lines[2].ends_with("  +0"));
+        assert!(lines[3].starts_with("  3 dummy           0.0   0.0     -    0.0     3"));
+    }
+}
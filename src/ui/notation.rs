@@ -1,4 +1,5 @@
 pub mod epd;
 pub mod fen;
 pub mod long_notation;
+pub mod pgn;
 pub mod san;
@@ -0,0 +1,211 @@
+use std::fmt;
+
+use crate::entity::game::component::bitboard::{zobrist, BitBoardMove};
+use crate::entity::game::component::game_state::{EndGame, GameState};
+use crate::entity::game::component::square;
+use crate::ui::notation::fen::{self, EncodeUserInput};
+use crate::ui::notation::{long_notation, san};
+
+const LINE_LENGTH: usize = 80;
+// Seven Tag Roster: always written first, in this order
+const STR_TAGS: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+pub const RESULT_WHITE_WINS: &str = "1-0";
+pub const RESULT_BLACK_WINS: &str = "0-1";
+pub const RESULT_DRAW: &str = "1/2-1/2";
+pub const RESULT_UNKNOWN: &str = "*";
+
+pub fn result(end_game: &EndGame) -> &'static str {
+    match end_game {
+        EndGame::None => RESULT_UNKNOWN,
+        EndGame::Mat(square::Color::Black)
+        | EndGame::TimeOutLost(square::Color::Black)
+        | EndGame::Resign(square::Color::Black) => RESULT_WHITE_WINS,
+        EndGame::Mat(square::Color::White)
+        | EndGame::TimeOutLost(square::Color::White)
+        | EndGame::Resign(square::Color::White) => RESULT_BLACK_WINS,
+        _ => RESULT_DRAW,
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PgnGame {
+    tags: Vec<(String, String)>,
+    moves: Vec<String>, // SAN
+    first_move_number: u16,
+    is_black_first: bool,
+    result: String,
+}
+impl PgnGame {
+    // b_moves are played from the initial position
+    pub fn build(
+        initial: &GameState,
+        b_moves: &[BitBoardMove],
+        zobrist_table: &zobrist::Zobrist,
+        result: &str,
+    ) -> Result<Self, String> {
+        let mut pgn = Self {
+            first_move_number: initial.bit_position().bit_position_status().n_moves(),
+            is_black_first: !initial
+                .bit_position()
+                .bit_position_status()
+                .player_turn_white(),
+            result: result.to_string(),
+            ..Default::default()
+        };
+        let fen =
+            fen::Fen::encode(&initial.bit_position().to()).map_err(|err| format!("{:?}", err))?;
+        if fen != fen::FEN_START_POSITION {
+            pgn.set_tag("SetUp", "1");
+            pgn.set_tag("FEN", &fen);
+        }
+        let mut game = initial.clone();
+        for b_move in b_moves {
+            let legal_moves = game.gen_moves();
+            if !legal_moves.contains(b_move) {
+                return Err(format!("Illegal move {:?} in the game", b_move));
+            }
+            let mut san_move = san::san_to_str(
+                b_move,
+                &legal_moves,
+                &san::Lang::LangEn,
+                &game,
+                zobrist_table,
+                false,
+            )
+            .info()
+            .clone();
+            game.play_moves(
+                &[long_notation::LongAlgebricNotationMove::build_from_b_move(
+                    *b_move,
+                )],
+                zobrist_table,
+                None,
+                false,
+            )?;
+            game.update_endgame_status();
+            if game.end_game().is_mat() {
+                san_move = format!("{}#", san_move.trim_end_matches('+'));
+            }
+            pgn.moves.push(san_move);
+        }
+        Ok(pgn)
+    }
+    // replace the value of the tag if it is already defined
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, tag_value)) => *tag_value = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+    pub fn moves(&self) -> &Vec<String> {
+        &self.moves
+    }
+    pub fn result(&self) -> &str {
+        &self.result
+    }
+    fn movetext(&self) -> Vec<String> {
+        let mut tokens = vec![];
+        let mut move_number = self.first_move_number;
+        let mut is_white = !self.is_black_first;
+        for (idx, san_move) in self.moves.iter().enumerate() {
+            if is_white {
+                tokens.push(format!("{}.", move_number));
+            } else if idx == 0 {
+                tokens.push(format!("{}...", move_number));
+            }
+            tokens.push(san_move.clone());
+            if !is_white {
+                move_number += 1;
+            }
+            is_white = !is_white;
+        }
+        tokens.push(self.result.clone());
+        tokens
+    }
+}
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+impl fmt::Display for PgnGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for name in STR_TAGS {
+            let value = match name {
+                "Result" => self.result.as_str(),
+                _ => self.tag(name).unwrap_or("?"),
+            };
+            writeln!(f, "[{} \"{}\"]", name, escape(value))?;
+        }
+        for (name, value) in self
+            .tags
+            .iter()
+            .filter(|(name, _)| !STR_TAGS.contains(&name.as_str()))
+        {
+            writeln!(f, "[{} \"{}\"]", name, escape(value))?;
+        }
+        writeln!(f)?;
+        let mut line = String::new();
+        for token in self.movetext() {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_LENGTH {
+                writeln!(f, "{}", line)?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        writeln!(f, "{}", line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::notation::fen::Fen;
+
+    fn build(fen_str: &str, moves: &str) -> PgnGame {
+        let zobrist_table = zobrist::Zobrist::new();
+        let position = Fen::decode(fen_str).unwrap();
+        let initial = GameState::new(position, &zobrist_table);
+        let mut game = initial.clone();
+        let mut b_moves = vec![];
+        for move_str in moves.split_whitespace() {
+            let long_move =
+                long_notation::LongAlgebricNotationMove::build_from_str(move_str).unwrap();
+            b_moves.extend(
+                game.play_moves(&[long_move], &zobrist_table, None, false)
+                    .unwrap(),
+            );
+        }
+        game.update_endgame_status();
+        PgnGame::build(&initial, &b_moves, &zobrist_table, result(&game.end_game())).unwrap()
+    }
+
+    #[test]
+    fn test_pgn_fool_mate() {
+        let mut pgn = build(fen::FEN_START_POSITION, "f2f3 e7e5 g2g4 d8h4");
+        pgn.set_tag("White", "engine \"1\"");
+        assert_eq!(pgn.result(), RESULT_BLACK_WINS);
+        let text = pgn.to_string();
+        assert!(text.starts_with("[Event \"?\"]\n"));
+        assert!(text.contains("[White \"engine \\\"1\\\"\"]\n"));
+        assert!(text.contains("[Result \"0-1\"]\n"));
+        assert!(!text.contains("FEN"));
+        assert!(text.ends_with("\n\n1. f3 e5 2. g4 Qh4# 0-1\n"));
+    }
+
+    #[test]
+    fn test_pgn_from_position() {
+        let pgn = build("4k3/8/8/8/8/8/4P3/4K3 b - - 0 30", "e8d7 e2e4");
+        let text = pgn.to_string();
+        assert!(text.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 30\"]\n"));
+        assert!(text.ends_with("\n\n30... Kd7 31. e4 *\n"));
+    }
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use chess_actix::entity::clock::component::time_control;
+use chess_actix::entity::engine::component::engine_external;
+use chess_actix::entity::engine::component::engine_logic::Engine;
+use chess_actix::entity::engine::component::evaluation::evaluator;
+use chess_actix::match_runner;
+
+// our own binary is the external engine
+const ENGINE_PATH: &str = env!("CARGO_BIN_EXE_chess_rust");
+
+#[test]
+fn test_external_engine_handshake() {
+    let engine = engine_external::ExternalUciEngine::start(
+        ENGINE_PATH,
+        &[],
+        &[("Move Overhead".to_string(), "100".to_string())],
+        None,
+    )
+    .unwrap();
+    assert!(engine.id().name().starts_with("Alphabeta"));
+    assert_eq!(engine.id().author(), "Christophe le cam");
+}
+
+#[actix::test]
+async fn test_match_against_external_engine() {
+    let external = match_runner::EngineSpec::parse(&format!("uci,path={}", ENGINE_PATH)).unwrap();
+    let dummy = match_runner::EngineSpec::parse("dummy").unwrap();
+    let mut conf = match_runner::MatchConf::new(external, dummy, 2);
+    conf.time_control = time_control::TimeControl::fischer(2_000, 50);
+    conf.max_plies = 12;
+    let evaluator: Arc<dyn evaluator::Evaluator> = Arc::new(evaluator::ClassicEvaluator::default());
+    let result = match_runner::run_match(&conf, evaluator).await.unwrap();
+    assert_eq!(result.wdl.n_games(), 2);
+    // the external engine does not lose on time against a random player
+    assert_eq!(result.wdl.losses, 0);
+}
+
+#[actix::test]
+async fn test_match_against_crashing_engine() {
+    // answers the handshake, then terminates on the first search
+    let script = std::env::temp_dir().join(format!("crashing_engine_{}.sh", std::process::id()));
+    std::fs::write(
+        &script,
+        "#!/bin/sh\n\
+         while read line; do\n\
+         case \"$line\" in\n\
+         uci) echo 'id name Crashing'; echo 'uciok';;\n\
+         isready) echo 'readyok';;\n\
+         go*|quit) exit 0;;\n\
+         esac\n\
+         done\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::os::unix::fs::PermissionsExt::from_mode(0o755)).unwrap();
+    let crashing =
+        match_runner::EngineSpec::parse(&format!("uci,path={}", script.display())).unwrap();
+    let dummy = match_runner::EngineSpec::parse("dummy").unwrap();
+    let mut conf = match_runner::MatchConf::new(crashing, dummy, 1);
+    conf.time_control = time_control::TimeControl::fischer(60_000, 0);
+    conf.max_plies = 8;
+    let evaluator: Arc<dyn evaluator::Evaluator> = Arc::new(evaluator::ClassicEvaluator::default());
+    let result = match_runner::run_match(&conf, evaluator).await.unwrap();
+    let _ = std::fs::remove_file(&script);
+    assert_eq!(result.wdl.n_games(), 1);
+    // fallback moves are played: the game is not lost on time
+    assert_eq!(result.wdl.losses, 0);
+}